@@ -9,18 +9,21 @@
 use indexmap::set::IndexSet as HashSet;
 
 use autocxx_parser::{
-    directive_names::{EXTERN_RUST_FUN, EXTERN_RUST_TYPE},
-    RustFun, RustPath,
+    directive_names::{EXTERN_RUST_FUN, EXTERN_RUST_TRAIT, EXTERN_RUST_TYPE},
+    RustFun, RustPath, RustTrait,
 };
 use itertools::Itertools;
-use proc_macro2::Ident;
+use proc_macro2::{Ident, Span};
+use quote::ToTokens;
+use std::path::PathBuf;
 use syn::visit_mut::{visit_type_mut, VisitMut};
 use syn::{
     parse_quote, punctuated::Punctuated, AssocConst, AssocType, Attribute, Expr, ExprAssign,
     ExprAwait, ExprBinary, ExprBlock, ExprBreak, ExprCast, ExprConst, ExprField, ExprGroup,
-    ExprLet, ExprParen, ExprReference, ExprTry, ExprUnary, ImplItem, Item, ItemEnum, ItemStruct,
-    LocalInit, Pat, PatReference, PatSlice, PatTuple, Path, ReturnType, Signature, Stmt, TraitItem,
-    Type, TypeArray, TypeGroup, TypeParamBound, TypeParen, TypePtr, TypeReference, TypeSlice,
+    ExprLet, ExprLit, ExprParen, ExprReference, ExprTry, ExprUnary, FnArg, ImplItem, Item,
+    ItemEnum, ItemStruct, ItemTrait, Lit, LocalInit, Meta, MetaNameValue, Pat, PatReference,
+    PatSlice, PatTuple, Path, Receiver, ReturnType, Signature, Stmt, TraitItem, TraitItemFn, Type,
+    TypeArray, TypeGroup, TypeParamBound, TypeParen, TypePtr, TypeReference, TypeSlice,
 };
 use thiserror::Error;
 
@@ -29,27 +32,146 @@ pub(super) struct Discoveries {
     pub(super) cpp_list: HashSet<String>,
     pub(super) extern_rust_funs: Vec<RustFun>,
     pub(super) extern_rust_types: Vec<RustPath>,
+    pub(super) extern_rust_traits: Vec<RustTrait>,
+    /// Files read for out-of-line mods, which the build depends upon.
+    pub(super) mod_files: Vec<PathBuf>,
 }
 
 #[derive(Error, Debug)]
 pub enum DiscoveryErr {
-    #[error("#[extern_rust_function] was attached to a method in an impl block that was too complex for autocxx. autocxx supports only \"impl X {{...}}\" where X is a single identifier, not a path or more complex type.")]
+    #[error("#[extern_rust_function] was attached to a method in an impl block that was too complex for autocxx. autocxx supports only \"impl X {{...}}\" where X is a path to a type within this file, such as Foo, super::Foo or crate::a::Foo, not a more complex type.")]
     FoundExternRustFunOnTypeWithoutClearReceiver,
     #[error("#[extern_rust_function] was attached to a method taking a receiver by value.")]
     NoParameterOnMethod,
-    #[error("#[extern_rust_function] was in an impl block nested wihtin another block. This is only supported in the outermost mod of a file, alongside the include_cpp!.")]
-    FoundExternRustFunWithinMod,
+    #[error("#[extern_rust_trait] was attached to trait {0}, but {1}.")]
+    UnsupportedExternRustTrait(String, &'static str),
+    #[error("the file {0} for mod {1} couldn't be read: {2}")]
+    ModFileRead(String, String, std::io::Error),
+    #[error("the file {0} for mod {1} couldn't be parsed: {2}")]
+    ModFileSyntax(String, String, syn::Error),
+    #[error("{0} and {1} would both be called {2} in C++. Items exposed to C++ share a single namespace, so rename one of them.")]
+    ConflictingRustNames(String, String, String),
+}
+
+/// Where to find the source files for the submodules of a module
+/// (as declared by `mod foo;`).
+#[derive(Clone, Debug)]
+pub(crate) struct ModDirs {
+    /// The directory containing files for submodules.
+    children: PathBuf,
+    /// The directory relative to which any `#[path = "..."]` attribute
+    /// on a submodule is resolved.
+    path_attr_base: PathBuf,
+}
+
+impl ModDirs {
+    /// Works out where to find submodules for the module defined in a given
+    /// file. Files named `mod.rs` or crate roots (`lib.rs`, `main.rs`)
+    /// keep their submodules alongside them; others, like `foo.rs`, keep
+    /// them in a directory `foo/`.
+    pub(crate) fn for_file(file: &std::path::Path) -> Self {
+        let dir = file
+            .parent()
+            .map(std::path::Path::to_path_buf)
+            .unwrap_or_default();
+        match file.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if !matches!(stem, "mod" | "lib" | "main") => Self {
+                children: dir.join(stem),
+                path_attr_base: dir,
+            },
+            _ => Self::for_mod_rs_dir(dir),
+        }
+    }
+
+    fn for_mod_rs_dir(dir: PathBuf) -> Self {
+        Self {
+            children: dir.clone(),
+            path_attr_base: dir,
+        }
+    }
+
+    /// Where to find submodules for an inline `mod foo { ... }`.
+    pub(crate) fn for_inline_mod(&self, id: &Ident) -> Self {
+        Self::for_mod_rs_dir(self.children.join(id.to_string()))
+    }
+
+    /// Candidate files for an out-of-line `mod foo;`.
+    fn candidate_files(&self, id: &Ident, attrs: &[Attribute]) -> Vec<PathBuf> {
+        let path_attr = attrs.iter().find_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }),
+                ..
+            }) if path.is_ident("path") => Some(lit.value()),
+            _ => None,
+        });
+        match path_attr {
+            Some(path_attr) => vec![self.path_attr_base.join(path_attr)],
+            None => vec![
+                self.children.join(format!("{id}.rs")),
+                self.children.join(id.to_string()).join("mod.rs"),
+            ],
+        }
+    }
+}
+
+/// The path of the root module of the crate.
+fn crate_root_path() -> RustPath {
+    RustPath::new_from_ident(Ident::new("crate", Span::call_site()))
+}
+
+/// Works out which module of its crate is defined in a given file,
+/// assuming the usual Cargo layout, where the crate root (`lib.rs`,
+/// `main.rs` or a binary within `bin/`) is in a `src` directory
+/// alongside `Cargo.toml`. Files which we can't place within a crate
+/// are assumed to be its root.
+pub(crate) fn crate_mod_path_for_file(file: &std::path::Path) -> RustPath {
+    let relative = file
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .and_then(|dir| file.strip_prefix(dir.join("src")).ok());
+    let mut segs: Vec<String> = match relative {
+        Some(relative) => relative
+            .with_extension("")
+            .iter()
+            .map(|seg| seg.to_string_lossy().into_owned())
+            .collect(),
+        None => return crate_root_path(),
+    };
+    if segs.first().map(String::as_str) == Some("bin") {
+        segs.drain(..segs.len().min(2));
+    }
+    if matches!(segs.last().map(String::as_str), Some("mod"))
+        || matches!(segs.as_slice(), [root] if root == "lib" || root == "main")
+    {
+        segs.pop();
+    }
+    segs.iter()
+        .try_fold(crate_root_path(), |path, seg| {
+            syn::parse_str::<Ident>(seg).ok().map(|id| path.append(id))
+        })
+        .unwrap_or_else(crate_root_path)
 }
 
 impl Discoveries {
+    /// Searches an item within the mod at `mod_path`, which starts with
+    /// `crate`; if it's not given, the item is assumed to be at the crate
+    /// root. If `mod_dirs` is given, any out-of-line mods (`mod foo;`) are
+    /// read from disk and searched too.
     pub(super) fn search_item(
         &mut self,
         item: &Item,
         mod_path: Option<RustPath>,
+        mod_dirs: Option<&ModDirs>,
     ) -> Result<(), DiscoveryErr> {
         let mut this_mod = PerModDiscoveries {
             discoveries: self,
-            mod_path,
+            mod_path: mod_path.unwrap_or_else(crate_root_path),
+            mod_dirs: mod_dirs.cloned(),
         };
         this_mod.search_item(item)
     }
@@ -59,27 +181,86 @@ impl Discoveries {
     }
 
     pub(crate) fn found_rust(&self) -> bool {
-        !self.extern_rust_funs.is_empty() || !self.extern_rust_types.is_empty()
+        !self.extern_rust_funs.is_empty()
+            || !self.extern_rust_types.is_empty()
+            || !self.extern_rust_traits.is_empty()
+    }
+
+    /// Checks that no two different Rust functions or types would have the
+    /// same name in C++, which would otherwise only show up as confusing
+    /// errors from cxx.
+    pub(crate) fn check_names_unique(&self) -> Result<(), DiscoveryErr> {
+        let paths = self
+            .extern_rust_funs
+            .iter()
+            .filter(|fun| !fun.has_receiver)
+            .map(|fun| &fun.path)
+            .chain(self.extern_rust_types.iter())
+            .unique();
+        let mut by_name = std::collections::HashMap::new();
+        for path in paths {
+            let name = path.get_final_ident().to_string();
+            if let Some(other) = by_name.insert(name.clone(), path) {
+                return Err(DiscoveryErr::ConflictingRustNames(
+                    other.to_token_stream().to_string().replace(' ', ""),
+                    path.to_token_stream().to_string().replace(' ', ""),
+                    name,
+                ));
+            }
+        }
+        Ok(())
     }
 
     pub(crate) fn extend(&mut self, other: Self) {
         self.cpp_list.extend(other.cpp_list);
         self.extern_rust_funs.extend(other.extern_rust_funs);
         self.extern_rust_types.extend(other.extern_rust_types);
+        self.extern_rust_traits.extend(other.extern_rust_traits);
+        self.mod_files.extend(other.mod_files);
     }
 }
 
 struct PerModDiscoveries<'a> {
     discoveries: &'a mut Discoveries,
-    mod_path: Option<RustPath>,
+    /// The full path of this mod, starting with `crate`.
+    mod_path: RustPath,
+    mod_dirs: Option<ModDirs>,
 }
 
 impl<'b> PerModDiscoveries<'b> {
     fn deeper_path(&self, id: &Ident) -> RustPath {
-        match &self.mod_path {
-            None => RustPath::new_from_ident(id.clone()),
-            Some(mod_path) => mod_path.append(id.clone()),
+        self.mod_path.append(id.clone())
+    }
+
+    /// Resolves a path as written within this mod (for example `Foo`,
+    /// `self::a::Foo`, `super::Foo` or `crate::a::b::Foo`) into a full
+    /// path starting at `crate`. Returns `None` if the path can't be
+    /// resolved, for instance because it uses `super` to escape the crate.
+    fn resolve_path(&self, path: &Path) -> Option<RustPath> {
+        if path.leading_colon.is_some() || path.segments.iter().any(|seg| !seg.arguments.is_none())
+        {
+            return None;
+        }
+        let mut segs = path.segments.iter().map(|seg| &seg.ident).peekable();
+        let mut resolved: Vec<Ident> = match segs.peek() {
+            Some(id) if *id == "crate" => segs.next().into_iter().cloned().collect(),
+            _ => self.mod_path.iter().cloned().collect(),
+        };
+        while let Some(id) = segs.peek() {
+            if *id == "self" {
+                segs.next();
+            } else if *id == "super" {
+                if resolved.len() == 1 {
+                    return None;
+                }
+                resolved.pop();
+                segs.next();
+            } else {
+                break;
+            }
         }
+        resolved.extend(segs.cloned());
+        RustPath::from_idents(resolved)
     }
 
     fn search_item(&mut self, item: &Item) -> Result<(), DiscoveryErr> {
@@ -111,19 +292,7 @@ impl<'b> PerModDiscoveries<'b> {
                     // We do not allow 'extern_rust_fun' on trait impls
                     Some(_) => None,
                     None => match &*imp.self_ty {
-                        Type::Path(typ) => {
-                            let mut segs = typ.path.segments.iter();
-                            let id = segs.next();
-                            if let Some(seg) = id {
-                                if segs.next().is_some() {
-                                    None
-                                } else {
-                                    Some(self.deeper_path(&seg.ident))
-                                }
-                            } else {
-                                None
-                            }
-                        }
+                        Type::Path(typ) if typ.qself.is_none() => self.resolve_path(&typ.path),
                         _ => None,
                     },
                 };
@@ -132,14 +301,31 @@ impl<'b> PerModDiscoveries<'b> {
                 }
             }
             Item::Mod(md) => {
-                if let Some((_, items)) = &md.content {
-                    let mod_path = Some(self.deeper_path(&md.ident));
-                    let mut new_mod = PerModDiscoveries {
-                        discoveries: self.discoveries,
-                        mod_path,
-                    };
-                    for item in items {
-                        new_mod.search_item(item)?
+                let mod_path = self.deeper_path(&md.ident);
+                match &md.content {
+                    Some((_, items)) => {
+                        let mut new_mod = PerModDiscoveries {
+                            discoveries: self.discoveries,
+                            mod_path,
+                            mod_dirs: self
+                                .mod_dirs
+                                .as_ref()
+                                .map(|mod_dirs| mod_dirs.for_inline_mod(&md.ident)),
+                        };
+                        for item in items {
+                            new_mod.search_item(item)?
+                        }
+                    }
+                    None => {
+                        if let Some(mod_dirs) = &self.mod_dirs {
+                            let candidates = mod_dirs.candidate_files(&md.ident, &md.attrs);
+                            // If the file doesn't exist, the mod is probably
+                            // configured out using #[cfg]; if not, rustc will
+                            // complain soon enough.
+                            if let Some(file) = candidates.into_iter().find(|f| f.exists()) {
+                                self.search_mod_file(&file, &md.ident, mod_path)?
+                            }
+                        }
                     }
                 }
             }
@@ -147,6 +333,10 @@ impl<'b> PerModDiscoveries<'b> {
                 for item in &tr.items {
                     self.search_trait_item(item)?
                 }
+                if Self::has_attr(&tr.attrs, EXTERN_RUST_TRAIT) {
+                    let rust_trait = self.extern_rust_trait(tr)?;
+                    self.discoveries.extern_rust_traits.push(rust_trait);
+                }
             }
             Item::Struct(ItemStruct { ident, attrs, .. })
             | Item::Enum(ItemEnum { ident, attrs, .. })
@@ -161,8 +351,80 @@ impl<'b> PerModDiscoveries<'b> {
         Ok(())
     }
 
+    /// Works out the methods of a trait which is to be exposed to C++.
+    /// C++ calls these through a `Box<dyn Trait>`, so the trait must be
+    /// object safe, and each method must take `&self` or `&mut self`.
+    fn extern_rust_trait(&self, tr: &ItemTrait) -> Result<RustTrait, DiscoveryErr> {
+        let unsupported = |reason| {
+            Err(DiscoveryErr::UnsupportedExternRustTrait(
+                tr.ident.to_string(),
+                reason,
+            ))
+        };
+        if !tr.generics.params.is_empty() {
+            return unsupported("generic traits are not supported");
+        }
+        let path = self.deeper_path(&tr.ident);
+        let holder = RustTrait::holder_for(&tr.ident);
+        let mut methods = Vec::new();
+        for item in &tr.items {
+            let sig = match item {
+                TraitItem::Fn(TraitItemFn { sig, .. }) => sig,
+                _ => return unsupported("it contains items other than methods"),
+            };
+            if !sig.generics.params.is_empty() {
+                return unsupported("generic methods are not supported");
+            }
+            if !matches!(
+                sig.inputs.first(),
+                Some(FnArg::Receiver(Receiver {
+                    reference: Some(_),
+                    colon_token: None,
+                    ..
+                }))
+            ) {
+                return unsupported("each method must take &self or &mut self");
+            }
+            methods.push(RustFun {
+                path: path.append(sig.ident.clone()),
+                sig: add_receiver(sig, &holder)?,
+                has_receiver: true,
+            });
+        }
+        Ok(RustTrait { path, methods })
+    }
+
+    /// Searches an out-of-line mod stored in a different file.
+    fn search_mod_file(
+        &mut self,
+        file: &std::path::Path,
+        id: &Ident,
+        mod_path: RustPath,
+    ) -> Result<(), DiscoveryErr> {
+        let source = std::fs::read_to_string(file).map_err(|e| {
+            DiscoveryErr::ModFileRead(file.display().to_string(), id.to_string(), e)
+        })?;
+        self.discoveries.mod_files.push(file.to_path_buf());
+        let source = syn::parse_file(&source).map_err(|e| {
+            DiscoveryErr::ModFileSyntax(file.display().to_string(), id.to_string(), e)
+        })?;
+        let mut new_mod = PerModDiscoveries {
+            discoveries: self.discoveries,
+            mod_path,
+            mod_dirs: Some(ModDirs::for_file(file)),
+        };
+        for item in &source.items {
+            new_mod.search_item(item)?
+        }
+        Ok(())
+    }
+
     fn search_path(&mut self, path: &Path) -> Result<(), DiscoveryErr> {
-        let mut seg_iter = path.segments.iter();
+        // Paths to C++ types may be qualified, e.g. crate::ffi::A or super::ffi::A.
+        let mut seg_iter = path
+            .segments
+            .iter()
+            .skip_while(|seg| seg.ident == "crate" || seg.ident == "self" || seg.ident == "super");
         if let Some(first_seg) = seg_iter.next() {
             if first_seg.ident == "ffi" {
                 self.discoveries
@@ -335,14 +597,10 @@ impl<'b> PerModDiscoveries<'b> {
     ) -> Result<(), DiscoveryErr> {
         if let ImplItem::Fn(itm) = impl_item {
             if Self::has_attr(&itm.attrs, EXTERN_RUST_FUN) {
-                if self.mod_path.is_some() {
-                    return Err(DiscoveryErr::FoundExternRustFunWithinMod);
-                }
                 if let Some(receiver) = receiver {
                     // We have a method which we want to put into the cxx::bridge's
                     // "extern Rust" block.
                     let sig = add_receiver(&itm.sig, receiver.get_final_ident())?;
-                    assert!(receiver.len() == 1);
                     self.discoveries.extern_rust_funs.push(RustFun {
                         path: self.deeper_path(&itm.sig.ident),
                         sig,
@@ -554,12 +812,15 @@ fn add_receiver(sig: &Signature, receiver: &Ident) -> Result<Signature, Discover
 
 #[cfg(test)]
 mod tests {
+    use autocxx_parser::RustPath;
     use quote::{quote, ToTokens};
     use syn::{parse_quote, ImplItemFn};
 
     use crate::{ast_discoverer::add_receiver, types::make_ident};
 
-    use super::Discoveries;
+    use super::{
+        crate_mod_path_for_file, crate_root_path, Discoveries, ModDirs, PerModDiscoveries,
+    };
 
     fn assert_cpp_found(discoveries: &Discoveries) {
         assert!(!discoveries.cpp_list.is_empty());
@@ -576,7 +837,7 @@ mod tests {
                 }
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -588,7 +849,7 @@ mod tests {
                 ffi::xxx()
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -600,7 +861,7 @@ mod tests {
                 ffi::xxx();
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -612,7 +873,7 @@ mod tests {
                 ffi::a::b::xxx();
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert!(!discoveries.cpp_list.is_empty());
         assert!(discoveries.cpp_list.iter().next().unwrap() == "a::b::xxx");
     }
@@ -625,7 +886,7 @@ mod tests {
                 a + 3 * foo(ffi::xxx());
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -637,7 +898,7 @@ mod tests {
                 let foo: ffi::xxx = bar();
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -648,7 +909,7 @@ mod tests {
             fn bar(a: &mut ffi::xxx) {
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -659,7 +920,18 @@ mod tests {
             fn bar(a: cxx::UniquePtr<ffi::xxx>) {
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
+        assert_cpp_found(&discoveries);
+    }
+
+    #[test]
+    fn test_ty_in_fn_qualified() {
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            fn bar(a: &crate::ffi::xxx) {
+            }
+        };
+        discoveries.search_item(&itm, None, None).unwrap();
         assert_cpp_found(&discoveries);
     }
 
@@ -671,7 +943,7 @@ mod tests {
             fn bar(a: cxx::UniquePtr<ffi::xxx>) {
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert!(discoveries.extern_rust_funs.first().unwrap().sig.ident == "bar");
     }
 
@@ -685,10 +957,291 @@ mod tests {
                 }
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert!(discoveries.extern_rust_funs.first().unwrap().sig.ident == "bar");
     }
 
+    #[test]
+    fn test_extern_rust_method_qualified_receiver() {
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            impl crate::a::A {
+                #[autocxx::extern_rust::extern_rust_function]
+                fn bar(&self) {
+                }
+            }
+        };
+        discoveries.search_item(&itm, None, None).unwrap();
+        assert_eq!(
+            discoveries
+                .extern_rust_types
+                .first()
+                .unwrap()
+                .to_token_stream()
+                .to_string(),
+            quote! { crate::a::A }.to_string()
+        );
+        assert_eq!(
+            discoveries
+                .extern_rust_funs
+                .first()
+                .unwrap()
+                .sig
+                .to_token_stream()
+                .to_string(),
+            quote! { fn bar(self: &A) }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let mut discoveries = Discoveries::default();
+        let this_mod = PerModDiscoveries {
+            discoveries: &mut discoveries,
+            mod_path: crate_root_path()
+                .append(parse_quote! { a })
+                .append(parse_quote! { b }),
+            mod_dirs: None,
+        };
+        let resolve = |path: syn::Path| {
+            this_mod
+                .resolve_path(&path)
+                .map(|p| p.to_token_stream().to_string())
+        };
+        assert_eq!(
+            resolve(parse_quote! { Foo }),
+            Some("crate :: a :: b :: Foo".into())
+        );
+        assert_eq!(
+            resolve(parse_quote! { self::Foo }),
+            Some("crate :: a :: b :: Foo".into())
+        );
+        assert_eq!(
+            resolve(parse_quote! { super::Foo }),
+            Some("crate :: a :: Foo".into())
+        );
+        assert_eq!(
+            resolve(parse_quote! { super::super::Foo }),
+            Some("crate :: Foo".into())
+        );
+        assert_eq!(
+            resolve(parse_quote! { crate::c::Foo }),
+            Some("crate :: c :: Foo".into())
+        );
+        assert_eq!(resolve(parse_quote! { super::super::super::Foo }), None);
+        assert_eq!(resolve(parse_quote! { ::std::Foo }), None);
+        assert_eq!(resolve(parse_quote! { Foo<u32> }), None);
+    }
+
+    #[test]
+    fn test_extern_rust_method_in_mod() {
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            mod a {
+                impl A {
+                    #[autocxx::extern_rust::extern_rust_function]
+                    fn bar(&self) {
+                    }
+                }
+            }
+        };
+        discoveries.search_item(&itm, None, None).unwrap();
+        assert_eq!(
+            discoveries
+                .extern_rust_types
+                .first()
+                .unwrap()
+                .to_token_stream()
+                .to_string(),
+            quote! { crate::a::A }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_extern_rust_trait() {
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            mod a {
+                #[autocxx::extern_rust::extern_rust_trait]
+                pub trait Plugin {
+                    fn name(&self) -> String;
+                    fn run(&mut self, input: &ffi::A) -> u32 {
+                        3
+                    }
+                }
+            }
+        };
+        discoveries.search_item(&itm, None, None).unwrap();
+        let rust_trait = discoveries.extern_rust_traits.first().unwrap();
+        assert_eq!(
+            rust_trait.path.to_token_stream().to_string(),
+            quote! { crate::a::Plugin }.to_string()
+        );
+        assert_eq!(rust_trait.holder().to_string(), "PluginHolder");
+        let sigs: Vec<_> = rust_trait
+            .methods
+            .iter()
+            .map(|method| method.sig.to_token_stream().to_string())
+            .collect();
+        assert_eq!(
+            sigs,
+            vec![
+                quote! { fn name(self: &PluginHolder) -> String }.to_string(),
+                quote! { fn run(self: &mut PluginHolder, input: &ffi::A) -> u32 }.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extern_rust_trait_unsupported() {
+        let unsupported: [syn::Item; 4] = [
+            parse_quote! {
+                #[extern_rust_trait]
+                trait Generic<T> {
+                    fn get(&self) -> T;
+                }
+            },
+            parse_quote! {
+                #[extern_rust_trait]
+                trait ByValue {
+                    fn consume(self);
+                }
+            },
+            parse_quote! {
+                #[extern_rust_trait]
+                trait Constructor {
+                    fn new() -> Self;
+                }
+            },
+            parse_quote! {
+                #[extern_rust_trait]
+                trait AssociatedType {
+                    type Output;
+                }
+            },
+        ];
+        for itm in unsupported {
+            let mut discoveries = Discoveries::default();
+            assert!(discoveries.search_item(&itm, None, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_extern_rust_in_out_of_line_mod() {
+        let dir = std::env::temp_dir().join(format!("autocxx_discovery_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a")).unwrap();
+        std::fs::write(
+            dir.join("a.rs"),
+            quote! {
+                mod b;
+                #[autocxx::extern_rust::extern_rust_function]
+                pub fn foo() {}
+            }
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("a").join("b.rs"),
+            quote! {
+                #[autocxx::extern_rust::extern_rust_type]
+                pub struct Bar;
+                impl Bar {
+                    #[autocxx::extern_rust::extern_rust_function]
+                    pub fn baz(&self) {}
+                }
+            }
+            .to_string(),
+        )
+        .unwrap();
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            mod a;
+        };
+        let result =
+            discoveries.search_item(&itm, None, Some(&ModDirs::for_file(&dir.join("lib.rs"))));
+        std::fs::remove_dir_all(&dir).unwrap();
+        result.unwrap();
+        assert_eq!(
+            discoveries.mod_files,
+            vec![dir.join("a.rs"), dir.join("a").join("b.rs")]
+        );
+        let paths = |paths: Vec<RustPath>| {
+            paths
+                .into_iter()
+                .map(|p| p.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(
+                discoveries
+                    .extern_rust_funs
+                    .into_iter()
+                    .map(|f| f.path)
+                    .collect()
+            ),
+            vec!["crate :: a :: b :: baz", "crate :: a :: foo"]
+        );
+        assert_eq!(
+            paths(discoveries.extern_rust_types),
+            vec!["crate :: a :: b :: Bar", "crate :: a :: b :: Bar"]
+        );
+    }
+
+    #[test]
+    fn test_crate_mod_path_for_file() {
+        let dir = std::env::temp_dir().join(format!("autocxx_mod_path_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "").unwrap();
+        let mod_path = |file: &str| {
+            crate_mod_path_for_file(&dir.join(file))
+                .to_token_stream()
+                .to_string()
+        };
+        let results = [
+            mod_path("src/lib.rs"),
+            mod_path("src/main.rs"),
+            mod_path("src/a.rs"),
+            mod_path("src/a/mod.rs"),
+            mod_path("src/a/b.rs"),
+            mod_path("src/bin/tool.rs"),
+            mod_path("src/bin/tool/c.rs"),
+            mod_path("build.rs"),
+        ];
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            results,
+            [
+                "crate",
+                "crate",
+                "crate :: a",
+                "crate :: a",
+                "crate :: a :: b",
+                "crate",
+                "crate :: c",
+                "crate",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflicting_rust_names() {
+        let mut discoveries = Discoveries::default();
+        let itm = parse_quote! {
+            mod outer {
+                mod a {
+                    #[autocxx::extern_rust::extern_rust_type]
+                    pub struct Foo;
+                }
+                mod b {
+                    #[autocxx::extern_rust::extern_rust_type]
+                    pub struct Foo;
+                }
+            }
+        };
+        discoveries.search_item(&itm, None, None).unwrap();
+        assert!(discoveries.check_names_unique().is_err());
+    }
+
     #[test]
     fn test_extern_rust_ty() {
         let mut discoveries = Discoveries::default();
@@ -698,7 +1251,7 @@ mod tests {
 
             }
         };
-        discoveries.search_item(&itm, None).unwrap();
+        discoveries.search_item(&itm, None, None).unwrap();
         assert!(
             discoveries
                 .extern_rust_types
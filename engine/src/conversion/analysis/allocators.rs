@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Code to create functions to alloc and free while unitialized.
+//! Code to create functions to alloc and free while unitialized, and to
+//! move from a `std::unique_ptr` into a `std::shared_ptr`.
 
 use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, ReturnType};
 
@@ -51,6 +52,12 @@ fn create_alloc_and_free(ty_name: QualifiedName) -> impl Iterator<Item = Api<Pod
     let alloc_return: ReturnType = parse_quote! {
         -> *mut #typ
     };
+    let unique_to_shared_inputs: Punctuated<FnArg, Comma> = parse_quote! {
+        arg0: root::std::unique_ptr<#typ>
+    };
+    let unique_to_shared_return: ReturnType = parse_quote! {
+        -> root::std::shared_ptr<#typ>
+    };
     [
         (
             TraitSynthesis::AllocUninitialized(ty_name.clone()),
@@ -66,6 +73,13 @@ fn create_alloc_and_free(ty_name: QualifiedName) -> impl Iterator<Item = Api<Pod
             ReturnType::Default,
             CppFunctionBody::FreeUninitialized(ty_name.clone()),
         ),
+        (
+            TraitSynthesis::UniqueToShared(ty_name.clone()),
+            get_unique_to_shared_name(&ty_name),
+            unique_to_shared_inputs,
+            unique_to_shared_return,
+            CppFunctionBody::UniqueToShared(ty_name.clone()),
+        ),
     ]
     .into_iter()
     .map(
@@ -84,6 +98,8 @@ fn create_alloc_and_free(ty_name: QualifiedName) -> impl Iterator<Item = Api<Pod
                     cpp_vis: CppVisibility::Public,
                     special_member: None,
                     unused_template_param: false,
+                    returns_weak_ptr_to_const: false,
+                    is_explicit: false,
                     references: References::default(),
                     original_name: None,
                     self_ty: None,
@@ -108,6 +124,10 @@ pub(crate) fn get_free_name(ty_name: &QualifiedName) -> QualifiedName {
     get_name(ty_name, "autocxx_free")
 }
 
+fn get_unique_to_shared_name(ty_name: &QualifiedName) -> QualifiedName {
+    get_name(ty_name, "autocxx_unique_to_shared")
+}
+
 fn get_name(ty_name: &QualifiedName, label: &str) -> QualifiedName {
     let name = format!("{}_{}", ty_name.get_final_item(), label);
     let name_id = make_ident(name);
@@ -107,6 +107,8 @@ fn create_cast(from: &QualifiedName, to: &QualifiedName, mutable: CastMutability
             cpp_vis: crate::conversion::api::CppVisibility::Public,
             special_member: None,
             unused_template_param: false,
+            returns_weak_ptr_to_const: false,
+            is_explicit: false,
             references: References::new_with_this_and_return_as_reference(),
             original_name: None,
             self_ty: Some(from.clone()),
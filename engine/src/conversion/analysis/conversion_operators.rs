@@ -0,0 +1,107 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to create `From` implementations for C++ conversion operators,
+//! such as `operator bool()`.
+
+use autocxx_parser::UnsafePolicy;
+use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, ReturnType};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, ConversionOperator, CppVisibility, DeletedOrDefaulted, FuncToConvert,
+            Provenance, References, TraitSynthesis,
+        },
+        apivec::ApiVec,
+    },
+    minisyn::minisynize_punctuated,
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::{PodAnalysis, PodPhase},
+};
+
+/// Add a function for each conversion operator of each struct, which will
+/// become an implementation of `From<&T>` for the type it converts to.
+/// With C++ reference wrappers the receiver would be a `CppRef<T>`, which
+/// isn't a local type, so Rust wouldn't let us implement `From` for it.
+pub(crate) fn add_conversion_operators(
+    apis: ApiVec<PodPhase>,
+    unsafe_policy: &UnsafePolicy,
+) -> ApiVec<PodPhase> {
+    if unsafe_policy.requires_cpprefs() {
+        return apis;
+    }
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        if let Api::Struct {
+            name,
+            details,
+            analysis: PodAnalysis {
+                is_generic: false, ..
+            },
+        } = &api
+        {
+            for conversion_operator in &details.conversion_operators {
+                results.push(create_conversion(&name.name, *conversion_operator));
+            }
+        }
+        results.push(api);
+    }
+    results
+}
+
+fn create_conversion(
+    self_ty: &QualifiedName,
+    conversion_operator: ConversionOperator,
+) -> Api<PodPhase> {
+    let typ = self_ty.to_type_path();
+    let inputs: Punctuated<FnArg, Comma> = parse_quote! { this: *const #typ };
+    let (label, output): (_, ReturnType) = match conversion_operator {
+        ConversionOperator::Bool => ("bool", parse_quote! { -> bool }),
+        ConversionOperator::String => ("string", parse_quote! { -> root::std::string }),
+    };
+    let mut references = References::default();
+    references.ref_params.insert(make_ident("this"));
+    let ident = make_ident(format!(
+        "{}_autocxx_convert_to_{label}",
+        self_ty.get_final_item()
+    ));
+    Api::Function {
+        name: ApiName::new(self_ty.get_namespace(), ident.clone()),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: Vec::new(),
+            inputs: minisynize_punctuated(inputs),
+            output: output.into(),
+            vis: parse_quote! { pub },
+            virtualness: crate::conversion::api::Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            returns_weak_ptr_to_const: false,
+            is_explicit: false,
+            references,
+            original_name: None,
+            self_ty: Some(self_ty.clone()),
+            synthesized_this_type: None,
+            synthetic_cpp: Some((
+                CppFunctionBody::ConversionOperator(conversion_operator),
+                CppFunctionKind::ConstMethod,
+            )),
+            add_to_trait: Some(TraitSynthesis::ConversionOperator(conversion_operator)),
+            is_deleted: DeletedOrDefaulted::Neither,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
@@ -41,7 +41,14 @@ impl HasDependencies for Api<FnPrePhase1> {
                 superclass,
             } => Box::new(std::iter::once(superclass)),
             Api::RustSubclassFn { details, .. } => Box::new(details.dependencies.iter()),
-            Api::RustFn { deps, .. } => Box::new(deps.iter()),
+            Api::RustFn { deps, .. }
+            | Api::RustTrait { deps, .. }
+            | Api::Tuple { deps, .. }
+            | Api::Variant { deps, .. }
+            | Api::AssociativeContainer { deps, .. }
+            | Api::SmartPointer { deps, .. }
+            | Api::UniquePtrWithDeleter { deps, .. } => Box::new(deps.iter()),
+            Api::ContainerElement { element, .. } => Box::new(std::iter::once(element)),
             _ => Box::new(std::iter::empty()),
         }
     }
@@ -90,7 +97,14 @@ impl HasDependencies for Api<FnPhase> {
                 superclass,
             } => Box::new(std::iter::once(superclass)),
             Api::RustSubclassFn { details, .. } => Box::new(details.dependencies.iter()),
-            Api::RustFn { deps, .. } => Box::new(deps.iter()),
+            Api::RustFn { deps, .. }
+            | Api::RustTrait { deps, .. }
+            | Api::Tuple { deps, .. }
+            | Api::Variant { deps, .. }
+            | Api::AssociativeContainer { deps, .. }
+            | Api::SmartPointer { deps, .. }
+            | Api::UniquePtrWithDeleter { deps, .. } => Box::new(deps.iter()),
+            Api::ContainerElement { element, .. } => Box::new(std::iter::once(element)),
             _ => Box::new(std::iter::empty()),
         }
     }
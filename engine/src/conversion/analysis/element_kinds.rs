@@ -0,0 +1,123 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
+use syn::Type;
+
+use crate::{
+    conversion::{
+        api::{Api, TypeKind},
+        apivec::ApiVec,
+    },
+    known_types::known_types,
+    types::QualifiedName,
+};
+
+use super::{
+    fun::{FnPhase, PodAndDepAnalysis, PublicConstructors},
+    pod::PodAnalysis,
+};
+
+/// How an alternative of a `std::variant`, or a key or value of an
+/// associative container, is passed between Rust and C++.
+#[derive(Clone, Copy)]
+pub(crate) enum ElementKind {
+    ByValue,
+    UniquePtr,
+}
+
+/// Work out how each alternative of each `std::variant`, and the key and
+/// value types of each associative container, can be passed to and from
+/// Rust. Types with any element which can't be passed either way are
+/// omitted, and will remain opaque.
+pub(crate) fn find_element_kinds(
+    apis: &ApiVec<FnPhase>,
+) -> HashMap<QualifiedName, Vec<ElementKind>> {
+    let by_value: HashSet<&QualifiedName> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                analysis:
+                    PodAndDepAnalysis {
+                        pod:
+                            PodAnalysis {
+                                kind: TypeKind::Pod,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            }
+            | Api::Enum { .. }
+            | Api::ExternCppType { pod: true, .. } => Some(api.name()),
+            _ => None,
+        })
+        .collect();
+    let in_unique_ptr: HashSet<&QualifiedName> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                analysis:
+                    PodAndDepAnalysis {
+                        pod:
+                            PodAnalysis {
+                                kind: TypeKind::NonPod,
+                                ..
+                            },
+                        constructors:
+                            PublicConstructors {
+                                move_constructor: true,
+                                destructor: true,
+                            },
+                        ..
+                    },
+                ..
+            } => Some(api.name()),
+            _ => None,
+        })
+        .collect();
+    apis.iter()
+        .filter_map(|api| match api {
+            Api::Variant {
+                alternatives: elements,
+                ..
+            }
+            | Api::AssociativeContainer { elements, .. } => elements
+                .iter()
+                .map(|ty| {
+                    let tn = match &**ty {
+                        Type::Path(typ) => QualifiedName::from_type_path(typ),
+                        _ => return None,
+                    };
+                    let known = known_types().is_known_type(&tn);
+                    if by_value.contains(&tn)
+                        || known && known_types().permissible_within_tuple(&tn)
+                    {
+                        Some(ElementKind::ByValue)
+                    } else if in_unique_ptr.contains(&tn)
+                        || known && known_types().permissible_within_unique_ptr(&tn)
+                    {
+                        Some(ElementKind::UniquePtr)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|kinds| (api.name().clone(), kinds)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The name of one of the C++ functions which we generate to inspect,
+/// construct or modify a `std::variant`, associative container or custom
+/// smart pointer.
+pub(crate) fn shim_name(tn: &QualifiedName, purpose: &str) -> String {
+    format!("{}_autocxx_{}", tn.get_final_item(), purpose)
+}
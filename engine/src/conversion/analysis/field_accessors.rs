@@ -0,0 +1,352 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to create accessor methods for the public fields of non-POD types,
+//! which are otherwise opaque to Rust.
+
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
+
+use itertools::Itertools;
+use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, ReturnType, Type, Visibility};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, Provenance, References,
+            StructDetails, TypeKind, TypedefKind,
+        },
+        apivec::ApiVec,
+    },
+    minisyn::minisynize_punctuated,
+    types::{make_ident, validate_ident_ok_for_rust, QualifiedName},
+};
+
+use super::{
+    doc_label::make_doc_attrs,
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::{PodAnalysis, PodPhase},
+};
+
+/// How a field can be accessed from Rust.
+enum FieldAccess {
+    /// Fields of primitive, enum or POD types are read and written by value.
+    ByValue,
+    /// Fields of other types are accessed by `&T` or `Pin<&mut T>`.
+    ByReference,
+}
+
+/// Add getters and setters for each public field of each non-POD struct.
+/// These are synthesized as methods of the struct, so the usual function
+/// analysis works out how to convert the field types.
+///
+/// Setters and `_mut` getters are only added for fields which we know
+/// aren't `const`. Accessors aren't added where a real method has the same
+/// name; they're also added after all the other APIs, so that the function
+/// analysis names any other methods before them.
+pub(crate) fn add_field_accessors(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let by_value_types = find_by_value_types(&apis);
+    let method_names = find_method_names(&apis);
+    let no_methods = HashSet::new();
+    let mut accessors = ApiVec::new();
+    for api in apis.iter() {
+        if let Api::Struct {
+            name,
+            details,
+            analysis:
+                PodAnalysis {
+                    kind: TypeKind::NonPod,
+                    is_generic: false,
+                    ..
+                },
+        } = api
+        {
+            if !details.is_union {
+                let method_names = method_names.get(&name.name).unwrap_or(&no_methods);
+                for accessor in
+                    create_field_accessors(&name.name, details, &by_value_types, method_names)
+                {
+                    accessors.push(accessor);
+                }
+            }
+        }
+    }
+    let mut results = apis;
+    results.append(&mut accessors);
+    results
+}
+
+/// The Rust names which the methods of each type will want. Any type may
+/// also have constructors, called `new`, synthesized later.
+fn find_method_names(apis: &ApiVec<PodPhase>) -> HashMap<QualifiedName, HashSet<String>> {
+    let mut method_names: HashMap<QualifiedName, HashSet<String>> = HashMap::new();
+    for api in apis.iter() {
+        if let Api::Struct { name, .. } = api {
+            method_names
+                .entry(name.name.clone())
+                .or_default()
+                .insert("new".to_string());
+        }
+        if let Api::Function { name, fun, .. } = api {
+            if let Some(self_ty) = &fun.self_ty {
+                let cpp_name = name.cpp_name();
+                let rust_name = if validate_ident_ok_for_rust(&cpp_name).is_err() {
+                    format!("{cpp_name}_")
+                } else {
+                    cpp_name
+                };
+                method_names
+                    .entry(self_ty.clone())
+                    .or_default()
+                    .insert(rust_name);
+            }
+        }
+    }
+    method_names
+}
+
+/// Types of fields which may be copied in and out of a struct, along
+/// with typedefs of those.
+fn find_by_value_types(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
+    let mut by_value_types: HashSet<QualifiedName> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                name,
+                analysis:
+                    PodAnalysis {
+                        kind: TypeKind::Pod,
+                        ..
+                    },
+                ..
+            }
+            | Api::Enum { name, .. } => Some(name.name.clone()),
+            _ => None,
+        })
+        .collect();
+    let typedefs: HashMap<QualifiedName, Type> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Typedef {
+                name,
+                item: TypedefKind::Type(ity),
+                ..
+            } => Some((name.name.clone(), (*ity.ty).clone())),
+            Api::Typedef {
+                name,
+                item: TypedefKind::Use(_, ty),
+                ..
+            } => Some((name.name.clone(), ty.0.clone())),
+            _ => None,
+        })
+        .collect();
+    // Typedefs may refer to other typedefs, so keep going until we find
+    // no more.
+    loop {
+        let found = typedefs
+            .iter()
+            .filter(|(name, ty)| {
+                !by_value_types.contains(*name) && is_by_value_type(ty, &by_value_types)
+            })
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+        if found.is_empty() {
+            return by_value_types;
+        }
+        by_value_types.extend(found);
+    }
+}
+
+fn is_by_value_type(ty: &Type, by_value_types: &HashSet<QualifiedName>) -> bool {
+    match ty {
+        Type::Path(typ) if typ.qself.is_none() => {
+            let segments = typ
+                .path
+                .segments
+                .iter()
+                .map(|seg| seg.ident.to_string())
+                .collect_vec();
+            match segments.as_slice() {
+                [primitive] => matches!(
+                    primitive.as_str(),
+                    "bool"
+                        | "u8"
+                        | "u16"
+                        | "u32"
+                        | "u64"
+                        | "usize"
+                        | "i8"
+                        | "i16"
+                        | "i32"
+                        | "i64"
+                        | "isize"
+                        | "f32"
+                        | "f64"
+                ),
+                [std, os, raw, _] if std == "std" && os == "os" && raw == "raw" => true,
+                _ => by_value_types.contains(&QualifiedName::from_type_path(typ)),
+            }
+        }
+        _ => false,
+    }
+}
+
+fn field_access(ty: &Type, by_value_types: &HashSet<QualifiedName>) -> Option<FieldAccess> {
+    match ty {
+        _ if is_by_value_type(ty, by_value_types) => Some(FieldAccess::ByValue),
+        Type::Path(typ) if typ.qself.is_none() => Some(FieldAccess::ByReference),
+        // cxx can't represent arrays, and pointers (or reference fields,
+        // which bindgen represents as pointers) would make the accessors
+        // unsafe to call.
+        _ => None,
+    }
+}
+
+fn create_field_accessors<'a>(
+    self_ty: &'a QualifiedName,
+    details: &'a StructDetails,
+    by_value_types: &'a HashSet<QualifiedName>,
+    method_names: &'a HashSet<String>,
+) -> impl Iterator<Item = Api<PodPhase>> + 'a {
+    details
+        .item
+        .fields
+        .iter()
+        .filter(|f| matches!(f.vis, Visibility::Public(_)))
+        .filter_map(|f| f.ident.as_ref().map(|ident| (ident, &f.ty)))
+        .filter(|(ident, _)| !is_bindgen_field(&ident.to_string()))
+        .flat_map(move |(ident, ty)| {
+            let field_name = ident.to_string();
+            // bindgen appends an underscore to fields named after Rust
+            // keywords.
+            let cpp_field_name = match field_name.strip_suffix('_') {
+                Some(stripped) if validate_ident_ok_for_rust(stripped).is_err() => {
+                    stripped.to_string()
+                }
+                _ => field_name.clone(),
+            };
+            let typ = self_ty.to_type_path();
+            let is_const = !details.non_const_fields.contains(&cpp_field_name);
+            let mut accessors = match field_access(ty, by_value_types) {
+                None => Vec::new(),
+                Some(FieldAccess::ByValue) => vec![
+                    (
+                        field_name.clone(),
+                        format!("Returns the value of the `{cpp_field_name}` field."),
+                        parse_quote! { this: *const #typ },
+                        parse_quote! { -> #ty },
+                        References::default(),
+                        CppFunctionBody::FieldGetter(make_ident(&cpp_field_name)),
+                    ),
+                    (
+                        format!("set_{field_name}"),
+                        format!("Sets the value of the `{cpp_field_name}` field."),
+                        parse_quote! { this: *mut #typ, value: #ty },
+                        ReturnType::Default,
+                        References::default(),
+                        CppFunctionBody::FieldSetter(make_ident(&cpp_field_name)),
+                    ),
+                ],
+                Some(FieldAccess::ByReference) => vec![
+                    (
+                        field_name.clone(),
+                        format!("Returns a reference to the `{cpp_field_name}` field."),
+                        parse_quote! { this: *const #typ },
+                        parse_quote! { -> *const #ty },
+                        References::new_with_this_and_return_as_reference(),
+                        CppFunctionBody::FieldGetter(make_ident(&cpp_field_name)),
+                    ),
+                    (
+                        format!("{field_name}_mut"),
+                        format!("Returns a mutable reference to the `{cpp_field_name}` field."),
+                        parse_quote! { this: *mut #typ },
+                        parse_quote! { -> *mut #ty },
+                        References::new_with_this_and_return_as_reference(),
+                        CppFunctionBody::FieldGetter(make_ident(&cpp_field_name)),
+                    ),
+                ],
+            };
+            if is_const {
+                accessors.truncate(1);
+            }
+            accessors.retain(|(method_name, ..)| !method_names.contains(method_name));
+            accessors.into_iter().map(
+                move |(method_name, doc, inputs, output, references, cpp_function_body)| {
+                    create_field_accessor(
+                        self_ty,
+                        method_name,
+                        doc,
+                        inputs,
+                        output,
+                        references,
+                        cpp_function_body,
+                    )
+                },
+            )
+        })
+}
+
+/// Fields which bindgen made up, rather than fields from the original C++,
+/// such as `_base` or `_base_1` for base classes.
+fn is_bindgen_field(ident: &str) -> bool {
+    let is_numbered = |suffix: Option<&str>| {
+        suffix.is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    };
+    ident == "_base"
+        || ident == "_address"
+        || is_numbered(ident.strip_prefix("_base_"))
+        || ["__bindgen", "_bindgen", "_bitfield"]
+            .iter()
+            .any(|prefix| ident.starts_with(prefix))
+}
+
+fn create_field_accessor(
+    self_ty: &QualifiedName,
+    method_name: String,
+    doc: String,
+    inputs: Punctuated<FnArg, Comma>,
+    output: ReturnType,
+    mut references: References,
+    cpp_function_body: CppFunctionBody,
+) -> Api<PodPhase> {
+    references.ref_params.insert(make_ident("this"));
+    // As with methods found by bindgen, the API is named after the type
+    // and the method, and the function analysis will strip the type name
+    // off again.
+    let ident = make_ident(format!(
+        "{}_autocxx_field_{}",
+        self_ty.get_final_item(),
+        method_name
+    ));
+    Api::Function {
+        name: ApiName::new_with_cpp_name(self_ty.get_namespace(), ident.clone(), Some(method_name)),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: make_doc_attrs(doc).into_iter().map(Into::into).collect(),
+            inputs: minisynize_punctuated(inputs),
+            output: output.into(),
+            vis: parse_quote! { pub },
+            virtualness: crate::conversion::api::Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            returns_weak_ptr_to_const: false,
+            is_explicit: false,
+            references,
+            original_name: None,
+            self_ty: Some(self_ty.clone()),
+            synthesized_this_type: None,
+            synthetic_cpp: Some((cpp_function_body, CppFunctionKind::Method)),
+            add_to_trait: None,
+            is_deleted: DeletedOrDefaulted::Neither,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
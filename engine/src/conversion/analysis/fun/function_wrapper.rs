@@ -8,11 +8,16 @@
 
 use crate::minisyn::Ident;
 use crate::{
-    conversion::{api::SubclassName, type_helpers::extract_pinned_mutable_reference_type},
+    conversion::{
+        analysis::{element_kinds::shim_name, type_converter::OwningPointer},
+        api::{ConversionOperator, SubclassName},
+        type_helpers::extract_pinned_mutable_reference_type,
+    },
+    known_types::ChronoType,
     types::{Namespace, QualifiedName},
 };
 use quote::ToTokens;
-use syn::{parse_quote, Type, TypeReference};
+use syn::{parse_quote, Type, TypePath, TypeReference};
 
 #[derive(Clone, Debug)]
 pub(crate) enum CppConversionType {
@@ -25,8 +30,27 @@ pub(crate) enum CppConversionType {
     /// Ignored in the sense that it isn't passed into the C++ function.
     IgnoredPlacementPtrParameter,
     FromReturnValueToPlacementPtr,
-    FromPointerToReference, // unwrapped_type is always Type::Ptr
-    FromReferenceToPointer, // unwrapped_type is always Type::Ptr
+    FromPointerToReference,        // unwrapped_type is always Type::Ptr
+    FromReferenceToPointer,        // unwrapped_type is always Type::Ptr
+    FromNanosToChrono(ChronoType), // unwrapped_type is always i64
+    FromChronoToNanos,             // unwrapped_type is always i64
+    FromTupleStructToTuple,
+    FromTupleToTupleStruct,
+    /// A `std::shared_ptr` or `std::weak_ptr` whose pointee may be `const`,
+    /// given to Rust as a pointer to non-`const`.
+    FromSharedPtrToRust,
+    /// A `std::shared_ptr` or `std::weak_ptr` from Rust, which must not
+    /// reach C++ expecting a pointer to non-`const` if its pointee is
+    /// really `const`.
+    FromRustToSharedPtr,
+    /// A mutable reference to a `std::shared_ptr` or `std::weak_ptr` from
+    /// Rust, which must not reach C++ if its pointee is really `const`.
+    FromRustToMutableSharedPtr,
+    /// A custom smart pointer or `std::unique_ptr` with a custom deleter,
+    /// converted to and from an owning raw pointer by the named generated
+    /// function.
+    FromRawToSmartPointer(String), // unwrapped_type is always Type::Ptr
+    FromSmartPointerToRaw(String), // unwrapped_type is always Type::Ptr
 }
 
 impl CppConversionType {
@@ -42,6 +66,9 @@ impl CppConversionType {
             CppConversionType::FromValueToUniquePtr => CppConversionType::FromUniquePtrToValue,
             CppConversionType::FromPointerToReference => CppConversionType::FromReferenceToPointer,
             CppConversionType::FromReferenceToPointer => CppConversionType::FromPointerToReference,
+            CppConversionType::FromSharedPtrToRust => CppConversionType::FromRustToSharedPtr,
+            CppConversionType::FromRustToSharedPtr => CppConversionType::FromSharedPtrToRust,
+            CppConversionType::FromRustToMutableSharedPtr => CppConversionType::None,
             _ => panic!("Did not expect to have to invert this conversion"),
         }
     }
@@ -60,6 +87,13 @@ pub(crate) enum RustConversionType {
     FromRValueParamToPtr,
     FromReferenceWrapperToPointer, // unwrapped_type is always Type::Ptr
     FromPointerToReferenceWrapper, // unwrapped_type is always Type::Ptr
+    /// The type is the Rust equivalent of the `std::chrono` type.
+    FromChronoToNanos(ChronoType, crate::minisyn::Type),
+    FromNanosToChrono(ChronoType, crate::minisyn::Type),
+    FromTupleToTupleStruct(Vec<crate::minisyn::Type>),
+    FromTupleStructToTuple(Vec<crate::minisyn::Type>),
+    FromOwningPointerToRaw(crate::minisyn::Type),
+    FromRawToOwningPointer(crate::minisyn::Type),
 }
 
 impl RustConversionType {
@@ -148,6 +182,98 @@ impl TypeConversionPolicy {
         }
     }
 
+    /// A `std::chrono` type, which we pass between Rust and C++ as a count
+    /// of nanoseconds. `ty` is the converted type, which for a time point
+    /// names its clock.
+    pub(crate) fn new_for_chrono(chrono_type: ChronoType, ty: &TypePath, is_return: bool) -> Self {
+        let rust_type: crate::minisyn::Type = match chrono_type {
+            ChronoType::Duration => parse_quote! { autocxx::chrono::SignedDuration },
+            ChronoType::TimePoint => Type::Path(ty.clone()).into(),
+        };
+        let (cpp_conversion, rust_conversion) = if is_return {
+            (
+                CppConversionType::FromChronoToNanos,
+                RustConversionType::FromNanosToChrono(chrono_type, rust_type),
+            )
+        } else {
+            (
+                CppConversionType::FromNanosToChrono(chrono_type),
+                RustConversionType::FromChronoToNanos(chrono_type, rust_type),
+            )
+        };
+        TypeConversionPolicy {
+            unwrapped_type: parse_quote! { i64 },
+            cpp_conversion,
+            rust_conversion,
+        }
+    }
+
+    /// A `std::pair` or `std::tuple`, which we pass between Rust and C++
+    /// as a synthetic struct with a field per element, and which Rust
+    /// callers see as a native tuple.
+    pub(crate) fn new_for_tuple(ty: Type, elements: &[Type], is_return: bool) -> Self {
+        let elements = elements.iter().cloned().map(Into::into).collect();
+        let (cpp_conversion, rust_conversion) = if is_return {
+            (
+                CppConversionType::FromTupleToTupleStruct,
+                RustConversionType::FromTupleStructToTuple(elements),
+            )
+        } else {
+            (
+                CppConversionType::FromTupleStructToTuple,
+                RustConversionType::FromTupleToTupleStruct(elements),
+            )
+        };
+        TypeConversionPolicy {
+            unwrapped_type: ty.into(),
+            cpp_conversion,
+            rust_conversion,
+        }
+    }
+
+    /// A `std::shared_ptr` or `std::weak_ptr`, which might point to a
+    /// `const` type. The Rust side always sees a pointer to non-`const`.
+    pub(crate) fn new_for_shared_ptr(ty: Type, is_return: bool) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: ty.into(),
+            cpp_conversion: if is_return {
+                CppConversionType::FromSharedPtrToRust
+            } else {
+                CppConversionType::FromRustToSharedPtr
+            },
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    /// A custom smart pointer or `std::unique_ptr` with a custom deleter,
+    /// which we pass between Rust and C++ as an owning raw pointer, and
+    /// which Rust callers see as an `autocxx::RefPtr` or
+    /// `autocxx::UniquePtrWithDeleter`.
+    pub(crate) fn new_for_owning_pointer(
+        smart_pointer: &QualifiedName,
+        owning_pointer: &OwningPointer,
+        is_return: bool,
+    ) -> Self {
+        let rust_type = owning_pointer.rust_type.clone().into();
+        let (cpp_conversion, rust_conversion) = if is_return {
+            (
+                CppConversionType::FromSmartPointerToRaw(shim_name(smart_pointer, "into_raw")),
+                RustConversionType::FromRawToOwningPointer(rust_type),
+            )
+        } else {
+            (
+                CppConversionType::FromRawToSmartPointer(shim_name(smart_pointer, "from_raw")),
+                RustConversionType::FromOwningPointerToRaw(rust_type),
+            )
+        };
+        let pointee = &owning_pointer.pointee;
+        TypeConversionPolicy {
+            unwrapped_type: parse_quote! { *mut #pointee },
+            cpp_conversion,
+            rust_conversion,
+        }
+    }
+
     pub(crate) fn new_for_placement_return(ty: Type) -> Self {
         TypeConversionPolicy {
             unwrapped_type: ty.into(),
@@ -213,6 +339,38 @@ impl TypeConversionPolicy {
                 | RustConversionType::FromPlacementParamToNewReturn
                 | RustConversionType::FromPointerToReferenceWrapper { .. }
                 | RustConversionType::FromReferenceWrapperToPointer { .. }
+                | RustConversionType::FromOwningPointerToRaw(_)
+        )
+    }
+
+    pub(crate) fn is_chrono(&self) -> bool {
+        matches!(
+            self.cpp_conversion,
+            CppConversionType::FromNanosToChrono(_) | CppConversionType::FromChronoToNanos
+        )
+    }
+
+    pub(crate) fn is_shared_ptr(&self) -> bool {
+        matches!(
+            self.cpp_conversion,
+            CppConversionType::FromSharedPtrToRust
+                | CppConversionType::FromRustToSharedPtr
+                | CppConversionType::FromRustToMutableSharedPtr
+        )
+    }
+
+    pub(crate) fn is_tuple(&self) -> bool {
+        matches!(
+            self.cpp_conversion,
+            CppConversionType::FromTupleStructToTuple | CppConversionType::FromTupleToTupleStruct
+        )
+    }
+
+    pub(crate) fn is_smart_pointer(&self) -> bool {
+        matches!(
+            self.cpp_conversion,
+            CppConversionType::FromRawToSmartPointer(_)
+                | CppConversionType::FromSmartPointerToRaw(_)
         )
     }
 
@@ -241,6 +399,12 @@ pub(crate) enum CppFunctionBody {
     Destructor(Namespace, Ident),
     AllocUninitialized(QualifiedName),
     FreeUninitialized(QualifiedName),
+    UniqueToShared(QualifiedName),
+    MakeShared(QualifiedName),
+    AssignmentOperator,
+    FieldGetter(Ident),
+    FieldSetter(Ident),
+    ConversionOperator(ConversionOperator),
 }
 
 #[derive(Clone, Debug)]
@@ -658,12 +658,12 @@ fn find_explicit_items(
                 fun,
                 ..
             } => match method_kind {
-                MethodKind::Constructor { is_default: true } => {
-                    Some(ExplicitKind::DefaultConstructor)
-                }
-                MethodKind::Constructor { is_default: false } => {
-                    Some(ExplicitKind::OtherConstructor)
-                }
+                MethodKind::Constructor {
+                    is_default: true, ..
+                } => Some(ExplicitKind::DefaultConstructor),
+                MethodKind::Constructor {
+                    is_default: false, ..
+                } => Some(ExplicitKind::OtherConstructor),
                 _ => None,
             }
             .map_or((), |explicit_kind| {
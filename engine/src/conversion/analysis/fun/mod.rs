@@ -19,16 +19,17 @@ use crate::{
             type_converter::{self, add_analysis, TypeConversionContext, TypeConverter},
         },
         api::{
-            ApiName, CastMutability, CppVisibility, DeletedOrDefaulted, FuncToConvert, NullPhase,
-            Provenance, References, SpecialMemberKind, SubclassName, TraitImplSignature,
-            TraitSynthesis, UnsafetyNeeded, Virtualness,
+            ApiName, CastMutability, ConversionOperator, CppVisibility, DeletedOrDefaulted,
+            FuncToConvert, NullPhase, Provenance, References, SpecialMemberKind, SubclassName,
+            TraitImplSignature, TraitSynthesis, UnsafetyNeeded, Virtualness,
         },
         apivec::ApiVec,
         convert_error::ErrorContext,
         convert_error::{ConvertErrorWithContext, ErrorContextType},
         error_reporter::{convert_apis, report_any_error},
+        type_helpers::extract_pinned_mutable_reference_type,
     },
-    known_types::known_types,
+    known_types::{known_types, ChronoType},
     minisyn::minisynize_punctuated,
     types::validate_ident_ok_for_rust,
 };
@@ -39,7 +40,7 @@ use autocxx_parser::{ExternCppType, IncludeCppConfig, UnsafePolicy};
 use function_wrapper::{CppFunction, CppFunctionBody, TypeConversionPolicy};
 use itertools::Itertools;
 use proc_macro2::Span;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
     parse_quote, punctuated::Punctuated, token::Comma, FnArg, Ident, Pat, PatType, ReturnType,
     Type, TypePath, TypePtr, TypeReference, Visibility,
@@ -69,7 +70,7 @@ use super::{
     doc_label::make_doc_attrs,
     pod::{PodAnalysis, PodPhase},
     tdef::TypedefAnalysis,
-    type_converter::{Annotated, PointerTreatment},
+    type_converter::{Annotated, OwningPointer, PointerTreatment},
 };
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -81,7 +82,12 @@ pub(crate) enum ReceiverMutability {
 #[derive(Clone, Debug)]
 pub(crate) enum MethodKind {
     Normal,
-    Constructor { is_default: bool },
+    Constructor {
+        is_default: bool,
+        /// Whether this takes a single plain value, such that we can
+        /// also expose it as a conversion using `From`.
+        is_converting: bool,
+    },
     Static,
     Virtual(ReceiverMutability),
     PureVirtual(ReceiverMutability),
@@ -95,6 +101,8 @@ pub(crate) enum TraitMethodKind {
     Destructor,
     Alloc,
     Dealloc,
+    UniqueToShared,
+    ConversionOperator,
 }
 
 #[derive(Clone, Debug)]
@@ -161,6 +169,9 @@ pub(crate) struct FnAnalysis {
     pub(crate) requires_unsafe: UnsafetyNeeded,
     pub(crate) vis: Visibility,
     pub(crate) cpp_wrapper: Option<CppFunction>,
+    /// For a constructor, an additional C++ wrapper which instead creates
+    /// the object within a `std::shared_ptr` using `std::make_shared`.
+    pub(crate) make_shared_wrapper: Option<CppFunction>,
     pub(crate) deps: HashSet<QualifiedName>,
     /// Some methods still need to be recorded because we want
     /// to (a) generate the ability to call superclasses, (b) create
@@ -328,7 +339,9 @@ impl<'a> FnAnalyzer<'a> {
             Api::enum_unchanged,
             Api::typedef_unchanged,
         );
-        let mut results = me.add_constructors_present(results);
+        let results = me.add_constructors_present(results);
+        let results = handle_shared_ptr_construction(results);
+        let mut results = dedupe_converting_constructors(results);
         me.add_subclass_constructors(&mut results);
         results.extend(me.extra_apis.into_iter().map(add_analysis));
         results
@@ -443,10 +456,87 @@ impl<'a> FnAnalyzer<'a> {
     ) -> Result<Annotated<Box<Type>>, ConvertErrorFromCpp> {
         let ctx = TypeConversionContext::OuterType { pointer_treatment };
         let mut annotated = self.type_converter.convert_boxed_type(ty, ns, &ctx)?;
+        if let Some(tn) = annotated.types_encountered.iter().find(|tn| {
+            self.type_converter
+                .tuple_elements(tn)
+                .is_some_and(|elements| {
+                    !elements.iter().all(|el| self.permissible_within_tuple(el))
+                })
+        }) {
+            return Err(ConvertErrorFromCpp::TupleContainingNonPodType(tn.clone()));
+        }
         self.extra_apis.append(&mut annotated.extra_apis);
         Ok(annotated)
     }
 
+    fn permissible_within_tuple(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(p) => {
+                let tn = QualifiedName::from_type_path(p);
+                self.pod_safe_types.contains(&tn) && known_types().permissible_within_tuple(&tn)
+            }
+            _ => false,
+        }
+    }
+
+    /// `std::pair` and `std::tuple` are converted to Rust tuples when passed
+    /// by value or const reference. If this is such a type, returns the
+    /// synthetic struct which we pass through cxx, and the element types.
+    fn tuple_by_value_or_const_ref(&self, ty: &Type) -> Option<(Type, &[Type])> {
+        match ty {
+            Type::Reference(TypeReference {
+                mutability: None,
+                elem,
+                ..
+            }) => self.tuple_by_value_or_const_ref(elem),
+            Type::Path(p) => self
+                .type_converter
+                .tuple_elements(&QualifiedName::from_type_path(p))
+                .map(|elements| (ty.clone(), elements)),
+            _ => None,
+        }
+    }
+
+    /// Custom smart pointers are converted to `autocxx::RefPtr` when passed
+    /// by value or const reference, and `std::unique_ptr`s with custom
+    /// deleters to `autocxx::UniquePtrWithDeleter` when passed by value.
+    /// If this is such a type, returns the synthetic type representing it,
+    /// and how Rust sees it.
+    fn owning_pointer_param(&self, ty: &Type) -> Option<(QualifiedName, &OwningPointer)> {
+        match ty {
+            Type::Reference(TypeReference {
+                mutability: None,
+                elem,
+                ..
+            }) => self
+                .owning_pointer_param(elem)
+                .filter(|(_, owning_pointer)| owning_pointer.by_const_ref),
+            Type::Path(p) => {
+                let tn = QualifiedName::from_type_path(p);
+                self.type_converter
+                    .owning_pointer(&tn)
+                    .map(|owning_pointer| (tn, owning_pointer))
+            }
+            _ => None,
+        }
+    }
+
+    /// `std::pair` and `std::tuple` can't be used other than as handled by
+    /// [`Self::tuple_by_value_or_const_ref`] and return values, because the
+    /// synthetic struct we use isn't the same type as far as C++ is concerned.
+    fn reject_tuple_types(
+        &self,
+        types: &HashSet<QualifiedName>,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        match types
+            .iter()
+            .find(|tn| self.type_converter.tuple_elements(tn).is_some())
+        {
+            Some(tn) => Err(ConvertErrorFromCpp::UnsupportedTupleUsage(tn.clone())),
+            None => Ok(()),
+        }
+    }
+
     fn get_cxx_bridge_name(
         &mut self,
         type_name: Option<&str>,
@@ -815,6 +905,24 @@ impl<'a> FnAnalyzer<'a> {
             }
         };
 
+        // Copy and move assignment operators become copy_assign and move_assign
+        // methods. We can't support assignment from other types, because those
+        // would each need a name. We don't use these for `Clone::clone_from`,
+        // since the `Clone` impl for `CppPin<T>` is generic over any `T: CopyNew`
+        // and can't tell whether `T` also has a copy assignment operator.
+        let assignment_operator_name = match (&fun.special_member, &self_ty) {
+            (Some(SpecialMemberKind::AssignmentOperator), Some(self_ty))
+                if assigns_from_self_type(fun, self_ty) =>
+            {
+                Some(if fun.references.rvalue_ref_params.is_empty() {
+                    "copy_assign"
+                } else {
+                    "move_assign"
+                })
+            }
+            _ => None,
+        };
+
         // Part two, work out if this is a function, or method, or whatever.
         // First determine if this is actually a trait implementation.
         let trait_details = self.trait_creation_details_for_synthetic_function(
@@ -823,7 +931,7 @@ impl<'a> FnAnalyzer<'a> {
             &ideal_rust_name,
             &self_ty,
         );
-        let (kind, error_context, rust_name) = if let Some(trait_details) = trait_details {
+        let (mut kind, error_context, rust_name) = if let Some(trait_details) = trait_details {
             trait_details
         } else if let Some(self_ty) = self_ty {
             // Some kind of method or static method.
@@ -905,7 +1013,10 @@ impl<'a> FnAnalyzer<'a> {
                     (
                         FnKind::Method {
                             impl_for: self_ty,
-                            method_kind: MethodKind::Constructor { is_default: false },
+                            method_kind: MethodKind::Constructor {
+                                is_default: false,
+                                is_converting: false,
+                            },
                         },
                         error_context,
                         rust_name,
@@ -958,7 +1069,12 @@ impl<'a> FnAnalyzer<'a> {
                             fun.special_member,
                             Some(SpecialMemberKind::DefaultConstructor)
                         ),
+                        // Determined once we've analyzed the parameters.
+                        is_converting: false,
                     }
+                } else if let Some(assignment_operator_name) = assignment_operator_name {
+                    rust_name = assignment_operator_name.to_string();
+                    MethodKind::Normal
                 } else if is_static_method {
                     MethodKind::Static
                 } else {
@@ -1138,13 +1254,16 @@ impl<'a> FnAnalyzer<'a> {
         } else if matches!(
             fun.special_member,
             Some(SpecialMemberKind::AssignmentOperator)
-        ) {
+        ) && assignment_operator_name.is_none()
+        {
             // Be careful with the order of this if-else tree. Anything above here means we won't
             // treat it as an assignment operator, but anything below we still consider when
             // deciding which other C++ special member functions are implicitly defined.
             set_ignore_reason(ConvertErrorFromCpp::AssignmentOperator)
         } else if fun.references.rvalue_ref_return {
             set_ignore_reason(ConvertErrorFromCpp::RValueReturn)
+        } else if fun.returns_weak_ptr_to_const {
+            set_ignore_reason(ConvertErrorFromCpp::WeakPtrToConstReturn)
         } else if matches!(fun.is_deleted, DeletedOrDefaulted::Deleted) {
             set_ignore_reason(ConvertErrorFromCpp::Deleted)
         } else {
@@ -1197,9 +1316,16 @@ impl<'a> FnAnalyzer<'a> {
         let mut cxxbridge_name = make_ident(&cxxbridge_name);
 
         // Analyze the return type, just as we previously did for the
-        // parameters.
+        // parameters. Assignment operators return a reference to `*this`,
+        // which we discard since Rust callers already have it (and since we
+        // couldn't express its lifetime alongside that of the other argument).
+        let output = if assignment_operator_name.is_some() {
+            ReturnType::Default
+        } else {
+            fun.output.clone().into()
+        };
         let mut return_analysis = self
-            .convert_return_type(&fun.output, ns, &fun.references, sophistication)
+            .convert_return_type(&output, ns, &fun.references, sophistication)
             .unwrap_or_else(|err| {
                 set_ignore_reason(err);
                 ReturnTypeAnalysis::default()
@@ -1217,6 +1343,29 @@ impl<'a> FnAnalyzer<'a> {
 
         let requires_unsafe = self.should_be_unsafe(&param_details, &kind);
 
+        // A C++ converting constructor - a non-`explicit` one taking a
+        // single argument - can also be represented as `From`, so long as
+        // the argument is a plain value which needs no Rust-side conversion.
+        if let FnKind::Method {
+            method_kind:
+                MethodKind::Constructor {
+                    is_default: false,
+                    ref mut is_converting,
+                },
+            ..
+        } = kind
+        {
+            *is_converting = fun.special_member.is_none()
+                && !fun.is_explicit
+                && !matches!(requires_unsafe, UnsafetyNeeded::Always)
+                && matches!(param_details.as_slice(), [_, pd]
+                if !pd.conversion.rust_work_needed()
+                    && !matches!(
+                        pd.conversion.converted_rust_type(),
+                        Type::Reference(_) | Type::Ptr(_)
+                    ));
+        }
+
         // The following sections reject some types of function because of the arrangement
         // of Rust references. We could lift these restrictions when/if we switch to using
         // CppRef to represent C++ references.
@@ -1353,6 +1502,9 @@ impl<'a> FnAnalyzer<'a> {
                         ),
                         CppFunctionKind::Function,
                     ),
+                    FnKind::Method { .. } if assignment_operator_name.is_some() => {
+                        (CppFunctionBody::AssignmentOperator, CppFunctionKind::Method)
+                    }
                     FnKind::Method { .. } => (
                         CppFunctionBody::FunctionCall(ns.clone(), cpp_construction_ident),
                         CppFunctionKind::Method,
@@ -1444,6 +1596,7 @@ impl<'a> FnAnalyzer<'a> {
             requires_unsafe,
             vis: vis.into(),
             cpp_wrapper,
+            make_shared_wrapper: None,
             deps,
             ignore_reason,
             externally_callable,
@@ -1590,6 +1743,58 @@ impl<'a> FnAnalyzer<'a> {
                 "free_uninitialized_cpp_storage",
                 TraitMethodKind::Dealloc,
             ),
+            TraitSynthesis::UniqueToShared(ty) => {
+                let rust_name = self
+                    .get_function_overload_name(ty.get_namespace(), ideal_rust_name.to_string());
+                let typ = ty.to_type_path();
+                Some((
+                    FnKind::TraitMethod {
+                        impl_for: ty.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: Type::Path(typ).into(),
+                                trait_signature: parse_quote! { autocxx::UniqueToShared },
+                                unsafety: None,
+                            },
+                            avoid_self: true,
+                            method_name: make_ident("unique_to_shared"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                        }),
+                        kind: TraitMethodKind::UniqueToShared,
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
+            TraitSynthesis::ConversionOperator(conversion_operator) => {
+                let rust_name = self.get_function_overload_name(ns, ideal_rust_name.to_string());
+                let from_type = self_ty.as_ref().unwrap();
+                let from_type_path = from_type.to_type_path();
+                let ty: Type = match conversion_operator {
+                    ConversionOperator::Bool => parse_quote! { bool },
+                    ConversionOperator::String => parse_quote! { cxx::UniquePtr<cxx::CxxString> },
+                };
+                Some((
+                    FnKind::TraitMethod {
+                        kind: TraitMethodKind::ConversionOperator,
+                        impl_for: from_type.clone(),
+                        details: Box::new(TraitMethodDetails {
+                            trt: TraitImplSignature {
+                                ty: ty.into(),
+                                trait_signature: parse_quote! { From<&#from_type_path> },
+                                unsafety: None,
+                            },
+                            avoid_self: true,
+                            method_name: make_ident("from"),
+                            parameter_reordering: None,
+                            trait_call_is_unsafe: false,
+                        }),
+                    },
+                    ErrorContext::new_for_item(make_ident(&rust_name)),
+                    rust_name,
+                ))
+            }
         })
     }
 
@@ -1726,6 +1931,21 @@ impl<'a> FnAnalyzer<'a> {
                     self_type.is_some(),
                     is_placement_return_destination,
                 );
+                if !conversion.is_chrono() {
+                    reject_chrono_types(&annotated_type.types_encountered)?;
+                }
+                if !conversion.is_tuple() {
+                    self.reject_tuple_types(&annotated_type.types_encountered)?;
+                }
+                // A std::chrono type, std::pair, std::tuple or custom smart
+                // pointer passed by const reference is passed by value in Rust.
+                let has_lifetime = matches!(
+                    annotated_type.kind,
+                    type_converter::TypeKind::Reference
+                        | type_converter::TypeKind::MutableReference
+                ) && !conversion.is_chrono()
+                    && !conversion.is_tuple()
+                    && !conversion.is_smart_pointer();
                 let new_ty = annotated_type.ty;
                 pt.pat = Box::new(new_pat.clone());
                 pt.ty = new_ty;
@@ -1745,11 +1965,7 @@ impl<'a> FnAnalyzer<'a> {
                         self_type,
                         name: new_pat.into(),
                         conversion,
-                        has_lifetime: matches!(
-                            annotated_type.kind,
-                            type_converter::TypeKind::Reference
-                                | type_converter::TypeKind::MutableReference
-                        ),
+                        has_lifetime,
                         is_mutable_reference: matches!(
                             annotated_type.kind,
                             type_converter::TypeKind::MutableReference
@@ -1785,6 +2001,31 @@ impl<'a> FnAnalyzer<'a> {
             matches!(annotated_type.kind, type_converter::TypeKind::Reference) || is_self;
         let rust_conversion_forced = force_rust_conversion.is_some();
         let ty = &*annotated_type.ty;
+        if matches!(sophistication, TypeConversionSophistication::Regular) && !is_self {
+            if let Some((chrono_type, chrono_ty)) = chrono_type_by_value_or_const_ref(ty) {
+                return TypeConversionPolicy::new_for_chrono(chrono_type, chrono_ty, false);
+            }
+            if let Some((tuple_type, elements)) = self.tuple_by_value_or_const_ref(ty) {
+                return TypeConversionPolicy::new_for_tuple(tuple_type, elements, false);
+            }
+            if is_shared_ptr_by_value_or_const_ref(ty) {
+                return TypeConversionPolicy::new_for_shared_ptr(ty.clone(), false);
+            }
+            if is_shared_ptr_by_mutable_ref(ty) {
+                return TypeConversionPolicy::new(
+                    ty.clone(),
+                    CppConversionType::FromRustToMutableSharedPtr,
+                    RustConversionType::None,
+                );
+            }
+            if let Some((smart_pointer, owning_pointer)) = self.owning_pointer_param(ty) {
+                return TypeConversionPolicy::new_for_owning_pointer(
+                    &smart_pointer,
+                    owning_pointer,
+                    false,
+                );
+            }
+        }
         if let Some(holder_id) = is_subclass_holder {
             let subclass = SubclassName::from_holder_name(holder_id);
             return {
@@ -1930,6 +2171,74 @@ impl<'a> FnAnalyzer<'a> {
                     self.convert_boxed_type(boxed_type.clone(), ns, references.return_treatment())?;
                 let boxed_type = annotated_type.ty;
                 let ty: &Type = boxed_type.as_ref();
+                let chrono_type = match ty {
+                    Type::Path(p)
+                        if matches!(sophistication, TypeConversionSophistication::Regular) =>
+                    {
+                        known_types()
+                            .chrono_type(&QualifiedName::from_type_path(p))
+                            .map(|chrono_type| (chrono_type, p))
+                    }
+                    _ => None,
+                };
+                if let Some((chrono_type, chrono_ty)) = chrono_type {
+                    return Ok(ReturnTypeAnalysis {
+                        rt: ReturnType::Type(*rarrow, boxed_type.clone()),
+                        conversion: Some(TypeConversionPolicy::new_for_chrono(
+                            chrono_type,
+                            chrono_ty,
+                            true,
+                        )),
+                        deps: annotated_type.types_encountered,
+                        ..Default::default()
+                    });
+                }
+                reject_chrono_types(&annotated_type.types_encountered)?;
+                let tuple_elements = match ty {
+                    Type::Path(p)
+                        if matches!(sophistication, TypeConversionSophistication::Regular) =>
+                    {
+                        self.type_converter
+                            .tuple_elements(&QualifiedName::from_type_path(p))
+                    }
+                    _ => None,
+                };
+                if let Some(elements) = tuple_elements {
+                    return Ok(ReturnTypeAnalysis {
+                        rt: ReturnType::Type(*rarrow, boxed_type.clone()),
+                        conversion: Some(TypeConversionPolicy::new_for_tuple(
+                            ty.clone(),
+                            elements,
+                            true,
+                        )),
+                        deps: annotated_type.types_encountered,
+                        ..Default::default()
+                    });
+                }
+                self.reject_tuple_types(&annotated_type.types_encountered)?;
+                let owning_pointer = match ty {
+                    Type::Path(p)
+                        if matches!(sophistication, TypeConversionSophistication::Regular) =>
+                    {
+                        let tn = QualifiedName::from_type_path(p);
+                        self.type_converter
+                            .owning_pointer(&tn)
+                            .map(|owning_pointer| (tn, owning_pointer))
+                    }
+                    _ => None,
+                };
+                if let Some((smart_pointer, owning_pointer)) = owning_pointer {
+                    return Ok(ReturnTypeAnalysis {
+                        rt: ReturnType::Type(*rarrow, boxed_type.clone()),
+                        conversion: Some(TypeConversionPolicy::new_for_owning_pointer(
+                            &smart_pointer,
+                            owning_pointer,
+                            true,
+                        )),
+                        deps: annotated_type.types_encountered,
+                        ..Default::default()
+                    });
+                }
                 match ty {
                     Type::Path(p)
                         if !self
@@ -1992,18 +2301,30 @@ impl<'a> FnAnalyzer<'a> {
                         );
                         let was_reference = was_mutable_reference
                             || matches!(annotated_type.kind, type_converter::TypeKind::Reference);
-                        let conversion = Some(
-                            if was_reference
-                                && matches!(
-                                    self.config.unsafe_policy,
-                                    UnsafePolicy::ReferencesWrappedAllFunctionsSafe
-                                )
+                        let is_shared_ptr = match ty {
+                            Type::Path(p)
+                                if !was_reference
+                                    && matches!(
+                                        sophistication,
+                                        TypeConversionSophistication::Regular
+                                    ) =>
                             {
-                                TypeConversionPolicy::return_reference_into_wrapper(ty.clone())
-                            } else {
-                                TypeConversionPolicy::new_unconverted(ty.clone())
-                            },
-                        );
+                                known_types().is_shared_ptr(&QualifiedName::from_type_path(p))
+                            }
+                            _ => false,
+                        };
+                        let conversion = Some(if is_shared_ptr {
+                            TypeConversionPolicy::new_for_shared_ptr(ty.clone(), true)
+                        } else if was_reference
+                            && matches!(
+                                self.config.unsafe_policy,
+                                UnsafePolicy::ReferencesWrappedAllFunctionsSafe
+                            )
+                        {
+                            TypeConversionPolicy::return_reference_into_wrapper(ty.clone())
+                        } else {
+                            TypeConversionPolicy::new_unconverted(ty.clone())
+                        });
                         ReturnTypeAnalysis {
                             rt: ReturnType::Type(*rarrow, boxed_type),
                             conversion,
@@ -2170,6 +2491,8 @@ impl<'a> FnAnalyzer<'a> {
                         cpp_vis: CppVisibility::Public,
                         special_member: Some(special_member),
                         unused_template_param: false,
+                        returns_weak_ptr_to_const: false,
+                        is_explicit: false,
                         references,
                         original_name: None,
                         synthesized_this_type: None,
@@ -2247,8 +2570,15 @@ impl Api<FnPhase> {
                 ..
             } | Api::StringConstructor { .. }
                 | Api::ConcreteType { .. }
+                | Api::Tuple { .. }
+                | Api::Variant { .. }
+                | Api::AssociativeContainer { .. }
+                | Api::SmartPointer { .. }
+                | Api::UniquePtrWithDeleter { .. }
+                | Api::ContainerElement { .. }
                 | Api::CType { .. }
                 | Api::RustSubclassFn { .. }
+                | Api::RustTrait { .. }
                 | Api::Subclass { .. }
                 | Api::Struct {
                     analysis: PodAndDepAnalysis {
@@ -2275,6 +2605,59 @@ impl Api<FnPhase> {
     }
 }
 
+/// `std::chrono` types are converted to Rust equivalents when passed by
+/// value or const reference.
+fn chrono_type_by_value_or_const_ref(ty: &Type) -> Option<(ChronoType, &TypePath)> {
+    match ty {
+        Type::Reference(TypeReference {
+            mutability: None,
+            elem,
+            ..
+        }) => chrono_type_by_value_or_const_ref(elem),
+        Type::Path(p) => known_types()
+            .chrono_type(&QualifiedName::from_type_path(p))
+            .map(|chrono_type| (chrono_type, p)),
+        _ => None,
+    }
+}
+
+fn is_shared_ptr_by_value_or_const_ref(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(TypeReference {
+            mutability: None,
+            elem,
+            ..
+        }) => is_shared_ptr_by_value_or_const_ref(elem),
+        Type::Path(p) => known_types().is_shared_ptr(&QualifiedName::from_type_path(p)),
+        _ => false,
+    }
+}
+
+/// A `std::shared_ptr` or `std::weak_ptr` passed by mutable reference, which
+/// C++ can only accept if its pointee isn't `const`.
+fn is_shared_ptr_by_mutable_ref(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => matches!(
+            extract_pinned_mutable_reference_type(p),
+            Some(Type::Path(p)) if known_types().is_shared_ptr(&QualifiedName::from_type_path(p))
+        ),
+        _ => false,
+    }
+}
+
+/// `std::chrono` types can't be used other than as handled by
+/// [`chrono_type_by_value_or_const_ref`] and return values, because we
+/// only know how to represent them to Rust in those positions.
+fn reject_chrono_types(types: &HashSet<QualifiedName>) -> Result<(), ConvertErrorFromCpp> {
+    match types
+        .iter()
+        .find(|tn| known_types().chrono_type(tn).is_some())
+    {
+        Some(tn) => Err(ConvertErrorFromCpp::UnsupportedChronoUsage(tn.clone())),
+        None => Ok(()),
+    }
+}
+
 fn extract_type_from_pinned_mut_ref(ty: &TypePath) -> Type {
     match ty
         .path
@@ -2342,6 +2725,189 @@ impl HasFieldsAndBases for Api<FnPrePhase2> {
     }
 }
 
+/// `std::shared_ptr` needs to be able to call the destructor of its pointee.
+/// For types which have an accessible destructor, add a `std::make_shared`
+/// wrapper to each constructor. For other types, remove the functions which
+/// move a `std::unique_ptr` into a `std::shared_ptr`.
+fn handle_shared_ptr_construction(apis: ApiVec<FnPrePhase2>) -> ApiVec<FnPrePhase2> {
+    let types_without_destructors: HashSet<_> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                name,
+                analysis:
+                    PodAndConstructorAnalysis {
+                        constructors:
+                            PublicConstructors {
+                                destructor: false, ..
+                            },
+                        ..
+                    },
+                ..
+            } => Some(name.name.clone()),
+            _ => None,
+        })
+        .collect();
+    apis.into_iter()
+        .filter(|api| {
+            !matches!(
+                api,
+                Api::Function {
+                    analysis: FnAnalysis {
+                        kind: FnKind::TraitMethod {
+                            impl_for,
+                            kind: TraitMethodKind::UniqueToShared,
+                            ..
+                        },
+                        ..
+                    },
+                    ..
+                } if types_without_destructors.contains(impl_for)
+            )
+        })
+        .map(|mut api| {
+            if let Api::Function {
+                analysis:
+                    FnAnalysis {
+                        kind:
+                            FnKind::Method {
+                                impl_for,
+                                method_kind: MethodKind::Constructor { .. },
+                                ..
+                            },
+                        cpp_wrapper: Some(cpp_wrapper),
+                        make_shared_wrapper,
+                        ..
+                    },
+                ..
+            } = &mut api
+            {
+                if matches!(cpp_wrapper.payload, CppFunctionBody::PlacementNew(..))
+                    && !types_without_destructors.contains(impl_for)
+                {
+                    *make_shared_wrapper = Some(make_shared_wrapper_for(cpp_wrapper, impl_for));
+                }
+            }
+            api
+        })
+        .collect()
+}
+
+/// A wrapper which creates an object within a `std::shared_ptr`, taking the
+/// same arguments as the wrapper for its constructor, except for the
+/// memory in which to construct it.
+fn make_shared_wrapper_for(constructor: &CppFunction, ty: &QualifiedName) -> CppFunction {
+    let ty_path = ty.to_type_path();
+    CppFunction {
+        payload: CppFunctionBody::MakeShared(ty.clone()),
+        wrapper_function_name: make_ident(format!(
+            "{}_make_shared",
+            constructor.wrapper_function_name
+        )),
+        original_cpp_name: constructor.original_cpp_name.clone(),
+        return_conversion: Some(TypeConversionPolicy::new_unconverted(
+            parse_quote! { cxx::SharedPtr<#ty_path> },
+        )),
+        argument_conversion: constructor.argument_conversion[1..].to_vec(),
+        kind: CppFunctionKind::Function,
+        pass_obs_field: false,
+        qualification: None,
+    }
+}
+
+/// Keep only the first converting constructor for each type and argument
+/// type, since distinct C++ types (such as `char` and `int8_t`) may become
+/// the same Rust type, and `From` can only be implemented once for each.
+/// Constructors taking typedefs aren't used at all, since we can't tell
+/// which other argument types those are the same as.
+fn dedupe_converting_constructors(apis: ApiVec<FnPrePhase2>) -> ApiVec<FnPrePhase2> {
+    let typedefs: HashSet<QualifiedName> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Typedef { name, .. } => Some(name.name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut conversions_found = HashSet::new();
+    apis.into_iter()
+        .map(|mut api| {
+            if let Api::Function {
+                analysis:
+                    FnAnalysis {
+                        kind:
+                            FnKind::Method {
+                                impl_for,
+                                method_kind: MethodKind::Constructor { is_converting, .. },
+                            },
+                        param_details,
+                        ..
+                    },
+                ..
+            } = &mut api
+            {
+                if *is_converting {
+                    let arg_ty = param_details
+                        .iter()
+                        .find(|pd| !pd.is_placement_return_destination)
+                        .map(|pd| pd.conversion.converted_rust_type());
+                    *is_converting = match arg_ty {
+                        Some(Type::Path(typ))
+                            if typedefs.contains(&QualifiedName::from_type_path(&typ)) =>
+                        {
+                            false
+                        }
+                        Some(ty) => {
+                            let keys = conversion_keys(&ty)
+                                .into_iter()
+                                .map(|key| (impl_for.clone(), key))
+                                .collect_vec();
+                            let is_new = !keys.iter().any(|key| conversions_found.contains(key));
+                            if is_new {
+                                conversions_found.extend(keys);
+                            }
+                            is_new
+                        }
+                        None => false,
+                    };
+                }
+            }
+            api
+        })
+        .collect()
+}
+
+/// The Rust types which an argument type might be. `c_char` is the same
+/// type as either `i8` or `u8`, depending on the platform.
+fn conversion_keys(ty: &Type) -> Vec<String> {
+    match ty {
+        Type::Path(typ)
+            if typ
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "c_char") =>
+        {
+            vec!["i8".to_string(), "u8".to_string()]
+        }
+        _ => vec![ty.to_token_stream().to_string()],
+    }
+}
+
+/// Whether an assignment operator takes another instance of its own type,
+/// i.e. it's a copy or move assignment operator.
+fn assigns_from_self_type(fun: &FuncToConvert, self_ty: &QualifiedName) -> bool {
+    match fun.inputs.iter().nth(1).map(|arg| &arg.0) {
+        Some(FnArg::Typed(PatType { ty, .. })) => match ty.as_ref() {
+            Type::Ptr(TypePtr { elem, .. }) => match elem.as_ref() {
+                Type::Path(typ) => QualifiedName::from_type_path(typ) == *self_ty,
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 /// Stringify a function argument for diagnostics
 fn describe_arg(arg: &FnArg) -> String {
     match arg {
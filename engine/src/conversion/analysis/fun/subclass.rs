@@ -66,6 +66,8 @@ pub(super) fn create_subclass_fn_wrapper(
         cpp_vis: CppVisibility::Public,
         special_member: None,
         unused_template_param: fun.unused_template_param,
+        returns_weak_ptr_to_const: fun.returns_weak_ptr_to_const,
+        is_explicit: fun.is_explicit,
         original_name: None,
         references: fun.references.clone(),
         add_to_trait: fun.add_to_trait.clone(),
@@ -249,6 +251,8 @@ pub(super) fn create_subclass_constructor(
         special_member: fun.special_member.clone(),
         original_name: None,
         unused_template_param: fun.unused_template_param,
+        returns_weak_ptr_to_const: fun.returns_weak_ptr_to_const,
+        is_explicit: fun.is_explicit,
         references: fun.references.clone(),
         synthesized_this_type: Some(cpp.clone()),
         self_ty: Some(cpp),
@@ -10,10 +10,13 @@ pub(crate) mod abstract_types;
 pub(crate) mod allocators;
 pub(crate) mod casts;
 pub(crate) mod constructor_deps;
+pub(crate) mod conversion_operators;
 pub(crate) mod ctypes;
 pub(crate) mod deps;
 mod depth_first;
 mod doc_label;
+pub(crate) mod element_kinds;
+pub(crate) mod field_accessors;
 pub(crate) mod fun;
 pub(crate) mod gc;
 mod name_check;
@@ -66,11 +66,18 @@ pub(crate) fn check_names(apis: ApiVec<FnPhase>) -> ApiVec<FnPhase> {
             Ok(Box::new(std::iter::once(api)))
         }
         Api::ConcreteType { .. }
+        | Api::Tuple { .. }
+        | Api::Variant { .. }
+        | Api::AssociativeContainer { .. }
+        | Api::SmartPointer { .. }
+        | Api::UniquePtrWithDeleter { .. }
+        | Api::ContainerElement { .. }
         | Api::CType { .. }
         | Api::StringConstructor { .. }
         | Api::RustType { .. }
         | Api::RustSubclassFn { .. }
         | Api::RustFn { .. }
+        | Api::RustTrait { .. }
         | Api::SubclassTraitItem { .. }
         | Api::ExternCppType { .. }
         | Api::IgnoredItem { .. } => Ok(Box::new(std::iter::once(api))),
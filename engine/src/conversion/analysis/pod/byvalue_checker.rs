@@ -12,12 +12,13 @@ use crate::{
     conversion::{
         analysis::tdef::TypedefPhase,
         api::{Api, TypedefKind},
+        type_helpers::extract_manually_drop_inner_type,
     },
     types::{Namespace, QualifiedName},
 };
 use autocxx_parser::IncludeCppConfig;
 use std::collections::HashMap;
-use syn::{ItemStruct, Type};
+use syn::{Field, ItemStruct, Type};
 
 #[derive(Clone)]
 enum PodState {
@@ -131,7 +132,11 @@ impl ByValueChecker {
         }
         for api in apis.iter() {
             if let Api::Struct { details, .. } = api {
-                byvalue_checker.ingest_struct(&details.item, api.name().get_namespace())
+                byvalue_checker.ingest_struct(
+                    &details.item,
+                    details.all_fields(),
+                    api.name().get_namespace(),
+                )
             }
         }
         let pod_requests = config
@@ -145,22 +150,21 @@ impl ByValueChecker {
         Ok(byvalue_checker)
     }
 
-    fn ingest_struct(&mut self, def: &ItemStruct, ns: &Namespace) {
+    fn ingest_struct<'a>(
+        &mut self,
+        def: &ItemStruct,
+        fields: impl Iterator<Item = &'a Field>,
+        ns: &Namespace,
+    ) {
         // For this struct, work out whether it _could_ be safe as a POD.
         let tyname = QualifiedName::new(ns, def.ident.clone().into());
         let mut field_safety_problem = PodState::SafeToBePod;
-        let fieldlist = Self::get_field_types(def);
+        let fieldlist = Self::get_field_types(fields);
         for ty_id in &fieldlist {
             match self.results.get(ty_id) {
                 None if ty_id.get_final_item() == "__BindgenUnionField" => {
                     field_safety_problem = PodState::UnsafeToBePod(format!(
-                        "Type {tyname} could not be POD because it is a union"
-                    ));
-                    break;
-                }
-                None if ty_id.get_final_item() == "__BindgenBitfieldUnit" => {
-                    field_safety_problem = PodState::UnsafeToBePod(format!(
-                        "Type {tyname} could not be POD because it is a bitfield"
+                        "Type {tyname} could not be POD because it is a union which bindgen could not represent as a Rust union"
                     ));
                     break;
                 }
@@ -253,10 +257,11 @@ impl ByValueChecker {
     /// It would be nice to unify them. However, this version only cares about spotting
     /// fields which may be non-POD, so can largely concern itself with just `Type::Path`
     /// fields.
-    fn get_field_types(def: &ItemStruct) -> Vec<QualifiedName> {
+    fn get_field_types<'a>(fields: impl Iterator<Item = &'a Field>) -> Vec<QualifiedName> {
         let mut results = Vec::new();
-        for f in &def.fields {
-            let fty = &f.ty;
+        for f in fields {
+            // Union members may be wrapped in ManuallyDrop.
+            let fty = extract_manually_drop_inner_type(&f.ty).unwrap_or(&f.ty);
             if let Type::Path(p) = fty {
                 results.push(QualifiedName::from_type_path(p));
             }
@@ -303,7 +308,22 @@ mod tests {
             }
         };
         let t_id = ty_from_ident(&t.ident);
-        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
+        bvc.satisfy_requests(vec![t_id.clone()]).unwrap();
+        assert!(bvc.is_pod(&t_id));
+    }
+
+    #[test]
+    fn test_union_members() {
+        let mut bvc = ByValueChecker::new();
+        let t: ItemStruct = parse_quote! {
+            struct Foo {
+                a: i32,
+                b: ::std::mem::ManuallyDrop<i64>,
+            }
+        };
+        let t_id = ty_from_ident(&t.ident);
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
         bvc.satisfy_requests(vec![t_id.clone()]).unwrap();
         assert!(bvc.is_pod(&t_id));
     }
@@ -317,7 +337,7 @@ mod tests {
                 b: i64,
             }
         };
-        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
         let t: ItemStruct = parse_quote! {
             struct Bar {
                 a: Foo,
@@ -325,7 +345,7 @@ mod tests {
             }
         };
         let t_id = ty_from_ident(&t.ident);
-        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
         bvc.satisfy_requests(vec![t_id.clone()]).unwrap();
         assert!(bvc.is_pod(&t_id));
     }
@@ -340,7 +360,7 @@ mod tests {
             }
         };
         let t_id = ty_from_ident(&t.ident);
-        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
         bvc.satisfy_requests(vec![t_id.clone()]).unwrap();
         assert!(bvc.is_pod(&t_id));
     }
@@ -355,7 +375,7 @@ mod tests {
             }
         };
         let t_id = ty_from_ident(&t.ident);
-        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_struct(&t, t.fields.iter(), &Namespace::new());
         assert!(bvc.satisfy_requests(vec![t_id]).is_err());
     }
 }
@@ -23,6 +23,7 @@ use crate::{
         convert_error::{ConvertErrorWithContext, ErrorContext},
         error_reporter::convert_apis,
         parse::BindgenSemanticAttributes,
+        type_helpers::extract_manually_drop_inner_type,
         ConvertErrorFromCpp,
     },
     types::{Namespace, QualifiedName},
@@ -152,7 +153,7 @@ fn analyze_struct(
     let field_conversion_errors = get_struct_field_types(
         type_converter,
         name.name.get_namespace(),
-        &details.item,
+        &details,
         &mut field_deps,
         &mut field_definition_deps,
         &mut field_info,
@@ -208,21 +209,24 @@ fn analyze_struct(
 fn get_struct_field_types(
     type_converter: &mut TypeConverter,
     ns: &Namespace,
-    s: &ItemStruct,
+    details: &StructDetails,
     field_deps: &mut HashSet<QualifiedName>,
     field_definition_deps: &mut HashSet<QualifiedName>,
     field_info: &mut Vec<FieldInfo>,
     extra_apis: &mut ApiVec<NullPhase>,
 ) -> Vec<ConvertErrorFromCpp> {
     let mut convert_errors = Vec::new();
+    let s = &details.item;
     let struct_type_params = s
         .generics
         .type_params()
         .map(|tp| tp.ident.clone())
         .collect();
     let type_conversion_context = TypeConversionContext::WithinStructField { struct_type_params };
-    for f in &s.fields {
-        let annotated = type_converter.convert_type(f.ty.clone(), ns, &type_conversion_context);
+    for f in details.all_fields() {
+        // Union members may be wrapped in ManuallyDrop.
+        let ty = extract_manually_drop_inner_type(&f.ty).unwrap_or(&f.ty);
+        let annotated = type_converter.convert_type(ty.clone(), ns, &type_conversion_context);
         match annotated {
             Ok(mut r) => {
                 extra_apis.append(&mut r.extra_apis);
@@ -255,6 +259,17 @@ fn get_struct_field_types(
             Err(e) => convert_errors.push(e),
         };
     }
+    // The types of any bitfields are exposed by their accessors, so
+    // they're needed just as much as the types of ordinary fields.
+    for bitfield in &details.bitfields {
+        match type_converter.convert_type(bitfield.ty.0.clone(), ns, &type_conversion_context) {
+            Ok(mut r) => {
+                extra_apis.append(&mut r.extra_apis);
+                field_deps.extend(r.types_encountered);
+            }
+            Err(e) => convert_errors.push(e),
+        }
+    }
     convert_errors
 }
 
@@ -13,10 +13,10 @@ use crate::{
         codegen_cpp::type_to_cpp::CppNameMap,
         ConvertErrorFromCpp,
     },
-    known_types::{known_types, CxxGenericType},
+    known_types::{known_types, AssociativeContainerType, ChronoType, CxxGenericType},
     types::{make_ident, Namespace, QualifiedName},
 };
-use autocxx_parser::IncludeCppConfig;
+use autocxx_parser::{IncludeCppConfig, SmartPointer};
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 use itertools::Itertools;
@@ -91,6 +91,7 @@ pub(crate) enum PointerTreatment {
 /// from [TypeConverter] _might_ be used in the [cxx::bridge].
 pub(crate) enum TypeConversionContext {
     WithinReference,
+    WithinOuterReference, // the referent of a reference in a function signature
     WithinStructField { struct_type_params: HashSet<Ident> },
     WithinContainer,
     OuterType { pointer_treatment: PointerTreatment },
@@ -99,14 +100,20 @@ pub(crate) enum TypeConversionContext {
 impl TypeConversionContext {
     fn pointer_treatment(&self) -> PointerTreatment {
         match self {
-            Self::WithinReference | Self::WithinContainer | Self::WithinStructField { .. } => {
-                PointerTreatment::Pointer
-            }
+            Self::WithinReference
+            | Self::WithinOuterReference
+            | Self::WithinContainer
+            | Self::WithinStructField { .. } => PointerTreatment::Pointer,
             Self::OuterType { pointer_treatment } => *pointer_treatment,
         }
     }
     fn allow_instantiation_of_forward_declaration(&self) -> bool {
-        matches!(self, Self::WithinReference)
+        matches!(self, Self::WithinReference | Self::WithinOuterReference)
+    }
+    /// `std::pair` and `std::tuple` are only converted to tuple structs
+    /// in function signatures; elsewhere they remain opaque.
+    fn allows_tuple(&self) -> bool {
+        matches!(self, Self::OuterType { .. } | Self::WithinOuterReference)
     }
     fn allowed_generic_type(&self, ident: &Ident) -> bool {
         !matches!(self,
@@ -115,6 +122,37 @@ impl TypeConversionContext {
     }
 }
 
+/// How Rust code sees one of our synthetic types representing a custom
+/// smart pointer or a `std::unique_ptr` with a custom deleter, when it's
+/// passed or returned by value.
+#[derive(Clone)]
+pub(crate) struct OwningPointer {
+    /// The autocxx type which owns the pointee.
+    pub(crate) rust_type: Type,
+    pub(crate) pointee: Type,
+    /// Whether it can also be passed by const reference. That's possible
+    /// only if we can create an extra owning pointer for the call.
+    pub(crate) by_const_ref: bool,
+}
+
+impl OwningPointer {
+    fn ref_ptr(pointee: Type) -> Self {
+        Self {
+            rust_type: parse_quote! { autocxx::RefPtr<#pointee> },
+            pointee,
+            by_const_ref: true,
+        }
+    }
+
+    fn unique_ptr_with_deleter(pointee: Type, deleter: Type) -> Self {
+        Self {
+            rust_type: parse_quote! { autocxx::UniquePtrWithDeleter<#pointee, #deleter> },
+            pointee,
+            by_const_ref: false,
+        }
+    }
+}
+
 /// A type which can convert from a type encountered in `bindgen`
 /// output to the sort of type we should represeent to `cxx`.
 /// As a simple example, `std::string` should be replaced
@@ -127,6 +165,9 @@ pub(crate) struct TypeConverter<'a> {
     types_found: HashSet<QualifiedName>,
     typedefs: HashMap<QualifiedName, Type>,
     concrete_templates: HashMap<String, QualifiedName>,
+    container_elements: HashSet<QualifiedName>,
+    tuples: HashMap<QualifiedName, Vec<Type>>,
+    owning_pointers: HashMap<QualifiedName, OwningPointer>,
     forward_declarations: HashSet<QualifiedName>,
     ignored_types: HashSet<QualifiedName>,
     config: &'a IncludeCppConfig,
@@ -142,6 +183,9 @@ impl<'a> TypeConverter<'a> {
             types_found: find_types(apis),
             typedefs: Self::find_typedefs(apis),
             concrete_templates: Self::find_concrete_templates(apis),
+            container_elements: Self::find_container_elements(apis),
+            tuples: Self::find_tuples(apis),
+            owning_pointers: Self::find_owning_pointers(apis),
             forward_declarations: Self::find_incomplete_types(apis),
             ignored_types: Self::find_ignored_types(apis),
             config,
@@ -294,9 +338,61 @@ impl<'a> TypeConverter<'a> {
             }
         };
 
+        // cxx's UniquePtr supports only the default deleter, so a
+        // std::unique_ptr with any other becomes a synthetic type instead.
+        if known_types().is_unique_ptr(&tn) {
+            match Self::unique_ptr_custom_deleter(&typ) {
+                Some(GenericArgument::Type(Type::Path(_))) => {
+                    let mut annotated = self.convert_unique_ptr_with_deleter(typ, &tn, ns)?;
+                    deps.remove(&tn);
+                    deps.extend(annotated.types_encountered.drain(..));
+                    return Ok(Annotated::new(
+                        annotated.ty,
+                        deps,
+                        annotated.extra_apis,
+                        TypeKind::Regular,
+                    ));
+                }
+                Some(_) => {
+                    return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(tn));
+                }
+                None => {}
+            }
+        }
+
         // Now let's see if it's a known type.
         // (We may entirely reject some types at this point too.)
         let mut typ = match known_types().consider_substitution(&tn) {
+            // Any deleter is the default one, which cxx doesn't want to hear about.
+            Some(mut substitute_type) if known_types().is_unique_ptr(&tn) => {
+                if let Some(PathArguments::AngleBracketed(mut ab)) =
+                    typ.path.segments.into_iter().last().map(|ps| ps.arguments)
+                {
+                    ab.args = ab.args.into_iter().take(1).collect();
+                    let last_seg = substitute_type.path.segments.last_mut().unwrap();
+                    last_seg.arguments = PathArguments::AngleBracketed(ab);
+                }
+                substitute_type
+            }
+            // Whatever the representation and period of a std::chrono type,
+            // we just pass a count of nanoseconds, so the Rust equivalents
+            // don't care about them. A time point is only meaningful
+            // alongside its clock, though, so that becomes a type parameter.
+            Some(mut substitute_type) if known_types().chrono_type(&tn).is_some() => {
+                if known_types().chrono_type(&tn) == Some(ChronoType::TimePoint) {
+                    let mut clock = self.convert_clock(&typ, &tn, ns)?;
+                    deps.extend(clock.types_encountered.drain(..));
+                    let clock = clock.ty;
+                    substitute_type.path.segments.last_mut().unwrap().arguments =
+                        PathArguments::AngleBracketed(parse_quote! { <#clock> });
+                }
+                return Ok(Annotated::new(
+                    Type::Path(substitute_type),
+                    deps,
+                    ApiVec::new(),
+                    TypeKind::Regular,
+                ));
+            }
             Some(mut substitute_type) => {
                 if let Some(last_seg_args) =
                     typ.path.segments.into_iter().last().map(|ps| ps.arguments)
@@ -326,6 +422,12 @@ impl<'a> TypeConverter<'a> {
                         &TypeConversionContext::WithinContainer,
                     )?;
                     ab.args = innerty.ty;
+                    self.substitute_container_elements(
+                        &mut ab.args,
+                        &tn,
+                        &mut deps,
+                        &mut extra_apis,
+                    );
                     kind = self.confirm_inner_type_is_acceptable_generic_payload(
                         &ab.args,
                         &tn,
@@ -361,6 +463,34 @@ impl<'a> TypeConverter<'a> {
                         }
                     }
                 }
+                let is_tuple =
+                    Self::is_std_template(&tn, "pair") || Self::is_std_template(&tn, "tuple");
+                let synthesized = if is_tuple && ctx.allows_tuple() {
+                    Some(self.convert_tuple(typ.clone(), &tn, ns)?)
+                } else if Self::is_std_template(&tn, "variant") {
+                    Some(self.convert_variant(typ.clone(), &tn, ns)?)
+                } else if let Some(container_type) = known_types().associative_container_type(&tn) {
+                    Some(self.convert_associative_container(
+                        typ.clone(),
+                        &tn,
+                        container_type,
+                        ns,
+                    )?)
+                } else if let Some(details) = self.config.get_smart_pointer(&tn.to_cpp_name()) {
+                    Some(self.convert_smart_pointer(typ.clone(), &tn, details.clone(), ns)?)
+                } else {
+                    None
+                };
+                if let Some(mut annotated) = synthesized {
+                    deps.remove(&tn);
+                    deps.extend(annotated.types_encountered.drain(..));
+                    return Ok(Annotated::new(
+                        annotated.ty,
+                        deps,
+                        annotated.extra_apis,
+                        kind,
+                    ));
+                }
                 // Let's second see if this is a concrete version of a templated type
                 // which we already rejected. Some, but possibly not all, of the reasons
                 // for its rejection would also apply to any concrete types we
@@ -472,8 +602,11 @@ impl<'a> TypeConverter<'a> {
             }
             PointerTreatment::Reference => {
                 let mutability = ptr.mutability;
-                let elem =
-                    self.convert_boxed_type(ptr.elem, ns, &TypeConversionContext::WithinReference)?;
+                let elem = self.convert_boxed_type(
+                    ptr.elem,
+                    ns,
+                    &TypeConversionContext::WithinOuterReference,
+                )?;
                 // TODO - in the future, we should check if this is a rust::Str and throw
                 // a wobbler if not. rust::Str should only be seen _by value_ in C++
                 // headers; it manifests as &str in Rust but on the C++ side it must
@@ -523,6 +656,25 @@ impl<'a> TypeConverter<'a> {
         &mut self,
         rs_definition: &Type,
     ) -> Result<(QualifiedName, Option<UnanalyzedApi>), ConvertErrorFromCpp> {
+        self.get_synthetic_typename(rs_definition, |name, cpp_definition| {
+            UnanalyzedApi::ConcreteType {
+                name,
+                cpp_definition,
+                rs_definition: Some(Box::new(rs_definition.clone().into())),
+            }
+        })
+    }
+
+    /// Find or invent a name for a synthetic type standing in for the
+    /// given templated type, along with an API to create it if it's new.
+    fn get_synthetic_typename<F>(
+        &mut self,
+        rs_definition: &Type,
+        api_creator: F,
+    ) -> Result<(QualifiedName, Option<UnanalyzedApi>), ConvertErrorFromCpp>
+    where
+        F: FnOnce(ApiName, String) -> UnanalyzedApi,
+    {
         let count = self.concrete_templates.len();
         // We just use this as a hash key, essentially.
         // TODO: Once we've completed the TypeConverter refactoring (see #220),
@@ -553,11 +705,10 @@ impl<'a> TypeConverter<'a> {
                     None => synthetic_ident,
                     Some(_) => format!("AutocxxConcrete{count}"),
                 };
-                let api = UnanalyzedApi::ConcreteType {
-                    name: ApiName::new_in_root_namespace(make_ident(synthetic_ident)),
-                    cpp_definition: cpp_definition.clone(),
-                    rs_definition: Some(Box::new(rs_definition.clone().into())),
-                };
+                let api = api_creator(
+                    ApiName::new_in_root_namespace(make_ident(synthetic_ident)),
+                    cpp_definition.clone(),
+                );
                 self.concrete_templates
                     .insert(cpp_definition, api.name().clone());
                 Ok((api.name().clone(), Some(api)))
@@ -565,6 +716,320 @@ impl<'a> TypeConverter<'a> {
         }
     }
 
+    fn is_std_template(tn: &QualifiedName, template_name: &str) -> bool {
+        tn.get_namespace().iter().next().map(String::as_str) == Some("std")
+            && tn.get_final_item() == template_name
+    }
+
+    /// Find the Rust type representing the clock of a
+    /// `std::chrono::time_point`. The standard clocks have equivalents in
+    /// `autocxx::chrono`; any other clock is represented by its own type.
+    fn convert_clock(
+        &mut self,
+        typ: &TypePath,
+        tn: &QualifiedName,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let clock = match typ.path.segments.last().map(|seg| &seg.arguments) {
+            Some(PathArguments::AngleBracketed(ab)) => ab.args.first(),
+            _ => None,
+        };
+        let Some(GenericArgument::Type(Type::Path(clock))) = clock else {
+            return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                tn.clone(),
+            ));
+        };
+        let clock_tn = QualifiedName::from_type_path(clock);
+        let std_clock = if clock_tn
+            .get_namespace()
+            .iter()
+            .take(2)
+            .eq(["std", "chrono"])
+        {
+            match clock_tn.get_final_item() {
+                "system_clock" => Some("SystemClock"),
+                "steady_clock" => Some("SteadyClock"),
+                "high_resolution_clock" => Some("HighResolutionClock"),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match std_clock {
+            Some(std_clock) => {
+                let std_clock = make_ident(std_clock);
+                Ok(Annotated::new(
+                    parse_quote! { autocxx::chrono::#std_clock },
+                    HashSet::new(),
+                    ApiVec::new(),
+                    TypeKind::Regular,
+                ))
+            }
+            None => {
+                self.convert_type_path(clock.clone(), ns, &TypeConversionContext::WithinContainer)
+            }
+        }
+    }
+
+    /// Convert the type arguments of a `std::pair`, `std::tuple`,
+    /// `std::variant` or associative container. These must be simple types,
+    /// since we'll generate code which passes them individually.
+    fn convert_template_elements(
+        &mut self,
+        typ: &TypePath,
+        tn: &QualifiedName,
+        ns: &Namespace,
+    ) -> Result<Annotated<Vec<Type>>, ConvertErrorFromCpp> {
+        let args = match typ.path.segments.last().map(|seg| &seg.arguments) {
+            Some(PathArguments::AngleBracketed(ab)) if !ab.args.is_empty() => ab.args.clone(),
+            _ => {
+                return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                    tn.clone(),
+                ))
+            }
+        };
+        let converted =
+            self.convert_punctuated(args, ns, &TypeConversionContext::WithinContainer)?;
+        let elements = converted
+            .ty
+            .into_iter()
+            .map(|arg| match arg {
+                GenericArgument::Type(Type::Path(tp))
+                    if tp.path.segments.iter().all(|seg| seg.arguments.is_empty()) =>
+                {
+                    Ok(Type::Path(tp))
+                }
+                _ => Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                    tn.clone(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Annotated::new(
+            elements,
+            converted.types_encountered,
+            converted.extra_apis,
+            TypeKind::Regular,
+        ))
+    }
+
+    /// Convert a `std::pair` or `std::tuple` into a synthetic struct with
+    /// a field for each element.
+    fn convert_tuple(
+        &mut self,
+        typ: TypePath,
+        tn: &QualifiedName,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let elements = self.convert_template_elements(&typ, tn, ns)?;
+        let element_deps: Vec<_> = elements.types_encountered.iter().cloned().collect();
+        let (new_tn, api) =
+            self.get_synthetic_typename(&Type::Path(typ), |name, cpp_definition| {
+                UnanalyzedApi::Tuple {
+                    name,
+                    elements: elements.ty.iter().cloned().map(Into::into).collect(),
+                    cpp_definition,
+                    deps: element_deps,
+                }
+            })?;
+        self.tuples.insert(new_tn.clone(), elements.ty.clone());
+        Ok(Self::add_synthetic_type(elements, new_tn, api))
+    }
+
+    /// Convert a `std::variant` into an opaque concrete type, which
+    /// can be converted to and from a Rust enum.
+    fn convert_variant(
+        &mut self,
+        typ: TypePath,
+        tn: &QualifiedName,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let alternatives = self.convert_template_elements(&typ, tn, ns)?;
+        let alternative_deps: Vec<_> = alternatives.types_encountered.iter().cloned().collect();
+        let (new_tn, api) =
+            self.get_synthetic_typename(&Type::Path(typ), |name, cpp_definition| {
+                UnanalyzedApi::Variant {
+                    name,
+                    alternatives: alternatives.ty.iter().cloned().map(Into::into).collect(),
+                    cpp_definition,
+                    deps: alternative_deps,
+                }
+            })?;
+        Ok(Self::add_synthetic_type(alternatives, new_tn, api))
+    }
+
+    /// Convert a `std::map`, `std::unordered_map` or `std::set` into an
+    /// opaque concrete type, for which we'll generate methods.
+    fn convert_associative_container(
+        &mut self,
+        typ: TypePath,
+        tn: &QualifiedName,
+        container_type: AssociativeContainerType,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let elements = self.convert_template_elements(&typ, tn, ns)?;
+        let expected_elements = if container_type.has_values() { 2 } else { 1 };
+        if elements.ty.len() != expected_elements {
+            return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                tn.clone(),
+            ));
+        }
+        let element_deps: Vec<_> = elements.types_encountered.iter().cloned().collect();
+        let (new_tn, api) =
+            self.get_synthetic_typename(&Type::Path(typ), |name, cpp_definition| {
+                UnanalyzedApi::AssociativeContainer {
+                    name,
+                    container_type,
+                    elements: elements.ty.iter().cloned().map(Into::into).collect(),
+                    cpp_definition,
+                    deps: element_deps,
+                }
+            })?;
+        Ok(Self::add_synthetic_type(elements, new_tn, api))
+    }
+
+    /// Convert an instantiation of a custom smart pointer into an opaque
+    /// concrete type, noting its pointee so that functions can instead
+    /// pass an `autocxx::RefPtr`.
+    fn convert_smart_pointer(
+        &mut self,
+        typ: TypePath,
+        tn: &QualifiedName,
+        details: SmartPointer,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let pointee = self.convert_template_elements(&typ, tn, ns)?;
+        if pointee.ty.len() != 1 {
+            return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                tn.clone(),
+            ));
+        }
+        let pointee_ty = pointee.ty[0].clone();
+        let pointee_deps: Vec<_> = pointee.types_encountered.iter().cloned().collect();
+        let (new_tn, api) =
+            self.get_synthetic_typename(&Type::Path(typ), |name, cpp_definition| {
+                UnanalyzedApi::SmartPointer {
+                    name,
+                    details,
+                    pointee: Box::new(pointee_ty.clone().into()),
+                    cpp_definition,
+                    deps: pointee_deps,
+                }
+            })?;
+        self.owning_pointers
+            .insert(new_tn.clone(), OwningPointer::ref_ptr(pointee_ty));
+        Ok(Self::add_synthetic_type(pointee, new_tn, api))
+    }
+
+    /// The deleter of a `std::unique_ptr`, if one is given, and if it isn't
+    /// the default.
+    fn unique_ptr_custom_deleter(typ: &TypePath) -> Option<&GenericArgument> {
+        match typ.path.segments.last().map(|seg| &seg.arguments) {
+            Some(PathArguments::AngleBracketed(ab)) => ab.args.iter().nth(1).filter(|arg| {
+                !matches!(arg, GenericArgument::Type(Type::Path(tp))
+                    if Self::is_std_template(&QualifiedName::from_type_path(tp), "default_delete"))
+            }),
+            _ => None,
+        }
+    }
+
+    /// Convert a `std::unique_ptr` with a custom deleter into an opaque
+    /// concrete type, noting its pointee and deleter so that functions can
+    /// instead pass an `autocxx::UniquePtrWithDeleter`.
+    fn convert_unique_ptr_with_deleter(
+        &mut self,
+        typ: TypePath,
+        tn: &QualifiedName,
+        ns: &Namespace,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        let elements = self.convert_template_elements(&typ, tn, ns)?;
+        let (pointee, deleter) = match elements.ty.as_slice() {
+            [pointee, deleter] => (pointee.clone(), deleter.clone()),
+            _ => {
+                return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
+                    tn.clone(),
+                ))
+            }
+        };
+        let deps: Vec<_> = elements.types_encountered.iter().cloned().collect();
+        let (new_tn, api) =
+            self.get_synthetic_typename(&Type::Path(typ), |name, cpp_definition| {
+                UnanalyzedApi::UniquePtrWithDeleter {
+                    name,
+                    pointee: Box::new(pointee.clone().into()),
+                    deleter: Box::new(deleter.clone().into()),
+                    cpp_definition,
+                    deps,
+                }
+            })?;
+        self.owning_pointers.insert(
+            new_tn.clone(),
+            OwningPointer::unique_ptr_with_deleter(pointee, deleter),
+        );
+        Ok(Self::add_synthetic_type(elements, new_tn, api))
+    }
+
+    fn add_synthetic_type<T>(
+        mut annotated: Annotated<T>,
+        new_tn: QualifiedName,
+        api: Option<UnanalyzedApi>,
+    ) -> Annotated<Type> {
+        annotated.extra_apis.extend(api.into_iter());
+        annotated.types_encountered.insert(new_tn.clone());
+        Annotated::new(
+            Type::Path(new_tn.to_type_path()),
+            annotated.types_encountered,
+            annotated.extra_apis,
+            TypeKind::Regular,
+        )
+    }
+
+    /// If this is one of our synthetic structs representing a `std::pair`
+    /// or `std::tuple`, the Rust types of its elements.
+    pub(crate) fn tuple_elements(&self, tn: &QualifiedName) -> Option<&[Type]> {
+        self.tuples.get(tn).map(Vec::as_slice)
+    }
+
+    /// If this is one of our synthetic types representing an instantiation
+    /// of a custom smart pointer or a `std::unique_ptr` with a custom
+    /// deleter, how Rust sees it.
+    pub(crate) fn owning_pointer(&self, tn: &QualifiedName) -> Option<&OwningPointer> {
+        self.owning_pointers.get(tn)
+    }
+
+    /// cxx can't hold some C types, such as `c_int`, within a
+    /// `std::vector` or smart pointer. Replace any such type with a
+    /// wrapper type which it can hold, creating that type if need be.
+    fn substitute_container_elements(
+        &mut self,
+        path_args: &mut Punctuated<GenericArgument, Comma>,
+        container: &QualifiedName,
+        deps: &mut HashSet<QualifiedName>,
+        extra_apis: &mut ApiVec<NullPhase>,
+    ) {
+        for inner in path_args.iter_mut() {
+            if let GenericArgument::Type(Type::Path(typ)) = inner {
+                let element = QualifiedName::from_type_path(typ);
+                if !known_types().needs_container_element(container, &element) {
+                    continue;
+                }
+                let ident = format!("{}_AutocxxElement", element.get_final_item());
+                let name = QualifiedName::new(&Namespace::new(), make_ident(&ident));
+                if self.container_elements.insert(name.clone()) {
+                    extra_apis.push(UnanalyzedApi::ContainerElement {
+                        name: ApiName::new_with_cpp_name(
+                            &Namespace::new(),
+                            make_ident(&ident),
+                            Some(self.config.uniquify_name_per_mod(&ident)),
+                        ),
+                        element,
+                    });
+                }
+                *typ = name.to_type_path();
+                deps.insert(name);
+            }
+        }
+    }
+
     fn confirm_inner_type_is_acceptable_generic_payload(
         &self,
         path_args: &Punctuated<GenericArgument, Comma>,
@@ -601,7 +1066,12 @@ impl<'a> TypeConverter<'a> {
                             }
                         }
                         CxxGenericType::CppPtr => {
-                            if !known_types().permissible_within_unique_ptr(&inner_qn) {
+                            let permissible = if known_types().is_shared_ptr(desc) {
+                                known_types().permissible_within_shared_ptr(&inner_qn)
+                            } else {
+                                known_types().permissible_within_unique_ptr(&inner_qn)
+                            };
+                            if !permissible {
                                 return Err(ConvertErrorFromCpp::InvalidTypeForCppPtr(inner_qn));
                             }
                         }
@@ -652,7 +1122,12 @@ impl<'a> TypeConverter<'a> {
     ) -> HashMap<String, QualifiedName> {
         apis.iter()
             .filter_map(|api| match &api {
-                Api::ConcreteType { cpp_definition, .. } => {
+                Api::ConcreteType { cpp_definition, .. }
+                | Api::Tuple { cpp_definition, .. }
+                | Api::Variant { cpp_definition, .. }
+                | Api::AssociativeContainer { cpp_definition, .. }
+                | Api::SmartPointer { cpp_definition, .. }
+                | Api::UniquePtrWithDeleter { cpp_definition, .. } => {
                     Some((cpp_definition.clone(), api.name().clone()))
                 }
                 _ => None,
@@ -660,6 +1135,50 @@ impl<'a> TypeConverter<'a> {
             .collect()
     }
 
+    fn find_container_elements<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<QualifiedName> {
+        apis.iter()
+            .filter_map(|api| match &api {
+                Api::ContainerElement { name, .. } => Some(name.name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn find_tuples<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashMap<QualifiedName, Vec<Type>> {
+        apis.iter()
+            .filter_map(|api| match &api {
+                Api::Tuple { elements, .. } => Some((
+                    api.name().clone(),
+                    elements.iter().cloned().map(Into::into).collect(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn find_owning_pointers<A: AnalysisPhase>(
+        apis: &ApiVec<A>,
+    ) -> HashMap<QualifiedName, OwningPointer> {
+        apis.iter()
+            .filter_map(|api| match &api {
+                Api::SmartPointer { pointee, .. } => Some((
+                    api.name().clone(),
+                    OwningPointer::ref_ptr(pointee.as_ref().clone().into()),
+                )),
+                Api::UniquePtrWithDeleter {
+                    pointee, deleter, ..
+                } => Some((
+                    api.name().clone(),
+                    OwningPointer::unique_ptr_with_deleter(
+                        pointee.as_ref().clone().into(),
+                        deleter.as_ref().clone().into(),
+                    ),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn find_incomplete_types<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<QualifiedName> {
         apis.iter()
             .filter_map(|api| match api {
@@ -701,6 +1220,68 @@ pub(crate) fn add_analysis<A: AnalysisPhase>(api: UnanalyzedApi) -> Api<A> {
             rs_definition,
             cpp_definition,
         },
+        Api::Tuple {
+            name,
+            elements,
+            cpp_definition,
+            deps,
+        } => Api::Tuple {
+            name,
+            elements,
+            cpp_definition,
+            deps,
+        },
+        Api::Variant {
+            name,
+            alternatives,
+            cpp_definition,
+            deps,
+        } => Api::Variant {
+            name,
+            alternatives,
+            cpp_definition,
+            deps,
+        },
+        Api::AssociativeContainer {
+            name,
+            container_type,
+            elements,
+            cpp_definition,
+            deps,
+        } => Api::AssociativeContainer {
+            name,
+            container_type,
+            elements,
+            cpp_definition,
+            deps,
+        },
+        Api::SmartPointer {
+            name,
+            details,
+            pointee,
+            cpp_definition,
+            deps,
+        } => Api::SmartPointer {
+            name,
+            details,
+            pointee,
+            cpp_definition,
+            deps,
+        },
+        Api::UniquePtrWithDeleter {
+            name,
+            pointee,
+            deleter,
+            cpp_definition,
+            deps,
+        } => Api::UniquePtrWithDeleter {
+            name,
+            pointee,
+            deleter,
+            cpp_definition,
+            deps,
+        },
+        Api::ContainerElement { name, element } => Api::ContainerElement { name, element },
         Api::IgnoredItem { name, err, ctx } => Api::IgnoredItem { name, err, ctx },
         _ => panic!("Function analysis created an unexpected type of extra API"),
     }
@@ -730,12 +1311,19 @@ pub(crate) fn find_types<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<Qualifie
             Api::ForwardDeclaration { .. }
             | Api::OpaqueTypedef { .. }
             | Api::ConcreteType { .. }
+            | Api::Tuple { .. }
+            | Api::Variant { .. }
+            | Api::AssociativeContainer { .. }
+            | Api::SmartPointer { .. }
+            | Api::UniquePtrWithDeleter { .. }
+            | Api::ContainerElement { .. }
             | Api::Typedef { .. }
             | Api::Enum { .. }
             | Api::Struct { .. }
             | Api::Subclass { .. }
             | Api::ExternCppType { .. }
-            | Api::RustType { .. } => Some(api.name()),
+            | Api::RustType { .. }
+            | Api::RustTrait { .. } => Some(api.name()),
             Api::StringConstructor { .. }
             | Api::Function { .. }
             | Api::Const { .. }
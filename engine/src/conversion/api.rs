@@ -15,13 +15,14 @@ use syn::{
     token::{Comma, Unsafe},
 };
 
+use crate::known_types::AssociativeContainerType;
 use crate::minisyn::{
     Attribute, FnArg, Ident, ItemConst, ItemEnum, ItemStruct, ItemType, ItemUse, LitBool, LitInt,
     Pat, ReturnType, Type, Visibility,
 };
 use crate::types::{make_ident, Namespace, QualifiedName};
-use autocxx_parser::{ExternCppType, RustFun, RustPath};
-use itertools::Itertools;
+use autocxx_parser::{ExternCppType, RustFun, RustPath, RustTrait, SmartPointer};
+use itertools::{Either, Itertools};
 use quote::ToTokens;
 
 use super::{
@@ -47,6 +48,25 @@ pub(crate) enum TypeKind {
             // in which case we'll err on the side of caution.
 }
 
+/// A C++ conversion operator which we can represent in Rust.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum ConversionOperator {
+    /// `operator bool()`
+    Bool,
+    /// `operator std::string()`
+    String,
+}
+
+impl ConversionOperator {
+    /// The C++ type to which this converts.
+    pub(crate) fn cpp_type(&self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::String => "std::string",
+        }
+    }
+}
+
 /// C++ visibility.
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub(crate) enum CppVisibility {
@@ -61,6 +81,65 @@ pub(crate) struct StructDetails {
     pub(crate) item: ItemStruct,
     pub(crate) layout: Option<Layout>,
     pub(crate) has_rvalue_reference_fields: bool,
+    /// Any bitfields within this struct, whose storage has been
+    /// represented in `item` as byte arrays.
+    pub(crate) bitfields: Vec<Bitfield>,
+    /// Whether this is actually a C++ union. If so, `item` is a struct
+    /// with the same fields as the union.
+    pub(crate) is_union: bool,
+    pub(crate) anonymous_unions: Vec<AnonymousUnion>,
+    /// Public `const` conversion operators which we can represent as
+    /// `From` implementations. bindgen doesn't tell us about these, so we
+    /// find them from the declaration.
+    pub(crate) conversion_operators: Vec<ConversionOperator>,
+    /// The C++ names of fields whose declarations show they aren't `const`.
+    /// bindgen doesn't tell us this, so if we couldn't find the declaration
+    /// this is empty.
+    pub(crate) non_const_fields: HashSet<String>,
+}
+
+impl StructDetails {
+    /// All the fields which contribute to the layout of this struct,
+    /// with the members of any anonymous unions in place of the
+    /// fields holding those unions.
+    pub(crate) fn all_fields(&self) -> impl Iterator<Item = &syn::Field> + '_ {
+        self.item.fields.iter().flat_map(move |f| {
+            match self
+                .anonymous_unions
+                .iter()
+                .find(|au| f.ident.as_ref() == Some(&au.field.0))
+            {
+                Some(au) => Either::Left(au.item.fields.iter()),
+                None => Either::Right(std::iter::once(f)),
+            }
+        })
+    }
+}
+
+/// A union without a name, nested within a struct. C++ allows its
+/// members to be accessed as if they were members of the struct.
+#[derive(Debug)]
+pub(crate) struct AnonymousUnion {
+    /// The field of the outer struct which holds the union.
+    pub(crate) field: Ident,
+    /// The union, represented as a struct as in [`StructDetails::item`].
+    pub(crate) item: ItemStruct,
+}
+
+/// A single C++ bitfield, as described by the accessor methods which
+/// bindgen generates for it.
+#[derive(Debug, Clone)]
+pub(crate) struct Bitfield {
+    pub(crate) getter: Ident,
+    pub(crate) setter: Ident,
+    /// The type exposed by the accessors.
+    pub(crate) ty: Type,
+    /// The integer type which bindgen transmutes to and from `ty`.
+    pub(crate) int_ty: Type,
+    /// The byte array field holding this bitfield.
+    pub(crate) storage: Ident,
+    pub(crate) bit_offset: usize,
+    pub(crate) bit_width: u8,
 }
 
 /// Layout of a type, equivalent to the same type in ir/layout.rs in bindgen
@@ -113,6 +192,8 @@ pub(crate) enum TraitSynthesis {
     },
     AllocUninitialized(QualifiedName),
     FreeUninitialized(QualifiedName),
+    UniqueToShared(QualifiedName),
+    ConversionOperator(ConversionOperator),
 }
 
 /// Details of a subclass constructor.
@@ -282,6 +363,11 @@ pub(crate) struct FuncToConvert {
     pub(crate) special_member: Option<SpecialMemberKind>,
     pub(crate) unused_template_param: bool,
     pub(crate) references: References,
+    /// bindgen can't tell us this, so we find it from the declaration.
+    pub(crate) returns_weak_ptr_to_const: bool,
+    /// Whether this is an `explicit` constructor, which bindgen also
+    /// doesn't tell us.
+    pub(crate) is_explicit: bool,
     pub(crate) original_name: Option<String>,
     /// Used for static functions only. For all other functons,
     /// this is figured out from the receiver type in the inputs.
@@ -491,6 +577,61 @@ pub(crate) enum Api<T: AnalysisPhase> {
         rs_definition: Option<Box<Type>>,
         cpp_definition: String,
     },
+    /// A synthetic POD struct standing in for a `std::pair` or
+    /// `std::tuple`, so that it can be passed across the bridge and
+    /// converted to a native Rust tuple.
+    Tuple {
+        name: ApiName,
+        elements: Vec<Type>,
+        cpp_definition: String,
+        deps: Vec<QualifiedName>,
+    },
+    /// A synthetic opaque type standing in for a `std::variant`, which
+    /// can be converted to and from a Rust enum.
+    Variant {
+        name: ApiName,
+        alternatives: Vec<Type>,
+        cpp_definition: String,
+        deps: Vec<QualifiedName>,
+    },
+    /// A synthetic opaque type standing in for a `std::map`,
+    /// `std::unordered_map` or `std::set`. The elements are the key type
+    /// followed, for maps, by the value type.
+    AssociativeContainer {
+        name: ApiName,
+        container_type: AssociativeContainerType,
+        elements: Vec<Type>,
+        cpp_definition: String,
+        deps: Vec<QualifiedName>,
+    },
+    /// A synthetic opaque type standing in for an instantiation of a custom
+    /// smart pointer declared with `smart_pointer!`. Functions taking or
+    /// returning it by value use an `autocxx::RefPtr` of the pointee instead.
+    SmartPointer {
+        name: ApiName,
+        details: SmartPointer,
+        pointee: Box<Type>,
+        cpp_definition: String,
+        deps: Vec<QualifiedName>,
+    },
+    /// A synthetic opaque type standing in for a `std::unique_ptr` with a
+    /// custom deleter. Functions taking or returning it by value use an
+    /// `autocxx::UniquePtrWithDeleter` instead.
+    UniquePtrWithDeleter {
+        name: ApiName,
+        pointee: Box<Type>,
+        deleter: Box<Type>,
+        cpp_definition: String,
+        deps: Vec<QualifiedName>,
+    },
+    /// A synthetic wrapper around a C type, such as `int` or `uint32_t`,
+    /// which cxx can't itself hold within a `std::vector` or smart
+    /// pointer. cxx can hold this instead, because it's generated
+    /// afresh for each `include_cpp!` block.
+    ContainerElement {
+        name: ApiName,
+        element: QualifiedName,
+    },
     /// A simple note that we want to make a constructor for
     /// a `std::string` on the heap.
     StringConstructor { name: ApiName },
@@ -545,6 +686,13 @@ pub(crate) enum Api<T: AnalysisPhase> {
         details: RustFun,
         deps: Vec<QualifiedName>,
     },
+    /// A Rust trait exposed to C++ as an abstract class. The name of
+    /// this API is that of the Rust type which holds a `Box<dyn Trait>`.
+    RustTrait {
+        name: ApiName,
+        details: RustTrait,
+        deps: Vec<QualifiedName>,
+    },
     /// Some function for the extern "Rust" block.
     RustSubclassFn {
         name: ApiName,
@@ -597,6 +745,12 @@ impl<T: AnalysisPhase> Api<T> {
             Api::ForwardDeclaration { name, .. } => name,
             Api::OpaqueTypedef { name, .. } => name,
             Api::ConcreteType { name, .. } => name,
+            Api::Tuple { name, .. } => name,
+            Api::Variant { name, .. } => name,
+            Api::AssociativeContainer { name, .. } => name,
+            Api::SmartPointer { name, .. } => name,
+            Api::UniquePtrWithDeleter { name, .. } => name,
+            Api::ContainerElement { name, .. } => name,
             Api::StringConstructor { name } => name,
             Api::Function { name, .. } => name,
             Api::Const { name, .. } => name,
@@ -607,6 +761,7 @@ impl<T: AnalysisPhase> Api<T> {
             Api::IgnoredItem { name, .. } => name,
             Api::RustType { name, .. } => name,
             Api::RustFn { name, .. } => name,
+            Api::RustTrait { name, .. } => name,
             Api::RustSubclassFn { name, .. } => name,
             Api::Subclass { name, .. } => &name.0,
             Api::SubclassTraitItem { name, .. } => name,
@@ -0,0 +1,45 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indoc::indoc;
+
+/// Conversions between `std::chrono` types and counts of nanoseconds,
+/// which is how we pass them to and from Rust.
+/// We don't know the exact `duration` or `time_point` type which a
+/// C++ function takes, because bindgen can't tell us the template
+/// arguments, so parameters are converted using a templated conversion
+/// operator which deduces them.
+pub(super) static CHRONO_PRELUDE: &str = indoc! {"
+    #ifndef AUTOCXX_CHRONO_PRELUDE
+    #define AUTOCXX_CHRONO_PRELUDE
+    struct autocxx_chrono_duration {
+      int64_t nanos;
+      template <typename Rep, typename Period>
+      operator std::chrono::duration<Rep, Period>() const {
+        return std::chrono::duration_cast<std::chrono::duration<Rep, Period>>(
+            std::chrono::nanoseconds(nanos));
+      }
+    };
+    struct autocxx_chrono_time_point {
+      int64_t nanos;
+      template <typename Clock, typename Duration>
+      operator std::chrono::time_point<Clock, Duration>() const {
+        return std::chrono::time_point<Clock, Duration>(
+            std::chrono::duration_cast<Duration>(std::chrono::nanoseconds(nanos)));
+      }
+    };
+    template <typename Rep, typename Period>
+    int64_t autocxx_chrono_to_nanos(std::chrono::duration<Rep, Period> d) {
+      return std::chrono::duration_cast<std::chrono::nanoseconds>(d).count();
+    }
+    template <typename Clock, typename Duration>
+    int64_t autocxx_chrono_to_nanos(std::chrono::time_point<Clock, Duration> t) {
+      return autocxx_chrono_to_nanos(t.time_since_epoch());
+    }
+    #endif // AUTOCXX_CHRONO_PRELUDE
+"};
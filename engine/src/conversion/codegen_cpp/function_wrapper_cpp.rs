@@ -8,10 +8,13 @@
 
 use syn::{Type, TypePtr};
 
-use crate::conversion::{
-    analysis::fun::function_wrapper::{CppConversionType, TypeConversionPolicy},
-    api::Pointerness,
-    ConvertErrorFromCpp,
+use crate::{
+    conversion::{
+        analysis::fun::function_wrapper::{CppConversionType, TypeConversionPolicy},
+        api::Pointerness,
+        ConvertErrorFromCpp,
+    },
+    known_types::ChronoType,
 };
 
 use super::type_to_cpp::CppNameMap;
@@ -116,6 +119,33 @@ impl TypeConversionPolicy {
             }
             CppConversionType::IgnoredPlacementPtrParameter => None,
             CppConversionType::FromReferenceToPointer { .. } => Some(format!("&{var_name}")),
+            CppConversionType::FromNanosToChrono(ChronoType::Duration) => {
+                Some(format!("autocxx_chrono_duration{{{var_name}}}"))
+            }
+            CppConversionType::FromNanosToChrono(ChronoType::TimePoint) => {
+                Some(format!("autocxx_chrono_time_point{{{var_name}}}"))
+            }
+            CppConversionType::FromChronoToNanos => {
+                Some(format!("autocxx_chrono_to_nanos({var_name})"))
+            }
+            CppConversionType::FromTupleStructToTuple => Some(format!("{var_name}.autocxx_into()")),
+            CppConversionType::FromTupleToTupleStruct => Some(format!(
+                "{}::autocxx_from({var_name})",
+                self.unwrapped_type_as_string(cpp_name_map)?
+            )),
+            CppConversionType::FromSharedPtrToRust => {
+                Some(format!("autocxx_shared_ptr_to_rust({var_name})"))
+            }
+            CppConversionType::FromRustToSharedPtr => {
+                Some(format!("autocxx_shared_ptr_from_rust({var_name})"))
+            }
+            CppConversionType::FromRustToMutableSharedPtr => {
+                Some(format!("autocxx_mutable_shared_ptr_from_rust({var_name})"))
+            }
+            CppConversionType::FromRawToSmartPointer(ref shim)
+            | CppConversionType::FromSmartPointerToRaw(ref shim) => {
+                Some(format!("{shim}({var_name})"))
+            }
         })
     }
 }
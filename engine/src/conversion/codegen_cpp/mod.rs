@@ -6,32 +6,40 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod chrono_prelude;
 mod function_wrapper_cpp;
 mod new_and_delete_prelude;
+mod shared_ptr_prelude;
 pub(crate) mod type_to_cpp;
 
 use crate::{
     conversion::analysis::fun::{function_wrapper::CppFunctionKind, FnAnalysis},
+    known_types::AssociativeContainerType,
     types::{make_ident, QualifiedName},
     CppCodegenOptions, CppFilePair,
 };
-use autocxx_parser::IncludeCppConfig;
+use autocxx_parser::{IncludeCppConfig, RustTrait, SmartPointer};
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
+use indoc::formatdoc;
 use itertools::Itertools;
 use std::borrow::Cow;
+use syn::{parse_quote, FnArg, ReturnType, Type, TypeReference};
 use type_to_cpp::CppNameMap;
 
 use super::{
     analysis::{
+        element_kinds::{find_element_kinds, shim_name, ElementKind},
         fun::{
             function_wrapper::{CppFunction, CppFunctionBody},
             FnPhase, PodAndDepAnalysis,
         },
         pod::PodAnalysis,
     },
-    api::{Api, Provenance, SubclassName, TypeKind},
+    api::{Api, ApiName, Provenance, SubclassName, TypeKind},
     apivec::ApiVec,
+    codegen_rs::unqualify::{unqualify_params, unqualify_ret_type},
+    type_helpers::{extract_option_box_inner_type, extract_result_ok_type},
     ConvertErrorFromCpp,
 };
 
@@ -41,6 +49,8 @@ enum Header {
     CxxH,
     CxxgenH,
     NewDeletePrelude,
+    ChronoPrelude,
+    SharedPtrPrelude,
 }
 
 impl Header {
@@ -64,6 +74,8 @@ impl Header {
                 format!("#include \"{prefix}{cxxgen_header_name}\"")
             }
             Header::NewDeletePrelude => new_and_delete_prelude::NEW_AND_DELETE_PRELUDE.to_string(),
+            Header::ChronoPrelude => chrono_prelude::CHRONO_PRELUDE.to_string(),
+            Header::SharedPtrPrelude => shared_ptr_prelude::SHARED_PTR_PRELUDE.to_string(),
         }
     }
 
@@ -127,7 +139,11 @@ impl<'a> CppCodeGenerator<'a> {
         };
         // The 'filter' on the following line is designed to ensure we don't accidentally
         // end up out of sync with needs_cpp_codegen
-        gen.add_needs(apis.iter().filter(|api| api.needs_cpp_codegen()))?;
+        let element_kinds = find_element_kinds(apis);
+        gen.add_needs(
+            apis.iter().filter(|api| api.needs_cpp_codegen()),
+            &element_kinds,
+        )?;
         Ok(gen.generate())
     }
 
@@ -135,6 +151,7 @@ impl<'a> CppCodeGenerator<'a> {
     fn add_needs<'b>(
         &mut self,
         apis: impl Iterator<Item = &'a Api<FnPhase>>,
+        element_kinds: &HashMap<QualifiedName, Vec<ElementKind>>,
     ) -> Result<(), ConvertErrorFromCpp> {
         let mut constructors_by_subclass: HashMap<SubclassName, Vec<&CppFunction>> = HashMap::new();
         let mut methods_by_subclass: HashMap<SubclassName, Vec<SubclassFunction>> = HashMap::new();
@@ -146,6 +163,7 @@ impl<'a> CppCodeGenerator<'a> {
                     analysis:
                         FnAnalysis {
                             cpp_wrapper: Some(cpp_wrapper),
+                            make_shared_wrapper,
                             ignore_reason: Ok(_),
                             externally_callable: true,
                             ..
@@ -159,7 +177,10 @@ impl<'a> CppCodeGenerator<'a> {
                             .or_default()
                             .push(&details.cpp_impl);
                     }
-                    self.generate_cpp_function(cpp_wrapper)?
+                    self.generate_cpp_function(cpp_wrapper)?;
+                    if let Some(make_shared_wrapper) = make_shared_wrapper {
+                        self.generate_cpp_function(make_shared_wrapper)?
+                    }
                 }
                 Api::ConcreteType {
                     rs_definition,
@@ -175,8 +196,65 @@ impl<'a> CppCodeGenerator<'a> {
 
                     self.generate_typedef(api.name(), &effective_cpp_definition)
                 }
+                Api::Tuple {
+                    name,
+                    elements,
+                    cpp_definition,
+                    ..
+                } => self.generate_tuple_struct(&name.name, elements, cpp_definition)?,
+                Api::Variant {
+                    name,
+                    alternatives,
+                    cpp_definition,
+                    ..
+                } => {
+                    self.generate_typedef(&name.name, cpp_definition);
+                    if let Some(kinds) = element_kinds.get(&name.name) {
+                        self.generate_variant_shims(&name.name, alternatives, kinds)?;
+                    }
+                }
+                Api::AssociativeContainer {
+                    name,
+                    container_type,
+                    elements,
+                    cpp_definition,
+                    ..
+                } => {
+                    self.generate_typedef(&name.name, cpp_definition);
+                    if let Some(kinds) = element_kinds.get(&name.name) {
+                        self.generate_associative_container_shims(
+                            &name.name,
+                            *container_type,
+                            elements,
+                            kinds,
+                        )?;
+                    }
+                }
+                Api::SmartPointer {
+                    name,
+                    details,
+                    pointee,
+                    cpp_definition,
+                    ..
+                } => {
+                    self.generate_typedef(&name.name, cpp_definition);
+                    self.generate_smart_pointer_shims(&name.name, details, pointee)?;
+                }
+                Api::UniquePtrWithDeleter {
+                    name,
+                    pointee,
+                    cpp_definition,
+                    ..
+                } => {
+                    self.generate_typedef(&name.name, cpp_definition);
+                    self.generate_unique_ptr_with_deleter_shims(&name.name, pointee)?;
+                }
                 Api::CType { typename, .. } => self.generate_ctype_typedef(typename),
+                Api::ContainerElement { name, element } => {
+                    self.generate_container_element_typedef(name, element)
+                }
                 Api::Subclass { .. } => deferred_apis.push(api),
+                Api::RustTrait { details, deps, .. } => self.generate_rust_trait(details, deps)?,
                 Api::RustSubclassFn {
                     subclass, details, ..
                 } => {
@@ -556,6 +634,49 @@ impl<'a> CppCodeGenerator<'a> {
                 "".to_string(),
                 true,
             ),
+            CppFunctionBody::AssignmentOperator => (
+                format!(
+                    "{} = {arg_list}",
+                    receiver.expect("Assignment operator had no receiver")
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::FieldGetter(field) => (
+                format!(
+                    "{}.{field}",
+                    receiver.expect("Field getter had no receiver")
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::FieldSetter(field) => (
+                format!(
+                    "{}.{field} = {arg_list}",
+                    receiver.expect("Field setter had no receiver")
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::UniqueToShared(ty) => (
+                format!("std::shared_ptr<{}>({arg_list})", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::ConversionOperator(conversion_operator) => (
+                format!(
+                    "static_cast<{}>({})",
+                    conversion_operator.cpp_type(),
+                    receiver.expect("Conversion operator had no receiver")
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::MakeShared(ty) => (
+                format!("std::make_shared<{}>({arg_list})", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
         };
         if let Some(ret) = &details.return_conversion {
             let call_itself = match conversion_direction {
@@ -612,6 +733,30 @@ impl<'a> CppCodeGenerator<'a> {
             headers.push(Header::System("stddef.h"));
             headers.push(Header::NewDeletePrelude);
         }
+        if details
+            .argument_conversion
+            .iter()
+            .chain(details.return_conversion.iter())
+            .any(|conv| conv.is_chrono())
+        {
+            headers.push(Header::System("chrono"));
+            headers.push(Header::System("cstdint"));
+            headers.push(Header::ChronoPrelude);
+        }
+        if details
+            .argument_conversion
+            .iter()
+            .chain(details.return_conversion.iter())
+            .any(|conv| conv.is_shared_ptr())
+        {
+            headers.push(Header::System("cstdio"));
+            headers.push(Header::System("cstdlib"));
+            headers.push(Header::System("iterator"));
+            headers.push(Header::System("mutex"));
+            headers.push(Header::System("set"));
+            headers.push(Header::System("type_traits"));
+            headers.push(Header::SharedPtrPrelude);
+        }
         Ok(ExtraCpp {
             declaration,
             definition,
@@ -629,6 +774,237 @@ impl<'a> CppCodeGenerator<'a> {
         self.generate_typedef(tn, &cpp_name)
     }
 
+    fn generate_container_element_typedef(&mut self, name: &ApiName, element: &QualifiedName) {
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(format!(
+                "typedef {} {};",
+                element.to_cpp_name(),
+                name.cpp_name()
+            )),
+            ..Default::default()
+        })
+    }
+
+    /// Generates a trivial struct corresponding to a `std::pair` or
+    /// `std::tuple`, with conversions to and from the original type.
+    fn generate_tuple_struct(
+        &mut self,
+        tn: &QualifiedName,
+        elements: &[crate::minisyn::Type],
+        cpp_definition: &str,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let our_name = tn.get_final_item();
+        let element_types = elements
+            .iter()
+            .map(|ty| self.original_name_map.type_to_cpp(ty))
+            .collect::<Result<Vec<_>, _>>()?;
+        let fields = element_types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| format!("    {ty} f{i};\n"))
+            .join("");
+        let getters = (0..elements.len())
+            .map(|i| format!("std::get<{i}>(t)"))
+            .join(", ");
+        let field_names = (0..elements.len()).map(|i| format!("f{i}")).join(", ");
+        let type_definition = formatdoc! {"
+            struct {our_name} {{
+            {fields}    static {our_name} autocxx_from(const {cpp_definition}& t) {{
+                    return {our_name}{{{getters}}};
+                }}
+                {cpp_definition} autocxx_into() const {{
+                    return {cpp_definition}({field_names});
+                }}
+            }};"
+        };
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(type_definition),
+            headers: vec![Header::System("tuple"), Header::System("utility")],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Generates functions to find which alternative a `std::variant`
+    /// holds, to extract it, and to construct a variant from it.
+    fn generate_variant_shims(
+        &mut self,
+        tn: &QualifiedName,
+        alternatives: &[crate::minisyn::Type],
+        kinds: &[ElementKind],
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let our_name = tn.get_final_item();
+        let index_fn = shim_name(tn, "index");
+        let mut declaration = format!(
+            "inline size_t {index_fn}(const {our_name}& variant) {{ return variant.index(); }}\n"
+        );
+        for (i, (ty, kind)) in alternatives.iter().zip(kinds.iter()).enumerate() {
+            let alternative = self.original_name_map.type_to_cpp(ty)?;
+            let get_fn = shim_name(tn, &format!("get_{i}"));
+            let make_fn = shim_name(tn, &format!("make_{i}"));
+            let (bridge_type, extracted, held) = match kind {
+                ElementKind::ByValue => (
+                    alternative.clone(),
+                    format!("std::get<{i}>(std::move(*variant))"),
+                    "alternative",
+                ),
+                ElementKind::UniquePtr => (
+                    format!("std::unique_ptr<{alternative}>"),
+                    format!("std::make_unique<{alternative}>(std::get<{i}>(std::move(*variant)))"),
+                    "*alternative",
+                ),
+            };
+            declaration.push_str(&format!(
+                "inline {bridge_type} {get_fn}(std::unique_ptr<{our_name}> variant) {{ return {extracted}; }}\n"
+            ));
+            declaration.push_str(&format!(
+                "inline std::unique_ptr<{our_name}> {make_fn}({bridge_type} alternative) {{ return std::make_unique<{our_name}>(std::in_place_index<{i}>, std::move({held})); }}\n"
+            ));
+        }
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declaration),
+            headers: vec![
+                Header::System("cstddef"),
+                Header::System("memory"),
+                Header::System("utility"),
+                Header::System("variant"),
+            ],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Generates functions to query and modify a `std::map`,
+    /// `std::unordered_map` or `std::set`, and to step through its keys.
+    fn generate_associative_container_shims(
+        &mut self,
+        tn: &QualifiedName,
+        container_type: AssociativeContainerType,
+        elements: &[crate::minisyn::Type],
+        kinds: &[ElementKind],
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let our_name = tn.get_final_item();
+        let mut params = Vec::new();
+        for ((ty, kind), param_name) in elements.iter().zip(kinds.iter()).zip(["key", "value"]) {
+            let cpp_type = self.original_name_map.type_to_cpp(ty)?;
+            let (param, held) = match kind {
+                ElementKind::ByValue => {
+                    (format!("{cpp_type} {param_name}"), param_name.to_string())
+                }
+                ElementKind::UniquePtr => (
+                    format!("std::unique_ptr<{cpp_type}> {param_name}"),
+                    format!("*{param_name}"),
+                ),
+            };
+            params.push((cpp_type, param, held));
+        }
+        let (key_type, key_param, key_held) = &params[0];
+        let len_fn = shim_name(tn, "len");
+        let insert_fn = shim_name(tn, "insert");
+        let remove_fn = shim_name(tn, "remove");
+        let first_fn = shim_name(tn, "first");
+        let next_fn = shim_name(tn, "next");
+        let key_of = if container_type.has_values() {
+            "it->first"
+        } else {
+            "*it"
+        };
+        let mut declaration = formatdoc! {"
+            inline size_t {len_fn}(const {our_name}& container) {{ return container.size(); }}
+            inline bool {remove_fn}({our_name}& container, const {key_type}& key) {{ return container.erase(key) != 0; }}
+            inline const {key_type}* {first_fn}(const {our_name}& container) {{ auto it = container.begin(); return it == container.end() ? nullptr : &{key_of}; }}
+            inline const {key_type}* {next_fn}(const {our_name}& container, const {key_type}& key) {{ auto it = container.find(key); if (it == container.end() || ++it == container.end()) return nullptr; return &{key_of}; }}
+        "};
+        if container_type.has_values() {
+            let (value_type, value_param, value_held) = &params[1];
+            let get_fn = shim_name(tn, "get");
+            declaration.push_str(&formatdoc! {"
+                inline const {value_type}* {get_fn}(const {our_name}& container, const {key_type}& key) {{ auto it = container.find(key); return it == container.end() ? nullptr : &it->second; }}
+                inline bool {insert_fn}({our_name}& container, {key_param}, {value_param}) {{ bool inserted = container.erase({key_held}) == 0; container.emplace(std::move({key_held}), std::move({value_held})); return inserted; }}
+            "});
+        } else {
+            let contains_fn = shim_name(tn, "contains");
+            declaration.push_str(&formatdoc! {"
+                inline bool {contains_fn}(const {our_name}& container, const {key_type}& key) {{ return container.count(key) != 0; }}
+                inline bool {insert_fn}({our_name}& container, {key_param}) {{ return container.insert(std::move({key_held})).second; }}
+            "});
+        }
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(declaration),
+            headers: vec![
+                Header::System("cstddef"),
+                Header::System("memory"),
+                Header::System("utility"),
+                Header::System(container_type.header()),
+            ],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Generates functions to convert a custom smart pointer to and from a
+    /// raw pointer which owns a reference, and to add and release references.
+    fn generate_smart_pointer_shims(
+        &mut self,
+        tn: &QualifiedName,
+        details: &SmartPointer,
+        pointee: &Type,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let our_name = tn.get_final_item();
+        let pointee = self.original_name_map.type_to_cpp(pointee)?;
+        let SmartPointer {
+            deref,
+            clone,
+            release,
+        } = details;
+        let into_raw_fn = shim_name(tn, "into_raw");
+        let from_raw_fn = shim_name(tn, "from_raw");
+        let add_ref_fn = shim_name(tn, "add_ref");
+        let release_fn = shim_name(tn, "release");
+        // The pointee may really be const, since bindgen doesn't tell us.
+        let type_definition = formatdoc! {"
+            inline {pointee}* {into_raw_fn}(const {our_name}& p) {{ {pointee}* raw = const_cast<{pointee}*>(p.{deref}()); if (raw) raw->{clone}(); return raw; }}
+            inline {our_name} {from_raw_fn}({pointee}* raw) {{ {our_name} p(raw); if (raw) raw->{release}(); return p; }}
+            inline void {add_ref_fn}(const {pointee}* raw) {{ const_cast<{pointee}*>(raw)->{clone}(); }}
+            inline void {release_fn}(const {pointee}* raw) {{ const_cast<{pointee}*>(raw)->{release}(); }}
+        "};
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(type_definition),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Generates functions to convert a `std::unique_ptr` with a custom
+    /// deleter to and from an owning raw pointer, and to delete the pointee.
+    fn generate_unique_ptr_with_deleter_shims(
+        &mut self,
+        tn: &QualifiedName,
+        pointee: &Type,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let our_name = tn.get_final_item();
+        let pointee = self.original_name_map.type_to_cpp(pointee)?;
+        let into_raw_fn = shim_name(tn, "into_raw");
+        let from_raw_fn = shim_name(tn, "from_raw");
+        let delete_fn = shim_name(tn, "delete");
+        // Rust holds only the raw pointer, and we make a fresh deleter
+        // whenever we rebuild the std::unique_ptr, so any state in the
+        // deleter would be lost.
+        let type_definition = formatdoc! {"
+            static_assert(std::is_empty<{our_name}::deleter_type>::value,
+                          \"autocxx can't represent a std::unique_ptr with a stateful deleter\");
+            inline {pointee}* {into_raw_fn}({our_name} p) {{ return p.release(); }}
+            inline {our_name} {from_raw_fn}({pointee}* raw) {{ return {our_name}(raw); }}
+            inline void {delete_fn}({pointee}* raw) {{ {our_name} owner(raw); }}
+        "};
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(type_definition),
+            headers: vec![Header::System("memory"), Header::System("type_traits")],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
     fn generate_typedef(&mut self, tn: &QualifiedName, definition: &str) {
         let our_name = tn.get_final_item();
         self.additional_functions.push(ExtraCpp {
@@ -637,6 +1013,99 @@ impl<'a> CppCodeGenerator<'a> {
         })
     }
 
+    /// Generates an abstract class corresponding to a Rust trait, and a
+    /// subclass which implements it by calling into a `rust::Box` of the
+    /// Rust type which holds a `Box<dyn Trait>`.
+    fn generate_rust_trait(
+        &mut self,
+        details: &RustTrait,
+        deps: &[QualifiedName],
+    ) -> Result<(), ConvertErrorFromCpp> {
+        let trait_id = details.path.get_final_ident();
+        let holder = details.holder();
+        let cpp_handle = details.cpp_handle();
+        let namespaced_types: HashMap<_, _> = deps
+            .iter()
+            .map(|dep| (dep.get_final_item().to_string(), dep.clone()))
+            .collect();
+        // cxx can't pass an Option<Box<T>>, so these become pointers.
+        let to_cpp = |ty: &Type| match extract_option_box_inner_type(ty) {
+            Some(inner) => self
+                .original_name_map
+                .rust_type_to_cpp(&parse_quote! { *mut #inner }, &namespaced_types),
+            None => self
+                .original_name_map
+                .rust_type_to_cpp(ty, &namespaced_types),
+        };
+        let mut abstract_decls = Vec::new();
+        let mut handle_decls = Vec::new();
+        let mut definitions = vec![format!(
+            "{cpp_handle}::{cpp_handle}(rust::Box<{holder}> obs) : obs(std::move(obs)) {{}}\n{cpp_handle}::~{cpp_handle}() {{}}"
+        )];
+        for method in &details.methods {
+            let name = &method.sig.ident;
+            let mut constness = " const";
+            let mut params = Vec::new();
+            let mut args = Vec::new();
+            for (counter, input) in unqualify_params(method.sig.inputs.clone())
+                .iter()
+                .enumerate()
+            {
+                match input {
+                    FnArg::Typed(pt) if counter == 0 => {
+                        // This is the receiver, which refers to the holder.
+                        if matches!(
+                            &*pt.ty,
+                            Type::Reference(TypeReference {
+                                mutability: Some(_),
+                                ..
+                            })
+                        ) {
+                            constness = "";
+                        }
+                    }
+                    FnArg::Typed(pt) => {
+                        let arg_name = format!("arg{counter}");
+                        let cpp_type = to_cpp(&pt.ty)?;
+                        args.push(if cpp_type.ends_with('&') {
+                            arg_name.clone()
+                        } else {
+                            format!("std::move({arg_name})")
+                        });
+                        params.push(format!("{cpp_type} {arg_name}"));
+                    }
+                    FnArg::Receiver(_) => {}
+                }
+            }
+            let ret = match unqualify_ret_type(method.sig.output.clone()) {
+                ReturnType::Default => "void".to_string(),
+                ReturnType::Type(_, ty) => match extract_result_ok_type(&ty).unwrap_or(&ty) {
+                    Type::Tuple(tt) if tt.elems.is_empty() => "void".to_string(),
+                    ty => to_cpp(ty)?,
+                },
+            };
+            let params = params.join(", ");
+            abstract_decls.push(format!("virtual {ret} {name}({params}){constness} = 0;"));
+            handle_decls.push(format!("{ret} {name}({params}){constness} override;"));
+            definitions.push(format!(
+                "{ret} {cpp_handle}::{name}({params}){constness} {{\nreturn obs->{name}({});\n}}",
+                args.join(", ")
+            ));
+        }
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(format!(
+                "struct {holder};\nclass {trait_id} {{\npublic:\nvirtual ~{trait_id}() {{}}\n{}\n}};\nclass {cpp_handle} : public {trait_id} {{\npublic:\nexplicit {cpp_handle}(rust::Box<{holder}> obs);\n~{cpp_handle}();\n{}\nprivate:\nrust::Box<{holder}> obs;\n}};",
+                abstract_decls.join("\n"),
+                handle_decls.join("\n"),
+            )),
+            definition: Some(definitions.join("\n")),
+            headers: vec![Header::CxxH],
+            cpp_headers: vec![Header::CxxgenH, Header::System("utility")],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
     fn generate_subclass(
         &mut self,
         superclass: &QualifiedName,
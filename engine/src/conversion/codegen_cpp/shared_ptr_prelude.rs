@@ -0,0 +1,126 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indoc::indoc;
+
+/// Conversions for `std::shared_ptr`s and `std::weak_ptr`s passed between
+/// Rust and C++. bindgen can't tell us whether their pointees are `const`,
+/// so Rust always sees a pointer to non-`const`.
+/// A `std::shared_ptr<const T>` returned to Rust is therefore given its own
+/// control block, whose deleter owns the original pointer. We record that
+/// control block as pointing to a `const` object, so if Rust passes it back
+/// to C++ which expects a `std::shared_ptr<T>` (or a `std::weak_ptr<T>`) we
+/// abort rather than allow the object to be mutated. We identify control
+/// blocks using `owner_before`, rather than by looking for our deleter,
+/// because `std::get_deleter` doesn't work without RTTI. A `std::weak_ptr`
+/// to `const` can't be recorded like this (its control block wouldn't have
+/// any owners) so it can't be returned to Rust at all.
+/// Parameters are converted using a templated conversion operator, as we
+/// don't know whether the C++ function takes a pointer to `const`, except
+/// for mutable references, which C++ only accepts for pointers to
+/// non-`const`.
+pub(super) static SHARED_PTR_PRELUDE: &str = indoc! {"
+    #ifndef AUTOCXX_SHARED_PTR_PRELUDE
+    #define AUTOCXX_SHARED_PTR_PRELUDE
+    template <typename T> struct autocxx_const_pointee_owner {
+      std::shared_ptr<const T> original;
+      void operator()(T *) { original.reset(); }
+    };
+    template <typename T> struct autocxx_always_false : std::false_type {};
+    template <typename T> class autocxx_const_pointees {
+      std::mutex mutex;
+      std::set<std::weak_ptr<T>, std::owner_less<std::weak_ptr<T>>> owners;
+
+    public:
+      static autocxx_const_pointees &get() {
+        // Leaked, so that it outlives any static shared_ptrs.
+        static autocxx_const_pointees *pointees = new autocxx_const_pointees;
+        return *pointees;
+      }
+      void add(const std::shared_ptr<T> &p) {
+        std::lock_guard<std::mutex> lock(mutex);
+        for (auto it = owners.begin(); it != owners.end();) {
+          it = it->expired() ? owners.erase(it) : std::next(it);
+        }
+        owners.insert(p);
+      }
+      bool contains(const std::weak_ptr<T> &p) {
+        std::lock_guard<std::mutex> lock(mutex);
+        return owners.count(p) != 0;
+      }
+    };
+    template <typename T>
+    std::shared_ptr<T> autocxx_shared_ptr_to_rust(std::shared_ptr<T> p) {
+      return p;
+    }
+    template <typename T>
+    std::shared_ptr<T> autocxx_shared_ptr_to_rust(std::shared_ptr<const T> p) {
+      if (!p) {
+        return nullptr;
+      }
+      T *pointee = const_cast<T *>(p.get());
+      std::shared_ptr<T> result(pointee,
+                                autocxx_const_pointee_owner<T>{std::move(p)});
+      autocxx_const_pointees<T>::get().add(result);
+      return result;
+    }
+    template <typename T>
+    std::weak_ptr<T> autocxx_shared_ptr_to_rust(std::weak_ptr<T> p) {
+      return p;
+    }
+    template <typename T>
+    std::weak_ptr<T> autocxx_shared_ptr_to_rust(std::weak_ptr<const T>) {
+      static_assert(autocxx_always_false<T>::value,
+                    \"autocxx can't return a std::weak_ptr to a const type to Rust\");
+      return {};
+    }
+    template <typename T>
+    void autocxx_check_pointee_not_const(const std::weak_ptr<T> &p) {
+      if (autocxx_const_pointees<T>::get().contains(p)) {
+        std::fputs(\"autocxx: a std::shared_ptr to a const object was passed \"
+                   \"where one to a non-const object was expected\\n\",
+                   stderr);
+        std::abort();
+      }
+    }
+    template <typename T> struct autocxx_shared_ptr_arg {
+      const std::shared_ptr<T> &p;
+      operator std::shared_ptr<const T>() const { return p; }
+      operator std::shared_ptr<T>() const {
+        autocxx_check_pointee_not_const<T>(p);
+        return p;
+      }
+    };
+    template <typename T> struct autocxx_weak_ptr_arg {
+      const std::weak_ptr<T> &p;
+      operator std::weak_ptr<const T>() const { return p; }
+      operator std::weak_ptr<T>() const {
+        autocxx_check_pointee_not_const<T>(p);
+        return p;
+      }
+    };
+    template <typename T>
+    autocxx_shared_ptr_arg<T> autocxx_shared_ptr_from_rust(const std::shared_ptr<T> &p) {
+      return {p};
+    }
+    template <typename T>
+    autocxx_weak_ptr_arg<T> autocxx_shared_ptr_from_rust(const std::weak_ptr<T> &p) {
+      return {p};
+    }
+    template <typename T>
+    std::shared_ptr<T> &autocxx_mutable_shared_ptr_from_rust(std::shared_ptr<T> &p) {
+      autocxx_check_pointee_not_const<T>(p);
+      return p;
+    }
+    template <typename T>
+    std::weak_ptr<T> &autocxx_mutable_shared_ptr_from_rust(std::weak_ptr<T> &p) {
+      autocxx_check_pointee_not_const<T>(p);
+      return p;
+    }
+    #endif // AUTOCXX_SHARED_PTR_PRELUDE
+"};
@@ -14,7 +14,7 @@ use indexmap::map::IndexMap as HashMap;
 use itertools::Itertools;
 use quote::ToTokens;
 use std::iter::once;
-use syn::{Token, Type};
+use syn::{GenericArgument, PathArguments, Token, Type};
 
 /// Map from QualifiedName to original C++ name. Original C++ name does not
 /// include the namespace; this can be assumed to be the same as the namespace
@@ -138,6 +138,70 @@ impl CppNameMap {
         }
     }
 
+    /// Convert a type as written in an `extern "Rust"` function signature
+    /// (such as `Box<T>`, `&[u8]` or `Pin<&mut T>`) to its C++ spelling as
+    /// generated by cxx. Types are expected to be unqualified, as in the
+    /// cxx::bridge; `namespaced_types` gives the full names of any which
+    /// are within C++ namespaces.
+    pub(crate) fn rust_type_to_cpp(
+        &self,
+        ty: &Type,
+        namespaced_types: &HashMap<String, QualifiedName>,
+    ) -> Result<String, ConvertErrorFromCpp> {
+        match ty {
+            Type::Path(typ) => {
+                let seg = typ.path.segments.last().unwrap();
+                let generic_args = match &seg.arguments {
+                    PathArguments::AngleBracketed(ab) => ab
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            GenericArgument::Type(ty) => {
+                                Some(self.rust_type_to_cpp(ty, namespaced_types))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    _ => Vec::new(),
+                };
+                match (seg.ident.to_string().as_str(), generic_args.as_slice()) {
+                    // Pin<&mut T> is a plain reference in C++.
+                    ("Pin", [inner]) => Ok(inner.clone()),
+                    ("Box", [inner]) => Ok(format!("rust::Box<{inner}>")),
+                    ("Vec", [inner]) => Ok(format!("rust::Vec<{inner}>")),
+                    (id, []) => Ok(match namespaced_types.get(id) {
+                        Some(qual_name) => self.map(qual_name),
+                        None => self.map(&QualifiedName::from_type_path(typ)),
+                    }),
+                    _ => Err(ConvertErrorFromCpp::UnsupportedType(
+                        ty.to_token_stream().to_string(),
+                    )),
+                }
+            }
+            Type::Reference(typr) => match &*typr.elem {
+                Type::Path(typ) if typ.path.is_ident("str") => Ok("rust::Str".into()),
+                Type::Slice(slice) => Ok(format!(
+                    "rust::Slice<{}{}>",
+                    get_mut_string(&typr.mutability),
+                    self.rust_type_to_cpp(&slice.elem, namespaced_types)?
+                )),
+                elem => Ok(format!(
+                    "{}{}&",
+                    get_mut_string(&typr.mutability),
+                    self.rust_type_to_cpp(elem, namespaced_types)?
+                )),
+            },
+            Type::Ptr(typp) => Ok(format!(
+                "{}{}*",
+                get_mut_string(&typp.mutability),
+                self.rust_type_to_cpp(&typp.elem, namespaced_types)?
+            )),
+            _ => Err(ConvertErrorFromCpp::UnsupportedType(
+                ty.to_token_stream().to_string(),
+            )),
+        }
+    }
+
     /// Check an individual item in the name map. Returns a thing if
     /// it's an inner type, otherwise returns none.
     pub(crate) fn get(&self, name: &QualifiedName) -> Option<&String> {
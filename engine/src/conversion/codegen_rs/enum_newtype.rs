@@ -0,0 +1,170 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use quote::{format_ident, quote};
+use syn::{parse_quote, Item, ItemEnum, Type};
+
+use crate::conversion::doc_attr::get_doc_attrs;
+
+/// Find the underlying integer type from the `#[repr(...)]` which bindgen
+/// puts on every enum.
+fn get_repr_type(e: &ItemEnum) -> Type {
+    e.attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .find_map(|attr| attr.parse_args::<Type>().ok())
+        .unwrap_or_else(|| parse_quote! { ::std::os::raw::c_uint })
+}
+
+/// Represent a C++ enum as a newtype around its underlying integer type,
+/// with an associated constant for each enumerator. Unlike a Rust `enum`,
+/// this can safely hold any value which C++ gives us. Returns the newtype,
+/// followed by its implementations, and the underlying type.
+fn make_newtype(e: &ItemEnum) -> (Item, Vec<Item>, Type) {
+    let id = &e.ident;
+    let repr = get_repr_type(e);
+    let doc_attrs = get_doc_attrs(&e.attrs);
+    let consts = e.variants.iter().filter_map(|v| {
+        let (_, discriminant) = v.discriminant.as_ref()?;
+        let variant_id = &v.ident;
+        let variant_doc_attrs = get_doc_attrs(&v.attrs);
+        Some(quote! {
+            #(#variant_doc_attrs)*
+            pub const #variant_id: Self = Self(#discriminant);
+        })
+    });
+    let newtype = Item::Struct(parse_quote! {
+        #(#doc_attrs)*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct #id(pub #repr);
+    });
+    let impls = vec![Item::Impl(parse_quote! {
+        impl #id {
+            #(#consts)*
+        }
+    })];
+    (newtype, impls, repr)
+}
+
+/// Represent a C++ enum as a set of bit flags, which may hold any
+/// combination of its enumerators. Returns the newtype, followed by its
+/// implementations.
+pub(super) fn make_bitflags(e: ItemEnum) -> (Item, Vec<Item>) {
+    let id = &e.ident;
+    let (newtype, mut impls, repr) = make_newtype(&e);
+    impls.extend([
+        Item::Impl(parse_quote! {
+            impl #id {
+                /// The raw value of these flags.
+                pub const fn bits(self) -> #repr {
+                    self.0
+                }
+
+                /// Whether all the flags in `other` are also set in `self`.
+                pub const fn contains(self, other: Self) -> bool {
+                    self.0 & other.0 == other.0
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::ops::BitOr for #id {
+                type Output = Self;
+                fn bitor(self, rhs: Self) -> Self {
+                    Self(self.0 | rhs.0)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::ops::BitOrAssign for #id {
+                fn bitor_assign(&mut self, rhs: Self) {
+                    self.0 |= rhs.0
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::ops::BitAnd for #id {
+                type Output = Self;
+                fn bitand(self, rhs: Self) -> Self {
+                    Self(self.0 & rhs.0)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::ops::BitAndAssign for #id {
+                fn bitand_assign(&mut self, rhs: Self) {
+                    self.0 &= rhs.0
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::ops::Not for #id {
+                type Output = Self;
+                fn not(self) -> Self {
+                    Self(!self.0)
+                }
+            }
+        }),
+    ]);
+    (newtype, impls)
+}
+
+/// Represent a C++ enum such that it can hold values other than its
+/// enumerators, along with a companion Rust `enum` which can only hold
+/// the enumerators, and conversions between the two. Returns the newtype,
+/// followed by the companion `enum` and the implementations.
+pub(super) fn make_non_exhaustive_enum(e: ItemEnum) -> (Item, Vec<Item>) {
+    let id = &e.ident;
+    let (newtype, mut impls, repr) = make_newtype(&e);
+    let enum_id = format_ident!("{}Enum", id);
+    let mut companion = e.clone();
+    companion.ident = enum_id.clone();
+    companion.attrs.retain(|attr| attr.path().is_ident("repr"));
+    let match_arms = e.variants.iter().filter_map(|v| {
+        let (_, discriminant) = v.discriminant.as_ref()?;
+        let variant_id = &v.ident;
+        Some(quote! {
+            #discriminant => Ok(#enum_id::#variant_id),
+        })
+    });
+    let companion_doc = format!("The known values of [`{id}`], obtained using `TryFrom`.");
+    impls.extend([
+        Item::Enum(parse_quote! {
+            #[doc = #companion_doc]
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            #companion
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::convert::TryFrom<#repr> for #enum_id {
+                type Error = #repr;
+                fn try_from(val: #repr) -> Result<Self, #repr> {
+                    match val {
+                        #(#match_arms)*
+                        _ => Err(val),
+                    }
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ::core::convert::TryFrom<#id> for #enum_id {
+                type Error = #id;
+                fn try_from(val: #id) -> Result<Self, #id> {
+                    Self::try_from(val.0).map_err(#id)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl From<#enum_id> for #id {
+                fn from(val: #enum_id) -> Self {
+                    Self(val as #repr)
+                }
+            }
+        }),
+    ]);
+    (newtype, impls)
+}
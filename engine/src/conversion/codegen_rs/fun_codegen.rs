@@ -102,6 +102,10 @@ pub(super) fn gen_function(
     let ret_conversion = analysis.ret_conversion;
     let param_details = analysis.param_details;
     let wrapper_function_needed = analysis.cpp_wrapper.is_some();
+    let make_shared_name = analysis
+        .make_shared_wrapper
+        .as_ref()
+        .map(|make_shared_wrapper| make_shared_wrapper.wrapper_function_name.0.clone());
     let params = analysis.params;
     let vis = analysis.vis;
     let kind = analysis.kind;
@@ -118,6 +122,7 @@ pub(super) fn gen_function(
     let fn_generator = FnGenerator {
         param_details: &param_details,
         cxxbridge_name: &cxxbridge_name,
+        make_shared_name: make_shared_name.as_ref(),
         rust_name,
         unsafety: &analysis.requires_unsafe,
         always_unsafe_due_to_trait_definition,
@@ -139,11 +144,33 @@ pub(super) fn gen_function(
         match kind {
             FnKind::Method {
                 ref impl_for,
-                method_kind: MethodKind::Constructor { .. },
+                method_kind:
+                    MethodKind::Constructor {
+                        is_default,
+                        is_converting,
+                    },
                 ..
             } => {
                 // Constructor.
                 impl_entry = Some(fn_generator.generate_constructor_impl(impl_for));
+                let is_pod = !non_pod_types.contains(impl_for);
+                if is_converting {
+                    bindgen_mod_items.push(fn_generator.generate_from_impl(impl_for, is_pod));
+                }
+                // We can only return a value from `Default::default` if the
+                // type is trivially movable; other types must be constructed
+                // in place using `new`.
+                if is_pod {
+                    // A constructor whose parameters all have default values
+                    // is also a default constructor, but we can't call it
+                    // without arguments.
+                    if is_default
+                        && param_details.len() == 1
+                        && analysis.requires_unsafe.wrapper_token().is_none()
+                    {
+                        bindgen_mod_items.push(fn_generator.generate_default_impl(impl_for));
+                    }
+                }
             }
             FnKind::Method {
                 ref impl_for,
@@ -196,6 +223,31 @@ pub(super) fn gen_function(
     // which the user has declared.
     let params = unqualify_params(params);
     let ret_type = unqualify_ret_type(ret_type.into_owned());
+    let bridge_unsafety = analysis.requires_unsafe.bridge_token();
+    // A constructor may also have a sibling C++ function which creates the
+    // object using `std::make_shared`, taking the same parameters except
+    // for the memory in which to construct it.
+    let make_shared_item = make_shared_name.map(|make_shared_name| {
+        let make_shared_params = params
+            .iter()
+            .zip(param_details.iter())
+            .filter(|(_, pd)| !pd.is_placement_return_destination)
+            .map(|(param, _)| param);
+        let make_shared_ret_type = unqualify_ret_type(
+            analysis
+                .make_shared_wrapper
+                .as_ref()
+                .and_then(|make_shared_wrapper| make_shared_wrapper.return_conversion.as_ref())
+                .map(|conversion| {
+                    let ty = conversion.unconverted_rust_type();
+                    parse_quote! { -> #ty }
+                })
+                .expect("make_shared wrapper had no return type"),
+        );
+        ForeignItem::Fn(parse_quote!(
+            #vis #bridge_unsafety fn #make_shared_name #lifetime_tokens ( #(#make_shared_params),* ) #make_shared_ret_type;
+        ))
+    });
     // And we need to make an attribute for the namespace that the function
     // itself is in.
     let namespace_attr = if ns.is_empty() || wrapper_function_needed {
@@ -209,7 +261,6 @@ pub(super) fn gen_function(
             .unwrap()
     };
     // At last, actually generate the cxx::bridge entry.
-    let bridge_unsafety = analysis.requires_unsafe.bridge_token();
     let extern_c_mod_item = ForeignItem::Fn(parse_quote!(
         #(#namespace_attr)*
         #(#cpp_name_attr)*
@@ -217,7 +268,9 @@ pub(super) fn gen_function(
         #vis #bridge_unsafety fn #cxxbridge_name #lifetime_tokens ( #params ) #ret_type;
     ));
     RsCodegenResult {
-        extern_c_mod_items: vec![extern_c_mod_item],
+        extern_c_mod_items: std::iter::once(extern_c_mod_item)
+            .chain(make_shared_item)
+            .collect(),
         bindgen_mod_items,
         impl_entry,
         trait_impl_entry,
@@ -233,6 +286,7 @@ struct FnGenerator<'a> {
     ret_conversion: &'a Option<TypeConversionPolicy>,
     ret_type: &'a ReturnType,
     cxxbridge_name: &'a Ident,
+    make_shared_name: Option<&'a Ident>,
     rust_name: &'a str,
     unsafety: &'a UnsafetyNeeded,
     always_unsafe_due_to_trait_definition: bool,
@@ -257,6 +311,7 @@ impl<'a> FnGenerator<'a> {
         let mut local_variables = Vec::new();
         let mut arg_list = Vec::new();
         let mut ptr_arg_name = None;
+        let mut ptr_arg_index = None;
         let mut ret_type: Cow<'a, _> = ret_type
             .map(Cow::Owned)
             .unwrap_or_else(|| Cow::Borrowed(self.ret_type));
@@ -281,6 +336,7 @@ impl<'a> FnGenerator<'a> {
                     arg_list.push(conversion.clone());
                     local_variables.append(&mut these_local_variables);
                     if pd.is_placement_return_destination {
+                        ptr_arg_index = Some(arg_list.len() - 1);
                         ptr_arg_name = Some(conversion);
                     } else {
                         let param_mutability = pd.conversion.rust_conversion.requires_mutability();
@@ -321,18 +377,15 @@ impl<'a> FnGenerator<'a> {
             || self.always_unsafe_due_to_trait_definition;
         let (call_body, ret_type) = match self.ret_conversion {
             Some(ret_conversion) if ret_conversion.rust_work_needed() => {
-                // There's a potential lurking bug below. If the return type conversion requires
-                // unsafe, then we'll end up doing something like
+                // If the return type conversion requires unsafe, the call body will
+                // be embedded within its unsafe block, so mustn't get its own:
+                // otherwise we'd generate
                 //   unsafe { do_return_conversion( unsafe { call_body() })}
-                // and the generated code will get warnings about nested unsafe blocks.
-                // That's because we convert the call body to tokens in the following
-                // line without considering the fact it's embedded in another expression.
-                // At the moment this is OK because no return type conversions require
-                // unsafe, but if this happens in future, we should do:
-                //   let temp_ret_val = unsafe { call_body() };
-                //   do_return_conversion(temp_ret_val)
-                // by returning a vector of MaybeUnsafes within call_body.
-                let expr = maybe_unsafes_to_tokens(vec![call_body], context_is_unsafe);
+                // and the generated code would get warnings about nested unsafe blocks.
+                let expr = maybe_unsafes_to_tokens(
+                    vec![call_body],
+                    context_is_unsafe || ret_conversion.return_conversion_requires_unsafe(),
+                );
                 let conv =
                     ret_conversion.rust_conversion(parse_quote! { #expr }, &mut variable_counter);
                 let (conversion, requires_unsafe, ty) = match conv {
@@ -361,7 +414,44 @@ impl<'a> FnGenerator<'a> {
             _ => (call_body, ret_type),
         };
 
-        let call_stmts = if let Some(ptr_arg_name) = ptr_arg_name {
+        let call_stmts = if let (Some(ptr_arg_name), Some(ptr_arg_index), Some(make_shared_name)) =
+            (&ptr_arg_name, ptr_arg_index, self.make_shared_name)
+        {
+            // This constructor can also create the object within a
+            // `std::shared_ptr` using `std::make_shared`, so the closure is
+            // told where to construct it.
+            let make_shared_args = arg_list
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != ptr_arg_index)
+                .map(|(_, arg)| arg);
+            let in_place_body = maybe_unsafes_to_tokens(
+                vec![
+                    MaybeUnsafeStmt::new(
+                        quote! { let #ptr_arg_name = #ptr_arg_name.get_unchecked_mut().as_mut_ptr();},
+                    ),
+                    call_body,
+                ],
+                true,
+            );
+            let mut closure_stmts = local_variables;
+            closure_stmts.push(MaybeUnsafeStmt::new(quote! {
+                match autocxx_gen_target {
+                    autocxx::ConstructionTarget::InPlace(#ptr_arg_name) => {
+                        #in_place_body
+                    }
+                    autocxx::ConstructionTarget::SharedPtr(autocxx_gen_shared_ptr) => {
+                        *autocxx_gen_shared_ptr = cxxbridge::#make_shared_name ( #(#make_shared_args),* )
+                    }
+                }
+            }));
+            let closure_stmts = maybe_unsafes_to_tokens(closure_stmts, true);
+            vec![MaybeUnsafeStmt::needs_unsafe(parse_quote! {
+                autocxx::CppConstructor::<Self, _>::by_raw(move |autocxx_gen_target| {
+                    #closure_stmts
+                })
+            })]
+        } else if let Some(ptr_arg_name) = ptr_arg_name {
             let mut closure_stmts = local_variables;
             closure_stmts.push(MaybeUnsafeStmt::binary(
                 quote! { let #ptr_arg_name = unsafe { #ptr_arg_name.get_unchecked_mut().as_mut_ptr() };},
@@ -453,7 +543,11 @@ impl<'a> FnGenerator<'a> {
         &self,
         impl_block_type_name: &QualifiedName,
     ) -> Box<ImplBlockDetails> {
-        let ret_type: ReturnType = parse_quote! { -> impl autocxx::moveit::new::New<Output=Self> };
+        let ret_type: ReturnType = if self.make_shared_name.is_some() {
+            parse_quote! { -> impl autocxx::moveit::new::New<Output=Self> + autocxx::WithinSharedPtr<Inner=Self> }
+        } else {
+            parse_quote! { -> impl autocxx::moveit::new::New<Output=Self> }
+        };
         let (lifetime_tokens, wrapper_params, ret_type, call_body) =
             self.common_parts(true, &None, Some(ret_type));
         let rust_name = make_ident(self.rust_name);
@@ -473,6 +567,66 @@ impl<'a> FnGenerator<'a> {
         })
     }
 
+    /// Generate an 'impl From<Arg> for Type' item which calls a converting
+    /// constructor. POD types are returned by value, but other types can't
+    /// be, so for those we instead implement `From` for `Pin<Box<Type>>`.
+    fn generate_from_impl(&self, impl_block_type_name: &QualifiedName, is_pod: bool) -> Item {
+        let arg_ty = self
+            .param_details
+            .iter()
+            .find(|pd| !pd.is_placement_return_destination)
+            .expect("Converting constructor had no argument")
+            .conversion
+            .converted_rust_type();
+        let ty = impl_block_type_name.to_type_path();
+        if is_pod {
+            let construct = self.construct_pod_value(quote! { value });
+            Item::Impl(parse_quote! {
+                impl From<#arg_ty> for #ty {
+                    fn from(value: #arg_ty) -> Self {
+                        #construct
+                    }
+                }
+            })
+        } else {
+            let rust_name = make_ident(self.rust_name);
+            Item::Impl(parse_quote! {
+                impl From<#arg_ty> for ::core::pin::Pin<Box<#ty>> {
+                    fn from(value: #arg_ty) -> Self {
+                        <Box<#ty> as autocxx::moveit::Emplace<#ty>>::emplace(#ty::#rust_name(value))
+                    }
+                }
+            })
+        }
+    }
+
+    /// Generate an 'impl Default for Type' item which calls the default
+    /// constructor, for a POD type which can be returned by value. Unlike
+    /// zeroing the type, this honors any C++ member initializers.
+    fn generate_default_impl(&self, impl_block_type_name: &QualifiedName) -> Item {
+        let ty = impl_block_type_name.to_type_path();
+        let construct = self.construct_pod_value(quote! {});
+        Item::Impl(parse_quote! {
+            impl Default for #ty {
+                fn default() -> Self {
+                    #construct
+                }
+            }
+        })
+    }
+
+    /// Statements which call this constructor with the given arguments to
+    /// make a value on the stack, then move it out.
+    fn construct_pod_value(&self, args: TokenStream) -> TokenStream {
+        let rust_name = make_ident(self.rust_name);
+        quote! {
+            autocxx::moveit::moveit! {
+                let value = Self::#rust_name(#args);
+            }
+            autocxx::moveit::MoveRef::into_inner(::core::pin::Pin::into_inner(value))
+        }
+    }
+
     /// Generate a function call wrapper
     fn generate_function_impl(&self) -> Item {
         let (lifetime_tokens, wrapper_params, ret_type, call_body) =
@@ -11,6 +11,7 @@ use syn::{Expr, Type, TypePtr};
 
 use crate::{
     conversion::analysis::fun::function_wrapper::{RustConversionType, TypeConversionPolicy},
+    known_types::ChronoType,
     types::make_ident,
 };
 use quote::quote;
@@ -32,6 +33,15 @@ pub(super) enum RustParamConversion {
 }
 
 impl TypeConversionPolicy {
+    /// Whether converting a return value requires unsafe, in which case
+    /// the call itself will be within the same unsafe block.
+    pub(super) fn return_conversion_requires_unsafe(&self) -> bool {
+        matches!(
+            self.rust_conversion,
+            RustConversionType::FromRawToOwningPointer(_)
+        )
+    }
+
     pub(super) fn rust_conversion(&self, var: Expr, counter: &mut usize) -> RustParamConversion {
         match self.rust_conversion {
             RustConversionType::None => RustParamConversion::Param {
@@ -213,6 +223,60 @@ impl TypeConversionPolicy {
                     conversion_requires_unsafe: false,
                 }
             }
+            RustConversionType::FromChronoToNanos(chrono_type, ref ty) => {
+                let (ty, conversion) = match chrono_type {
+                    ChronoType::Duration => (
+                        parse_quote! { impl Into<#ty> },
+                        quote! { autocxx::chrono::duration_to_nanos(#var) },
+                    ),
+                    ChronoType::TimePoint => (
+                        ty.clone().into(),
+                        quote! { autocxx::chrono::time_point_to_nanos(#var) },
+                    ),
+                };
+                RustParamConversion::Param {
+                    ty,
+                    local_variables: Vec::new(),
+                    conversion,
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromNanosToChrono(chrono_type, ref ty) => {
+                let conversion = match chrono_type {
+                    ChronoType::Duration => quote! { autocxx::chrono::duration_from_nanos(#var) },
+                    ChronoType::TimePoint => {
+                        quote! { autocxx::chrono::time_point_from_nanos(#var) }
+                    }
+                };
+                let ty = ty.clone().into();
+                RustParamConversion::Param {
+                    ty,
+                    local_variables: Vec::new(),
+                    conversion,
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromTupleToTupleStruct(ref elements)
+            | RustConversionType::FromTupleStructToTuple(ref elements) => {
+                RustParamConversion::Param {
+                    ty: parse_quote! { ( #(#elements,)* ) },
+                    local_variables: Vec::new(),
+                    conversion: quote! { #var.into() },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromOwningPointerToRaw(ref ty) => RustParamConversion::Param {
+                ty: ty.clone().into(),
+                local_variables: Vec::new(),
+                conversion: quote! { <#ty>::into_raw(#var) },
+                conversion_requires_unsafe: false,
+            },
+            RustConversionType::FromRawToOwningPointer(ref ty) => RustParamConversion::Param {
+                ty: ty.clone().into(),
+                local_variables: Vec::new(),
+                conversion: quote! { <#ty>::from_raw(#var) },
+                conversion_requires_unsafe: true,
+            },
         }
     }
 }
@@ -6,25 +6,28 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod enum_newtype;
 mod fun_codegen;
 mod function_wrapper_rs;
 mod impl_item_creator;
 mod lifetime;
 mod namespace_organizer;
 mod non_pod_struct;
+mod panic_guard;
 pub(crate) mod unqualify;
 
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
-use autocxx_parser::{ExternCppType, IncludeCppConfig, RustFun, UnsafePolicy};
+use autocxx_parser::{ExternCppType, IncludeCppConfig, RustFun, RustPath, RustTrait, UnsafePolicy};
 
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
 use syn::{
-    parse_quote, punctuated::Punctuated, token::Comma, Attribute, Expr, FnArg, ForeignItem,
-    ForeignItemFn, Ident, ImplItem, Item, ItemForeignMod, ItemMod, Lifetime, TraitItem, Type,
-    TypePath,
+    ext::IdentExt, parse_quote, punctuated::Punctuated, token::Comma, visit_mut::VisitMut,
+    Attribute, Expr, ExprLit, Fields, FnArg, ForeignItem, ForeignItemFn, Ident, ImplItem, Item,
+    ItemForeignMod, ItemMod, ItemStruct, ItemUnion, Lifetime, Lit, ReturnType, Token, TraitItem,
+    Type, TypeArray, TypePath, TypeReference,
 };
 
 use crate::{
@@ -32,24 +35,29 @@ use crate::{
         non_pod_struct::{make_non_pod, new_non_pod_struct},
         unqualify::{unqualify_params, unqualify_ret_type},
     },
+    known_types::AssociativeContainerType,
     minisyn::minisynize_punctuated,
     types::{make_ident, Namespace, QualifiedName},
 };
 use impl_item_creator::create_impl_items;
 
 use self::{
+    enum_newtype::{make_bitflags, make_non_exhaustive_enum},
     fun_codegen::gen_function,
     namespace_organizer::{HasNs, NamespaceEntries},
+    panic_guard::{BridgeTypeQualifier, PanicGuard},
 };
 
 use super::{
     analysis::{
+        element_kinds::{find_element_kinds, shim_name, ElementKind},
         fun::{FnPhase, PodAndDepAnalysis, ReceiverMutability},
         pod::PodAnalysis,
     },
-    api::{AnalysisPhase, Api, SubclassName, TypeKind, TypedefKind},
+    api::{AnalysisPhase, AnonymousUnion, Api, Bitfield, SubclassName, TypeKind, TypedefKind},
     convert_error::ErrorContextType,
     doc_attr::get_doc_attrs,
+    type_helpers::{extract_manually_drop_inner_type, extract_option_box_inner_type},
 };
 use super::{
     api::{Layout, Provenance, RustSubclassFnDetails, SuperclassMethod, TraitImplSignature},
@@ -57,7 +65,7 @@ use super::{
     codegen_cpp::type_to_cpp::CppNameMap,
 };
 use super::{convert_error::ErrorContext, ConvertErrorFromCpp};
-use quote::quote;
+use quote::{quote, ToTokens};
 
 #[derive(Clone, Hash, PartialEq, Eq)]
 struct ImplBlockKey {
@@ -92,6 +100,18 @@ enum Use {
     Custom(Box<Item>),
 }
 
+/// Refers to a Rust item from within the mod generated by `include_cpp!`.
+/// Items discovered by scanning the crate have full paths, starting with
+/// `crate`, whereas those named in directives are relative to the mod
+/// containing `include_cpp!`.
+fn rust_item_path(path: &RustPath) -> TokenStream {
+    if path.iter().next().is_some_and(|id| id == "crate") {
+        path.to_token_stream()
+    } else {
+        quote! { super::#path }
+    }
+}
+
 fn get_string_items() -> Vec<Item> {
     [
         Item::Trait(parse_quote! {
@@ -155,7 +175,7 @@ impl<'a> RsCodeGenerator<'a> {
         bindgen_mod: ItemMod,
         config: &'a IncludeCppConfig,
         header_name: Option<String>,
-    ) -> Vec<Item> {
+    ) -> Result<Vec<Item>, ConvertErrorFromCpp> {
         let c = Self {
             unsafe_policy,
             include_list,
@@ -167,7 +187,7 @@ impl<'a> RsCodeGenerator<'a> {
         c.rs_codegen(all_apis)
     }
 
-    fn rs_codegen(mut self, all_apis: ApiVec<FnPhase>) -> Vec<Item> {
+    fn rs_codegen(mut self, all_apis: ApiVec<FnPhase>) -> Result<Vec<Item>, ConvertErrorFromCpp> {
         // ... and now let's start to generate the output code.
         // First off, when we generate structs we may need to add some methods
         // if they're superclasses.
@@ -175,6 +195,8 @@ impl<'a> RsCodeGenerator<'a> {
         let subclasses_with_a_single_trivial_constructor =
             find_trivially_constructed_subclasses(&all_apis);
         let non_pod_types = find_non_pod_types(&all_apis);
+        let pod_traits = find_pod_traits(&all_apis, self.config)?;
+        let element_kinds = find_element_kinds(&all_apis);
         // Now let's generate the Rust code.
         let (rs_codegen_results_and_namespaces, additional_cpp_needs): (Vec<_>, Vec<_>) = all_apis
             .into_iter()
@@ -186,6 +208,8 @@ impl<'a> RsCodeGenerator<'a> {
                     &methods_by_superclass,
                     &subclasses_with_a_single_trivial_constructor,
                     &non_pod_types,
+                    &pod_traits,
+                    &element_kinds,
                 );
                 ((name, gen), more_cpp_needed)
             })
@@ -267,7 +291,7 @@ impl<'a> RsCodeGenerator<'a> {
             use bindgen::root;
         }));
         all_items.append(&mut use_statements);
-        all_items
+        Ok(all_items)
     }
 
     fn accumulate_superclass_methods(
@@ -470,6 +494,8 @@ impl<'a> RsCodeGenerator<'a> {
         associated_methods: &HashMap<QualifiedName, Vec<SuperclassMethod>>,
         subclasses_with_a_single_trivial_constructor: &HashSet<QualifiedName>,
         non_pod_types: &HashSet<QualifiedName>,
+        pod_traits: &PodTraits,
+        element_kinds: &HashMap<QualifiedName, Vec<ElementKind>>,
     ) -> RsCodegenResult {
         let name = api.name().clone();
         let id = name.get_final_ident();
@@ -510,7 +536,7 @@ impl<'a> RsCodeGenerator<'a> {
                 ..Default::default()
             },
             Api::Struct {
-                details,
+                mut details,
                 analysis:
                     PodAndDepAnalysis {
                         pod:
@@ -524,43 +550,167 @@ impl<'a> RsCodeGenerator<'a> {
             } => {
                 let doc_attrs = get_doc_attrs(&details.item.attrs);
                 let layout = details.layout.clone();
-                self.generate_type(
+                let bindgen_ident = details.item.ident.clone();
+                let bitfields = std::mem::take(&mut details.bitfields);
+                let anonymous_unions = std::mem::take(&mut details.anonymous_unions);
+                let is_pod = matches!(kind, TypeKind::Pod);
+                let debug_impl = if pod_traits.debug.contains(&name) {
+                    Some(generate_pod_debug_impl(&details.item, &pod_traits.debug))
+                } else {
+                    None
+                };
+                if pod_traits.partial_eq.contains(&name) {
+                    details
+                        .item
+                        .attrs
+                        .push(parse_quote! { #[derive(PartialEq)] });
+                }
+                if pod_traits.serde.contains(&name) {
+                    details.item.attrs.extend(serde_derive_attrs());
+                    add_serde_field_attrs(&mut details.item);
+                }
+                let item = if details.is_union && is_pod {
+                    Item::Union(struct_to_union(details.item.into()))
+                } else {
+                    Item::Struct(details.item.into())
+                };
+                let mut result = self.generate_type(
                     &name,
                     id,
                     kind,
                     constructors.move_constructor,
                     constructors.destructor,
-                    || Some((Item::Struct(details.item.into()), doc_attrs)),
+                    || Some((item, doc_attrs)),
                     associated_methods,
                     layout,
                     is_generic,
-                )
+                );
+                result.bindgen_mod_items.extend(debug_impl);
+                if is_pod && !is_generic {
+                    if !bitfields.is_empty() {
+                        result
+                            .bindgen_mod_items
+                            .push(generate_bitfield_accessors(&bindgen_ident, &bitfields));
+                    }
+                    if !anonymous_unions.is_empty() {
+                        result
+                            .bindgen_mod_items
+                            .push(generate_anonymous_union_accessors(
+                                &bindgen_ident,
+                                &anonymous_unions,
+                            ));
+                        result.bindgen_mod_items.extend(
+                            anonymous_unions
+                                .into_iter()
+                                .map(|au| Item::Union(struct_to_union(au.item.into()))),
+                        );
+                    }
+                }
+                result
             }
             Api::Enum { item, .. } => {
                 let doc_attrs = get_doc_attrs(&item.attrs);
-                self.generate_type(
+                let cpp_name = self.original_name_map.map(&name);
+                let (mut item, extra_items) = if self.config.is_bitflags(&cpp_name) {
+                    make_bitflags(item.into())
+                } else if self.config.is_non_exhaustive_enum(&cpp_name) {
+                    make_non_exhaustive_enum(item.into())
+                } else {
+                    let mut item: syn::ItemEnum = item.into();
+                    if !derives_trait(&item.attrs, "Debug") {
+                        item.attrs.push(parse_quote! { #[derive(Debug)] });
+                    }
+                    (Item::Enum(item), Vec::new())
+                };
+                if pod_traits.serde.contains(&name) {
+                    match &mut item {
+                        Item::Enum(e) => e.attrs.extend(serde_derive_attrs()),
+                        Item::Struct(s) => s.attrs.extend(serde_derive_attrs()),
+                        _ => {}
+                    }
+                }
+                let mut result = self.generate_type(
                     &name,
                     id,
                     TypeKind::Pod,
                     true,
                     true,
-                    || Some((Item::Enum(item.into()), doc_attrs)),
+                    || Some((item, doc_attrs)),
                     associated_methods,
                     None,
                     false,
-                )
+                );
+                result.bindgen_mod_items.extend(extra_items);
+                result
             }
-            Api::ConcreteType { .. } => self.generate_type(
-                &name,
-                id,
-                TypeKind::Abstract,
-                false, // assume for now that these types can't be kept in a Vector
-                true,  // assume for now that these types can be put in a smart pointer
-                || None,
-                associated_methods,
-                None,
-                false,
-            ),
+            Api::Tuple { elements, .. } => {
+                let (item, conversions) = generate_tuple_struct(&id, &elements);
+                let mut result = self.generate_type(
+                    &name,
+                    id,
+                    TypeKind::Pod,
+                    true,
+                    true,
+                    || Some((Item::Struct(item), Vec::new())),
+                    associated_methods,
+                    None,
+                    false,
+                );
+                result.bindgen_mod_items.extend(conversions);
+                result
+            }
+            Api::Variant { alternatives, .. } => {
+                let mut result = self.generate_concrete_type(&name, id.clone(), associated_methods);
+                if let Some(kinds) = element_kinds.get(&name) {
+                    let (enum_id, extern_c_mod_items, bindgen_mod_items) =
+                        generate_variant_enum(&name, &id, &alternatives, kinds);
+                    result.extern_c_mod_items.extend(extern_c_mod_items);
+                    result.bindgen_mod_items.extend(bindgen_mod_items);
+                    result
+                        .materializations
+                        .push(Use::SpecificNameFromBindgen(enum_id.into()));
+                }
+                result
+            }
+            Api::AssociativeContainer {
+                container_type,
+                elements,
+                ..
+            } => {
+                let mut result = self.generate_concrete_type(&name, id.clone(), associated_methods);
+                if let Some(kinds) = element_kinds.get(&name) {
+                    let (extern_c_mod_items, bindgen_mod_items) =
+                        generate_associative_container_impls(
+                            &name,
+                            &id,
+                            container_type,
+                            &elements,
+                            kinds,
+                        );
+                    result.extern_c_mod_items.extend(extern_c_mod_items);
+                    result.bindgen_mod_items.extend(bindgen_mod_items);
+                }
+                result
+            }
+            Api::SmartPointer { pointee, .. } => {
+                let mut result = self.generate_concrete_type(&name, id, associated_methods);
+                let (extern_c_mod_items, bindgen_mod_items) =
+                    generate_ref_counted_impl(&name, &pointee);
+                result.extern_c_mod_items.extend(extern_c_mod_items);
+                result.bindgen_mod_items.extend(bindgen_mod_items);
+                result
+            }
+            Api::UniquePtrWithDeleter {
+                pointee, deleter, ..
+            } => {
+                let mut result = self.generate_concrete_type(&name, id, associated_methods);
+                let (extern_c_mod_items, bindgen_mod_items) =
+                    generate_deleter_impl(&name, &pointee, &deleter);
+                result.extern_c_mod_items.extend(extern_c_mod_items);
+                result.bindgen_mod_items.extend(bindgen_mod_items);
+                result
+            }
+            Api::ConcreteType { .. } => self.generate_concrete_type(&name, id, associated_methods),
             Api::ForwardDeclaration { .. } | Api::OpaqueTypedef { .. } => self.generate_type(
                 &name,
                 id,
@@ -578,11 +728,73 @@ impl<'a> RsCodeGenerator<'a> {
                 })],
                 ..Default::default()
             },
+            Api::ContainerElement { element, .. } => {
+                let elem_ty = element.to_type_path();
+                let elem_name = element.to_cpp_name();
+                let doc =
+                    format!("A `{elem_name}` which can be held in a `CxxVector` or smart pointer.");
+                // There's no way to ask for std::vector<bool>, which is special.
+                let movable = elem_name != "bool";
+                let mut result = self.generate_type(
+                    &name,
+                    id.clone(),
+                    TypeKind::Pod,
+                    movable,
+                    true,
+                    || {
+                        Some((
+                            parse_quote! {
+                                #[doc = #doc]
+                                #[repr(transparent)]
+                                #[derive(Clone, Copy, Debug, PartialEq)]
+                                pub struct #id(pub #elem_ty);
+                            },
+                            Vec::new(),
+                        ))
+                    },
+                    associated_methods,
+                    None,
+                    false,
+                );
+                let path = name.get_bindgen_path_idents();
+                result.global_items.extend([
+                    parse_quote! {
+                        impl From<#elem_ty> for #(#path)::* {
+                            fn from(val: #elem_ty) -> Self {
+                                Self(val)
+                            }
+                        }
+                    },
+                    parse_quote! {
+                        impl From<#(#path)::*> for #elem_ty {
+                            fn from(val: #(#path)::*) -> Self {
+                                val.0
+                            }
+                        }
+                    },
+                    parse_quote! {
+                        impl PartialEq<#elem_ty> for #(#path)::* {
+                            fn eq(&self, other: &#elem_ty) -> bool {
+                                self.0 == *other
+                            }
+                        }
+                    },
+                    parse_quote! {
+                        impl PartialEq<#(#path)::*> for #elem_ty {
+                            fn eq(&self, other: &#(#path)::*) -> bool {
+                                *self == other.0
+                            }
+                        }
+                    },
+                ]);
+                result
+            }
             Api::RustType { path, .. } => {
                 let id = path.get_final_ident();
+                let path = rust_item_path(&path);
                 RsCodegenResult {
                     global_items: vec![parse_quote! {
-                        use super::#path;
+                        use #path;
                     }],
                     extern_rust_mod_items: vec![parse_quote! {
                         type #id;
@@ -594,35 +806,11 @@ impl<'a> RsCodeGenerator<'a> {
                     ..Default::default()
                 }
             }
-            Api::RustFn {
-                details:
-                    RustFun {
-                        path,
-                        mut sig,
-                        has_receiver,
-                        ..
-                    },
-                ..
-            } => {
-                sig.inputs = unqualify_params(sig.inputs);
-                sig.output = unqualify_ret_type(sig.output);
-                RsCodegenResult {
-                    global_items: if !has_receiver {
-                        vec![parse_quote! {
-                            use super::#path;
-                        }]
-                    } else {
-                        Vec::new()
-                    },
-                    extern_rust_mod_items: vec![parse_quote! {
-                        #sig;
-                    }],
-                    ..Default::default()
-                }
-            }
+            Api::RustFn { details, deps, .. } => self.generate_rust_fn(details, &deps, None),
+            Api::RustTrait { details, deps, .. } => self.generate_rust_trait(details, &deps),
             Api::RustSubclassFn {
                 details, subclass, ..
-            } => Self::generate_subclass_fn(id.into(), *details, subclass),
+            } => self.generate_subclass_fn(id.into(), *details, subclass),
             Api::Subclass {
                 name, superclass, ..
             } => {
@@ -803,7 +991,205 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// Generates a function in the `extern "Rust"` section of the bridge.
+    /// C++ doesn't call the user's function directly, but instead calls
+    /// a shim which stops any panic unwinding into C++, and converts any
+    /// types which cxx can't pass directly.
+    /// Generates an `extern_rust_function`. If `trait_path` is given, this
+    /// is instead a method of an `extern_rust_trait`, whose receiver is the
+    /// holder of a `Box<dyn Trait>`.
+    fn generate_rust_fn(
+        &self,
+        details: RustFun,
+        deps: &[QualifiedName],
+        trait_path: Option<&RustPath>,
+    ) -> RsCodegenResult {
+        let RustFun { path, mut sig, .. } = details;
+        sig.inputs = unqualify_params(sig.inputs);
+        sig.output = unqualify_ret_type(sig.output);
+        let id = sig.ident.clone();
+        let cxx_name = id.to_string();
+        let shim_id = make_ident(format!("{id}_autocxx_panic_guard"));
+        // Rename parameters, since they may be patterns rather than
+        // simple identifiers.
+        let mut receiver_ty = None;
+        let mut args: Punctuated<Expr, Comma> = Punctuated::new();
+        let mut requires_unsafe = false;
+        for (counter, input) in sig.inputs.iter_mut().enumerate() {
+            match input {
+                FnArg::Receiver(rec) => receiver_ty = Some(rec.ty.clone()),
+                FnArg::Typed(pt) if matches!(&*pt.pat, syn::Pat::Ident(pi) if pi.ident == "self") => {
+                    receiver_ty = Some(pt.ty.clone())
+                }
+                FnArg::Typed(pt) => {
+                    let arg_name = make_ident(format!("arg{counter}"));
+                    *pt.pat = parse_quote! { #arg_name };
+                    // cxx can't pass an Option<Box<T>>, so C++ instead
+                    // passes ownership using a possibly-null pointer.
+                    if let Some(inner) = extract_option_box_inner_type(&pt.ty) {
+                        *pt.ty = parse_quote! { *mut #inner };
+                        requires_unsafe = true;
+                        args.push(parse_quote! {
+                            (!#arg_name.is_null()).then(|| unsafe { Box::from_raw(#arg_name) })
+                        });
+                    } else {
+                        args.push(parse_quote! { #arg_name });
+                    }
+                }
+            }
+        }
+        let receiver_id = receiver_ty.as_deref().map(Self::receiver_id_from_type);
+        let (mut call, context) = match (&receiver_id, trait_path) {
+            (Some(_), Some(trait_path)) => {
+                let trait_id = trait_path.get_final_ident();
+                let trait_path = rust_item_path(trait_path);
+                let inner = match receiver_ty.as_deref() {
+                    Some(Type::Reference(TypeReference {
+                        mutability: Some(_),
+                        ..
+                    })) => quote! { &mut *self.0 },
+                    _ => quote! { &*self.0 },
+                };
+                (
+                    quote! { #trait_path::#id(#inner, #args) },
+                    format!("Rust trait method {trait_id}::{id} called from C++"),
+                )
+            }
+            (Some(receiver_id), None) => (
+                quote! { #receiver_id :: #id(self, #args) },
+                format!("Rust method {receiver_id}::{id} called from C++"),
+            ),
+            (None, _) => (
+                quote! { #id(#args) },
+                format!("Rust function {id} called from C++"),
+            ),
+        };
+        if let ReturnType::Type(_, ty) = &sig.output {
+            if let Some(inner) = extract_option_box_inner_type(ty) {
+                call = quote! { #call.map_or(::core::ptr::null_mut(), Box::into_raw) };
+                sig.output = parse_quote! { -> *mut #inner };
+            }
+        }
+        let guard = PanicGuard::new(&self.config.panic_policy, context);
+        let body = guard.wrap_body(call, &sig.output);
+        let mut shim_sig = sig.clone();
+        shim_sig.ident = shim_id.clone().into();
+        shim_sig.output = guard.rust_ret_type(&sig.output);
+        // The bridge refers to C++ types by their unqualified names, but
+        // outside the bridge we need to refer to those in namespaces
+        // by their full paths.
+        let namespaced_types: HashMap<_, _> = deps
+            .iter()
+            .filter(|dep| !dep.get_namespace().is_empty())
+            .map(|dep| (dep.get_final_ident().into(), dep.type_path_from_root()))
+            .collect();
+        BridgeTypeQualifier::new(&namespaced_types).visit_signature_mut(&mut shim_sig);
+        let shim: ImplItem = parse_quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #shim_sig {
+                #body
+            }
+        };
+        let global_items = match receiver_id {
+            Some(receiver_id) => vec![parse_quote! {
+                impl #receiver_id {
+                    #shim
+                }
+            }],
+            None => vec![
+                {
+                    let path = rust_item_path(&path);
+                    parse_quote! {
+                        use #path;
+                    }
+                },
+                Item::Verbatim(shim.into_token_stream()),
+            ],
+        };
+        let mut bridge_sig = sig;
+        bridge_sig.ident = shim_id.into();
+        bridge_sig.output = guard.bridge_ret_type(&bridge_sig.output);
+        if requires_unsafe {
+            bridge_sig.unsafety = Some(parse_quote! { unsafe });
+        }
+        RsCodegenResult {
+            global_items,
+            extern_rust_mod_items: vec![parse_quote! {
+                #[cxx_name = #cxx_name]
+                #bridge_sig;
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// Generates a Rust trait exposed to C++. C++ receives implementations
+    /// of the trait as a `Box` of a holder type, which owns a
+    /// `Box<dyn Trait>` and passes each method call through to it.
+    fn generate_rust_trait(&self, details: RustTrait, deps: &[QualifiedName]) -> RsCodegenResult {
+        let holder = details.holder();
+        let holder_doc = format!(
+            "Owns an implementation of the Rust trait `{}` so that it can be passed to C++.",
+            details.path.get_final_ident()
+        );
+        let trait_path = rust_item_path(&details.path);
+        let mut result = RsCodegenResult {
+            global_items: vec![
+                parse_quote! {
+                    #[doc = #holder_doc]
+                    pub struct #holder(pub Box<dyn #trait_path>);
+                },
+                parse_quote! {
+                    impl #holder {
+                        pub fn new(inner: impl #trait_path + 'static) -> Box<Self> {
+                            Box::new(Self(Box::new(inner)))
+                        }
+                    }
+                },
+            ],
+            extern_rust_mod_items: vec![parse_quote! {
+                type #holder;
+            }],
+            bindgen_mod_items: vec![parse_quote! {
+                #[allow(unused_imports)]
+                use super::super::#holder;
+            }],
+            ..Default::default()
+        };
+        for method in details.methods {
+            let method_result = self.generate_rust_fn(method, deps, Some(&details.path));
+            result.global_items.extend(method_result.global_items);
+            result
+                .extern_rust_mod_items
+                .extend(method_result.extern_rust_mod_items);
+        }
+        result
+    }
+
+    /// Finds the type on which an `extern_rust_function` method is
+    /// implemented, given the type of its `self` parameter, which is
+    /// something like `&A`, `&mut A` or `Pin<&mut A>`.
+    fn receiver_id_from_type(ty: &Type) -> Ident {
+        match ty {
+            Type::Reference(tr) => Self::receiver_id_from_type(&tr.elem),
+            Type::Path(tp) => {
+                let seg = tp.path.segments.last().unwrap();
+                match &seg.arguments {
+                    syn::PathArguments::AngleBracketed(ab) if seg.ident == "Pin" => {
+                        match ab.args.first() {
+                            Some(syn::GenericArgument::Type(ty)) => Self::receiver_id_from_type(ty),
+                            _ => seg.ident.clone(),
+                        }
+                    }
+                    _ => seg.ident.clone(),
+                }
+            }
+            _ => panic!("Unexpected receiver type for extern_rust_function method"),
+        }
+    }
+
     fn generate_subclass_fn(
+        &self,
         api_name: Ident,
         details: RustSubclassFnDetails,
         subclass: SubclassName,
@@ -811,15 +1197,23 @@ impl<'a> RsCodeGenerator<'a> {
         let params = details.params;
         let ret = details.ret;
         let unsafe_token = details.requires_unsafe.wrapper_token();
-        let global_def = quote! { #unsafe_token fn #api_name(#params) #ret };
-        let params = unqualify_params(minisynize_punctuated(params));
-        let ret = unqualify_ret_type(ret.into());
         let method_name = details.method_name;
+        let superclass_id = details.superclass.get_final_ident();
+        let guard = PanicGuard::new(
+            &self.config.panic_policy,
+            format!(
+                "Rust subclass API (method {} of subclass {} of superclass {})",
+                method_name, subclass.0.name, superclass_id
+            ),
+        );
+        let global_ret = guard.rust_ret_type(&ret);
+        let global_def = quote! { #unsafe_token fn #api_name(#params) #global_ret };
+        let params = unqualify_params(minisynize_punctuated(params));
+        let bridge_ret = guard.bridge_ret_type(&unqualify_ret_type(ret.clone().into()));
         let cxxbridge_decl: ForeignItemFn =
-            parse_quote! { #unsafe_token fn #api_name(#params) #ret; };
+            parse_quote! { #unsafe_token fn #api_name(#params) #bridge_ret; };
         let args: Punctuated<Expr, Comma> =
             Self::args_from_sig(&cxxbridge_decl.sig.inputs).collect();
-        let superclass_id = details.superclass.get_final_ident();
         let methods_trait = SubclassName::get_methods_trait_name(&details.superclass);
         let methods_trait = methods_trait.to_type_path();
         let (deref_ty, deref_call, borrow, mut_token) = match details.receiver_mutability {
@@ -836,20 +1230,26 @@ impl<'a> RsCodeGenerator<'a> {
         let borrow = make_ident(borrow);
         let destroy_panic_msg = format!("Rust subclass API (method {} of subclass {} of superclass {}) called after subclass destroyed", method_name, subclass.0.name, superclass_id);
         let reentrancy_panic_msg = format!("Rust subclass API (method {} of subclass {} of superclass {}) called whilst subclass already borrowed - likely a re-entrant call",  method_name, subclass.0.name, superclass_id);
+        let body = guard.wrap_body(
+            quote! {
+                let rc = me.0
+                    .get()
+                    .expect(#destroy_panic_msg);
+                let #mut_token b = rc
+                    .as_ref()
+                    .#borrow()
+                    .expect(#reentrancy_panic_msg);
+                let r = ::core::ops::#deref_ty::#deref_call(& #mut_token b);
+                #methods_trait :: #method_name
+                    (r,
+                    #args)
+            },
+            &ret,
+        );
         RsCodegenResult {
             global_items: vec![parse_quote! {
                 #global_def {
-                    let rc = me.0
-                        .get()
-                        .expect(#destroy_panic_msg);
-                    let #mut_token b = rc
-                        .as_ref()
-                        .#borrow()
-                        .expect(#reentrancy_panic_msg);
-                    let r = ::core::ops::#deref_ty::#deref_call(& #mut_token b);
-                    #methods_trait :: #method_name
-                        (r,
-                        #args)
+                    #body
                 }
             }],
             extern_rust_mod_items: vec![ForeignItem::Fn(cxxbridge_decl)],
@@ -867,6 +1267,27 @@ impl<'a> RsCodeGenerator<'a> {
         })
     }
 
+    /// Generate an opaque type for a concrete instantiation of a C++
+    /// template, which may then have extra items added to it.
+    fn generate_concrete_type(
+        &self,
+        name: &QualifiedName,
+        id: crate::minisyn::Ident,
+        associated_methods: &HashMap<QualifiedName, Vec<SuperclassMethod>>,
+    ) -> RsCodegenResult {
+        self.generate_type(
+            name,
+            id,
+            TypeKind::Abstract,
+            false, // assume for now that these types can't be kept in a Vector
+            true,  // assume for now that these types can be put in a smart pointer
+            || None,
+            associated_methods,
+            None,
+            false,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)] // currently the least unclear way
     fn generate_type<F>(
         &self,
@@ -1259,6 +1680,757 @@ fn find_non_pod_types(apis: &ApiVec<FnPhase>) -> HashSet<QualifiedName> {
         .collect()
 }
 
+/// The types for which POD structs can use `Debug` and `PartialEq`
+/// implementations in their own implementations of those traits, and
+/// the types for which we derive serde traits.
+struct PodTraits {
+    /// Non-generic POD structs, for which we implement `Debug`, enums,
+    /// which all derive `Debug`, and typedefs of any of those.
+    debug: HashSet<QualifiedName>,
+    /// The subset of `debug` whose fields can all be compared.
+    partial_eq: HashSet<QualifiedName>,
+    /// POD structs and enums requested using `derive_serde!`, and those
+    /// they refer to.
+    serde: HashSet<QualifiedName>,
+}
+
+/// Work out which POD structs get `Debug` and `PartialEq` implementations.
+/// Every non-generic POD struct gets `Debug`, showing whichever fields
+/// can be shown, but a struct only gets `PartialEq` if every field can be
+/// compared. That depends on the types of the fields, which may in turn
+/// be other POD structs or typedefs, so we repeatedly remove any type which
+/// refers to a type lacking the trait until nothing changes.
+fn find_pod_traits(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> Result<PodTraits, ConvertErrorFromCpp> {
+    let struct_fields: HashMap<QualifiedName, Vec<(String, Type)>> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                name,
+                details,
+                analysis:
+                    PodAndDepAnalysis {
+                        pod:
+                            PodAnalysis {
+                                kind: TypeKind::Pod,
+                                is_generic: false,
+                                ..
+                            },
+                        ..
+                    },
+            } if !details.is_union && !is_packed(&details.item) => Some((
+                name.name.clone(),
+                details
+                    .item
+                    .fields
+                    .iter()
+                    .filter(|f| !f.ident.as_ref().is_some_and(is_padding_field))
+                    .map(|f| (field_name(f), f.ty.clone()))
+                    .collect(),
+            )),
+            _ => None,
+        })
+        .collect();
+    let structs: HashMap<QualifiedName, Vec<Type>> = struct_fields
+        .iter()
+        .map(|(name, fields)| {
+            (
+                name.clone(),
+                fields.iter().map(|(_, ty)| ty.clone()).collect(),
+            )
+        })
+        .collect();
+    let typedefs: HashMap<QualifiedName, Vec<Type>> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Typedef {
+                name,
+                item: TypedefKind::Type(ity),
+                ..
+            } => Some((name.name.clone(), vec![(*ity.ty).clone()])),
+            Api::Typedef {
+                name,
+                item: TypedefKind::Use(_, ty),
+                ..
+            } => Some((name.name.clone(), vec![ty.0.clone()])),
+            _ => None,
+        })
+        .collect();
+    let all_types: HashSet<_> = structs
+        .keys()
+        .chain(typedefs.keys())
+        .cloned()
+        .chain(apis.iter().filter_map(|api| match api {
+            Api::Enum { name, .. } => Some(name.name.clone()),
+            _ => None,
+        }))
+        .collect();
+    // We implement Debug for all POD structs, whatever their fields.
+    let debug = remove_types_lacking_trait(all_types.clone(), &typedefs);
+    let referenced: HashMap<_, _> = structs.into_iter().chain(typedefs).collect();
+    let serde = find_serde_types(config, &all_types, &referenced, &struct_fields)?;
+    let partial_eq = remove_types_lacking_trait(all_types, &referenced);
+    Ok(PodTraits {
+        debug,
+        partial_eq,
+        serde,
+    })
+}
+
+/// Find the types requested using `derive_serde!`, plus any types to which
+/// their fields refer, recursing through typedefs and arrays. A derive on
+/// the outer type would fail to compile without derives on these too, so
+/// we report an error if any of those fields can't be serialized.
+fn find_serde_types(
+    config: &IncludeCppConfig,
+    all_types: &HashSet<QualifiedName>,
+    referenced: &HashMap<QualifiedName, Vec<Type>>,
+    struct_fields: &HashMap<QualifiedName, Vec<(String, Type)>>,
+) -> Result<HashSet<QualifiedName>, ConvertErrorFromCpp> {
+    let mut to_visit = config
+        .get_serde_types()
+        .iter()
+        .map(|cpp_name| QualifiedName::new_from_cpp_name(cpp_name))
+        .collect_vec();
+    let mut found = HashSet::new();
+    while let Some(name) = to_visit.pop() {
+        if !all_types.contains(&name) || found.contains(&name) {
+            continue;
+        }
+        if let Some(fields) = struct_fields.get(&name) {
+            for (field_name, ty) in fields {
+                if !is_serializable_field_type(ty, all_types, true) {
+                    return Err(ConvertErrorFromCpp::UnserializableField(
+                        name,
+                        field_name.clone(),
+                    ));
+                }
+            }
+        }
+        if let Some(field_types) = referenced.get(&name) {
+            to_visit.extend(field_types.iter().filter_map(type_name_within_arrays));
+        }
+        found.insert(name);
+    }
+    Ok(found)
+}
+
+/// The name of a named type, or of the element type of a (possibly
+/// nested) array of such a type.
+fn type_name_within_arrays(ty: &Type) -> Option<QualifiedName> {
+    match ty {
+        Type::Array(arr) => type_name_within_arrays(&arr.elem),
+        Type::Path(typ) if typ.qself.is_none() => Some(QualifiedName::from_type_path(typ)),
+        _ => None,
+    }
+}
+
+/// Whether serde can handle a field of the given type, once we've derived
+/// serde traits for all the `candidates`. serde only supports arrays of up
+/// to 32 elements, but we handle longer arrays using
+/// `autocxx::serde_support::array`, so long as they're the outermost type
+/// of the field.
+fn is_serializable_field_type(
+    ty: &Type,
+    candidates: &HashSet<QualifiedName>,
+    outermost: bool,
+) -> bool {
+    match ty {
+        Type::Array(arr) => {
+            (outermost || serde_supports_array(arr))
+                && is_serializable_field_type(&arr.elem, candidates, false)
+        }
+        Type::Ptr(_) => false,
+        _ => field_type_implements(ty, candidates),
+    }
+}
+
+/// Whether serde's own implementations handle an array of this length.
+fn serde_supports_array(arr: &TypeArray) -> bool {
+    match &arr.len {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(len), ..
+        }) => len.base10_parse::<usize>().is_ok_and(|len| len <= 32),
+        _ => false,
+    }
+}
+
+/// Fields which bindgen adds to make up a struct's layout, rather than
+/// fields from the original C++.
+fn is_padding_field(ident: &Ident) -> bool {
+    ident.to_string().starts_with("__bindgen_padding")
+}
+
+fn field_name(field: &syn::Field) -> String {
+    field
+        .ident
+        .as_ref()
+        .map(|ident| ident.unraw().to_string())
+        .unwrap_or_default()
+}
+
+/// Add serde attributes to the fields of a struct for which we derive serde
+/// traits: padding isn't serialized, and arrays too long for serde itself
+/// use our own implementation.
+fn add_serde_field_attrs(item: &mut ItemStruct) {
+    for field in item.fields.iter_mut() {
+        if field.ident.as_ref().is_some_and(is_padding_field) {
+            field.attrs.push(parse_quote! {
+                #[serde(skip, default = "autocxx::serde_support::zeroed_padding")]
+            });
+        } else if matches!(&field.ty, Type::Array(arr) if !serde_supports_array(arr)) {
+            field.attrs.push(parse_quote! {
+                #[serde(with = "autocxx::serde_support::array")]
+            });
+        }
+    }
+}
+
+/// Attributes deriving serde traits, using serde as re-exported by
+/// `autocxx` so that the user needn't depend on it directly.
+fn serde_derive_attrs() -> [Attribute; 2] {
+    [
+        parse_quote! { #[derive(autocxx::serde::Serialize, autocxx::serde::Deserialize)] },
+        parse_quote! { #[serde(crate = "autocxx::serde")] },
+    ]
+}
+
+/// Starting from an optimistic set of types implementing some trait,
+/// remove any of the `candidates` which refer to types not in the set,
+/// until none remain.
+fn remove_types_lacking_trait(
+    mut types_implementing: HashSet<QualifiedName>,
+    candidates: &HashMap<QualifiedName, Vec<Type>>,
+) -> HashSet<QualifiedName> {
+    loop {
+        let lacking = candidates
+            .iter()
+            .filter(|(name, referenced)| {
+                types_implementing.contains(*name)
+                    && !referenced
+                        .iter()
+                        .all(|ty| field_type_implements(ty, &types_implementing))
+            })
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+        if lacking.is_empty() {
+            return types_implementing;
+        }
+        for name in lacking {
+            types_implementing.swap_remove(&name);
+        }
+    }
+}
+
+/// Whether a field of a POD struct, as emitted by bindgen, implements
+/// some standard trait which all primitives, pointers and arrays of such
+/// types implement, and which is implemented by the given C++ types.
+fn field_type_implements(ty: &Type, types_implementing: &HashSet<QualifiedName>) -> bool {
+    match ty {
+        Type::Ptr(_) => true,
+        Type::Array(arr) => field_type_implements(&arr.elem, types_implementing),
+        Type::Path(typ) if typ.qself.is_none() => {
+            let segments = typ.path.segments.iter().map(|seg| seg.ident.to_string());
+            match segments.collect_vec().as_slice() {
+                [] => false,
+                [primitive] => matches!(
+                    primitive.as_str(),
+                    "bool"
+                        | "u8"
+                        | "u16"
+                        | "u32"
+                        | "u64"
+                        | "u128"
+                        | "usize"
+                        | "i8"
+                        | "i16"
+                        | "i32"
+                        | "i64"
+                        | "i128"
+                        | "isize"
+                        | "f32"
+                        | "f64"
+                ),
+                [std, os, raw, _] if std == "std" && os == "os" && raw == "raw" => true,
+                _ => types_implementing.contains(&QualifiedName::from_type_path(typ)),
+            }
+        }
+        _ => false,
+    }
+}
+
+fn is_packed(item: &ItemStruct) -> bool {
+    item.attrs.iter().any(|attr| {
+        attr.path().is_ident("repr") && attr.to_token_stream().to_string().contains("packed")
+    })
+}
+
+fn derives_trait(attrs: &[Attribute], trait_name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .is_ok_and(|paths| paths.iter().any(|p| p.is_ident(trait_name)))
+    })
+}
+
+/// Generate a `Debug` implementation for a POD struct, showing each
+/// field whose type implements `Debug`. We can't just derive `Debug`,
+/// since some fields may be of types which don't implement it.
+fn generate_pod_debug_impl(item: &ItemStruct, debug_types: &HashSet<QualifiedName>) -> Item {
+    let id = &item.ident;
+    let name = id.unraw().to_string();
+    let mut all_fields_shown = true;
+    let fields = item
+        .fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(|ident| (ident, &f.ty)))
+        .filter(|(ident, _)| !is_padding_field(ident))
+        .filter(|(_, ty)| {
+            let shown = field_type_implements(ty, debug_types);
+            all_fields_shown &= shown;
+            shown
+        })
+        .map(|(ident, _)| {
+            let field_name = ident.unraw().to_string();
+            quote! { .field(#field_name, &self.#ident) }
+        })
+        .collect_vec();
+    let finish = if all_fields_shown {
+        quote! { finish }
+    } else {
+        quote! { finish_non_exhaustive }
+    };
+    parse_quote! {
+        impl ::core::fmt::Debug for #id {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#name)
+                    #(#fields)*
+                    .#finish()
+            }
+        }
+    }
+}
+
+/// Generate a getter and setter for each bitfield in a POD struct.
+/// These mirror the accessors which bindgen would generate, but
+/// operate on the plain byte arrays which we use for bitfield storage.
+fn generate_bitfield_accessors(id: &Ident, bitfields: &[Bitfield]) -> Item {
+    let accessors = bitfields.iter().map(|bitfield| {
+        let Bitfield {
+            getter,
+            setter,
+            ty,
+            int_ty,
+            storage,
+            bit_offset,
+            bit_width,
+        } = bitfield;
+        quote! {
+            #[inline]
+            pub fn #getter(&self) -> #ty {
+                unsafe {
+                    ::core::mem::transmute(
+                        autocxx::bitfield::get(&self.#storage, #bit_offset, #bit_width) as #int_ty
+                    )
+                }
+            }
+            #[inline]
+            pub fn #setter(&mut self, val: #ty) {
+                unsafe {
+                    let val: #int_ty = ::core::mem::transmute(val);
+                    autocxx::bitfield::set(&mut self.#storage, #bit_offset, #bit_width, val as u64)
+                }
+            }
+        }
+    });
+    // As in bindgen's accessors, some of these transmutes are between
+    // identical types.
+    Item::Impl(parse_quote! {
+        #[allow(unknown_lints, unnecessary_transmutes, clippy::useless_transmute)]
+        impl #id {
+            #(#accessors)*
+        }
+    })
+}
+
+/// Generate a struct to stand in for a `std::pair` or `std::tuple`, with
+/// one field per element, plus conversions to and from the equivalent
+/// Rust tuple.
+fn generate_tuple_struct(id: &Ident, elements: &[crate::minisyn::Type]) -> (ItemStruct, Vec<Item>) {
+    let field_names: Vec<_> = (0..elements.len())
+        .map(|i| make_ident(format!("f{i}")))
+        .collect();
+    let indices = (0..elements.len()).map(syn::Index::from);
+    let item = parse_quote! {
+        #[repr(C)]
+        pub struct #id {
+            #(pub #field_names: #elements,)*
+        }
+    };
+    let tuple_type: Type = parse_quote! { ( #(#elements,)* ) };
+    let conversions = vec![
+        parse_quote! {
+            impl From<#tuple_type> for #id {
+                fn from(tuple: #tuple_type) -> Self {
+                    Self {
+                        #(#field_names: tuple.#indices,)*
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl From<#id> for #tuple_type {
+                fn from(value: #id) -> Self {
+                    ( #(value.#field_names,)* )
+                }
+            }
+        },
+    ];
+    (item, conversions)
+}
+
+/// Generate a Rust enum corresponding to a `std::variant`, along with
+/// declarations of the C++ functions we use to convert between the two.
+fn generate_variant_enum(
+    name: &QualifiedName,
+    id: &Ident,
+    alternatives: &[crate::minisyn::Type],
+    kinds: &[ElementKind],
+) -> (crate::minisyn::Ident, Vec<ForeignItem>, Vec<Item>) {
+    let enum_id = make_ident(format!("{id}Enum"));
+    let alternative_ids: Vec<_> = alternatives
+        .iter()
+        .filter_map(|ty| match &**ty {
+            Type::Path(typ) => typ.path.segments.last().map(|seg| seg.ident.clone().into()),
+            _ => None,
+        })
+        .collect();
+    let variant_ids: Vec<_> = if alternative_ids.iter().all_unique() {
+        alternative_ids.clone()
+    } else {
+        (0..alternatives.len())
+            .map(|i| make_ident(format!("Alternative{i}")))
+            .collect()
+    };
+    let index_fn = make_ident(shim_name(name, "index"));
+    let get_fns: Vec<_> = (0..alternatives.len())
+        .map(|i| make_ident(shim_name(name, &format!("get_{i}"))))
+        .collect();
+    let make_fns: Vec<_> = (0..alternatives.len())
+        .map(|i| make_ident(shim_name(name, &format!("make_{i}"))))
+        .collect();
+    let indices = (0..alternatives.len()).map(proc_macro2::Literal::usize_unsuffixed);
+    let (held_types, bridge_types): (Vec<Type>, Vec<Type>) = alternatives
+        .iter()
+        .zip(alternative_ids.iter())
+        .zip(kinds.iter())
+        .map(|((ty, alternative_id), kind)| match kind {
+            ElementKind::ByValue => (ty.0.clone(), parse_quote! { #alternative_id }),
+            ElementKind::UniquePtr => (
+                parse_quote! { cxx::UniquePtr<#ty> },
+                parse_quote! { UniquePtr<#alternative_id> },
+            ),
+        })
+        .unzip();
+    let mut extern_c_mod_items = vec![parse_quote! {
+        fn #index_fn(variant: &#id) -> usize;
+    }];
+    for ((get_fn, make_fn), bridge_type) in get_fns.iter().zip(make_fns.iter()).zip(bridge_types) {
+        extern_c_mod_items.push(parse_quote! {
+            fn #get_fn(variant: UniquePtr<#id>) -> #bridge_type;
+        });
+        extern_c_mod_items.push(parse_quote! {
+            fn #make_fn(alternative: #bridge_type) -> UniquePtr<#id>;
+        });
+    }
+    let doc = format!("A Rust equivalent of [`{id}`], which is a `std::variant`.");
+    let bindgen_mod_items = vec![
+        parse_quote! {
+            #[doc = #doc]
+            #[allow(non_camel_case_types)]
+            pub enum #enum_id {
+                #(#variant_ids(#held_types),)*
+            }
+        },
+        parse_quote! {
+            impl TryFrom<cxx::UniquePtr<#id>> for #enum_id {
+                type Error = autocxx::VariantConversionError;
+
+                fn try_from(variant: cxx::UniquePtr<#id>) -> Result<Self, Self::Error> {
+                    let index = cxxbridge::#index_fn(
+                        variant.as_ref().ok_or(autocxx::VariantConversionError::Null)?,
+                    );
+                    match index {
+                        #(#indices => Ok(Self::#variant_ids(cxxbridge::#get_fns(variant))),)*
+                        _ => Err(autocxx::VariantConversionError::ValuelessByException),
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl From<#enum_id> for cxx::UniquePtr<#id> {
+                fn from(value: #enum_id) -> Self {
+                    match value {
+                        #(#enum_id::#variant_ids(alternative) => cxxbridge::#make_fns(alternative),)*
+                    }
+                }
+            }
+        },
+    ];
+    (enum_id, extern_c_mod_items, bindgen_mod_items)
+}
+
+/// Generate an implementation of `autocxx::CxxMap` or `autocxx::CxxSet` for
+/// a `std::map`, `std::unordered_map` or `std::set`, along with declarations
+/// of the C++ functions it calls.
+fn generate_associative_container_impls(
+    name: &QualifiedName,
+    id: &Ident,
+    container_type: AssociativeContainerType,
+    elements: &[crate::minisyn::Type],
+    kinds: &[ElementKind],
+) -> (Vec<ForeignItem>, Vec<Item>) {
+    let shim = |purpose| make_ident(shim_name(name, purpose));
+    let (len_fn, insert_fn, remove_fn, first_fn, next_fn) = (
+        shim("len"),
+        shim("insert"),
+        shim("remove"),
+        shim("first"),
+        shim("next"),
+    );
+    let bridge_ids: Vec<_> = elements
+        .iter()
+        .filter_map(|ty| match &**ty {
+            Type::Path(typ) => typ.path.segments.last().map(|seg| seg.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let (owned_types, bridge_owned_types): (Vec<Type>, Vec<Type>) = elements
+        .iter()
+        .zip(bridge_ids.iter())
+        .zip(kinds.iter())
+        .map(|((ty, bridge_id), kind)| match kind {
+            ElementKind::ByValue => (ty.0.clone(), parse_quote! { #bridge_id }),
+            ElementKind::UniquePtr => (
+                parse_quote! { cxx::UniquePtr<#ty> },
+                parse_quote! { UniquePtr<#bridge_id> },
+            ),
+        })
+        .unzip();
+    let key = &elements[0];
+    let (owned_key, key_id, bridge_owned_key) =
+        (&owned_types[0], &bridge_ids[0], &bridge_owned_types[0]);
+    let mut extern_c_mod_items: Vec<ForeignItem> = vec![
+        parse_quote! {
+            fn #len_fn(container: &#id) -> usize;
+        },
+        parse_quote! {
+            fn #remove_fn(container: Pin<&mut #id>, key: &#key_id) -> bool;
+        },
+        parse_quote! {
+            unsafe fn #first_fn(container: &#id) -> *const #key_id;
+        },
+        parse_quote! {
+            unsafe fn #next_fn(container: &#id, key: &#key_id) -> *const #key_id;
+        },
+    ];
+    let common_methods: Vec<ImplItem> = vec![
+        parse_quote! {
+            fn len(&self) -> usize {
+                cxxbridge::#len_fn(self)
+            }
+        },
+        parse_quote! {
+            fn remove(self: ::std::pin::Pin<&mut Self>, key: &#key) -> bool {
+                cxxbridge::#remove_fn(self, key)
+            }
+        },
+        parse_quote! {
+            fn first_key(&self) -> Option<&#key> {
+                unsafe { cxxbridge::#first_fn(self).as_ref() }
+            }
+        },
+        parse_quote! {
+            fn next_key(&self, key: &#key) -> Option<&#key> {
+                unsafe { cxxbridge::#next_fn(self, key).as_ref() }
+            }
+        },
+    ];
+    let mut bindgen_mod_items: Vec<Item> = Vec::new();
+    if container_type.has_values() {
+        let get_fn = shim("get");
+        let value = &elements[1];
+        let (owned_value, value_id, bridge_owned_value) =
+            (&owned_types[1], &bridge_ids[1], &bridge_owned_types[1]);
+        extern_c_mod_items.push(parse_quote! {
+            unsafe fn #get_fn(container: &#id, key: &#key_id) -> *const #value_id;
+        });
+        extern_c_mod_items.push(parse_quote! {
+            fn #insert_fn(container: Pin<&mut #id>, key: #bridge_owned_key, value: #bridge_owned_value) -> bool;
+        });
+        bindgen_mod_items.push(parse_quote! {
+            impl autocxx::CxxMap<#key, #value> for #id {
+                type OwnedKey = #owned_key;
+                type OwnedValue = #owned_value;
+                fn get(&self, key: &#key) -> Option<&#value> {
+                    unsafe { cxxbridge::#get_fn(self, key).as_ref() }
+                }
+                fn insert(self: ::std::pin::Pin<&mut Self>, key: #owned_key, value: #owned_value) -> bool {
+                    cxxbridge::#insert_fn(self, key, value)
+                }
+                #(#common_methods)*
+            }
+        });
+        if matches!(container_type, AssociativeContainerType::UnorderedMap) {
+            bindgen_mod_items.push(parse_quote! {
+                impl autocxx::CxxUnorderedMap<#key, #value> for #id {}
+            });
+        }
+    } else {
+        let contains_fn = shim("contains");
+        extern_c_mod_items.push(parse_quote! {
+            fn #contains_fn(container: &#id, key: &#key_id) -> bool;
+        });
+        extern_c_mod_items.push(parse_quote! {
+            fn #insert_fn(container: Pin<&mut #id>, key: #bridge_owned_key) -> bool;
+        });
+        bindgen_mod_items.push(parse_quote! {
+            impl autocxx::CxxSet<#key> for #id {
+                type OwnedKey = #owned_key;
+                fn contains(&self, key: &#key) -> bool {
+                    cxxbridge::#contains_fn(self, key)
+                }
+                fn insert(self: ::std::pin::Pin<&mut Self>, key: #owned_key) -> bool {
+                    cxxbridge::#insert_fn(self, key)
+                }
+                #(#common_methods)*
+            }
+        });
+    }
+    (extern_c_mod_items, bindgen_mod_items)
+}
+
+/// Generate an implementation of `autocxx::RefCounted` for the pointee of a
+/// custom smart pointer, along with declarations of the C++ functions it
+/// calls.
+fn generate_ref_counted_impl(
+    name: &QualifiedName,
+    pointee: &Type,
+) -> (Vec<ForeignItem>, Vec<Item>) {
+    let add_ref_fn = make_ident(shim_name(name, "add_ref"));
+    let release_fn = make_ident(shim_name(name, "release"));
+    let pointee_id = bridge_ident(pointee);
+    let extern_c_mod_items = vec![
+        parse_quote! {
+            unsafe fn #add_ref_fn(raw: *const #pointee_id);
+        },
+        parse_quote! {
+            unsafe fn #release_fn(raw: *const #pointee_id);
+        },
+    ];
+    let bindgen_mod_items = vec![parse_quote! {
+        unsafe impl autocxx::RefCounted for #pointee {
+            unsafe fn add_ref(this: *const Self) {
+                cxxbridge::#add_ref_fn(this)
+            }
+            unsafe fn release(this: *const Self) {
+                cxxbridge::#release_fn(this)
+            }
+        }
+    }];
+    (extern_c_mod_items, bindgen_mod_items)
+}
+
+/// Generate an implementation of `autocxx::Deleter` for the deleter of a
+/// `std::unique_ptr`, along with a declaration of the C++ function it calls.
+fn generate_deleter_impl(
+    name: &QualifiedName,
+    pointee: &Type,
+    deleter: &Type,
+) -> (Vec<ForeignItem>, Vec<Item>) {
+    let delete_fn = make_ident(shim_name(name, "delete"));
+    let pointee_id = bridge_ident(pointee);
+    let extern_c_mod_items = vec![parse_quote! {
+        unsafe fn #delete_fn(raw: *mut #pointee_id);
+    }];
+    let bindgen_mod_items = vec![parse_quote! {
+        unsafe impl autocxx::Deleter<#pointee> for #deleter {
+            unsafe fn delete(ptr: *mut #pointee) {
+                cxxbridge::#delete_fn(ptr)
+            }
+        }
+    }];
+    (extern_c_mod_items, bindgen_mod_items)
+}
+
+/// The name by which the cxx bridge knows a type which we've converted
+/// to a path.
+fn bridge_ident(ty: &Type) -> Ident {
+    match ty {
+        Type::Path(typ) => typ.path.segments.last().map(|seg| seg.ident.clone()),
+        _ => None,
+    }
+    .expect("type was not a path")
+}
+
+/// Turn our representation of a union, as a struct, back into a union.
+fn struct_to_union(s: ItemStruct) -> ItemUnion {
+    let fields = match s.fields {
+        Fields::Named(fields) => fields,
+        _ => panic!("Unions always have named fields"),
+    };
+    ItemUnion {
+        attrs: s.attrs,
+        vis: s.vis,
+        union_token: Token![union](s.struct_token.span),
+        ident: s.ident,
+        generics: s.generics,
+        fields,
+    }
+}
+
+/// C++ allows the members of an anonymous union to be accessed as if they
+/// were members of the enclosing struct. Rust has no anonymous unions, so
+/// generate accessors on the struct for each such member. These are
+/// `unsafe` because the member may not be the active one. (A union type
+/// without a name may also be used for a named field, which is accessed
+/// in the usual way.)
+fn generate_anonymous_union_accessors(id: &Ident, anonymous_unions: &[AnonymousUnion]) -> Item {
+    let accessors = anonymous_unions
+        .iter()
+        .filter(|au| au.field.to_string().starts_with("__bindgen_anon"))
+        .flat_map(|au| {
+            let field = &au.field;
+            au.item.fields.iter().filter_map(move |f| {
+                let member = f.ident.as_ref()?;
+                if member.to_string().starts_with("_bitfield")
+                    || member.to_string().starts_with("__bindgen")
+                {
+                    return None;
+                }
+                let member_mut = make_ident(format!("{}_mut", member.unraw()));
+                let ty = extract_manually_drop_inner_type(&f.ty).unwrap_or(&f.ty);
+                Some(quote! {
+                    pub unsafe fn #member(&self) -> &#ty {
+                        unsafe { &self.#field.#member }
+                    }
+                    pub unsafe fn #member_mut(&mut self) -> &mut #ty {
+                        unsafe { &mut self.#field.#member }
+                    }
+                })
+            })
+        });
+    Item::Impl(parse_quote! {
+        #[allow(unused_unsafe)]
+        impl #id {
+            #(#accessors)*
+        }
+    })
+}
+
 impl HasNs for (QualifiedName, RsCodegenResult) {
     fn get_namespace(&self) -> &Namespace {
         self.0.get_namespace()
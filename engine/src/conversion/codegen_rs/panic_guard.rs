@@ -0,0 +1,163 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+
+use autocxx_parser::PanicPolicy;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{parse_quote, visit_mut::VisitMut, ReturnType, Type, TypePath};
+
+use crate::conversion::type_helpers::extract_result_ok_type;
+
+/// Generates the code which stops a Rust panic unwinding into C++ frames,
+/// for any Rust function which is called from C++.
+pub(super) struct PanicGuard<'a> {
+    policy: &'a PanicPolicy,
+    context: String,
+}
+
+impl<'a> PanicGuard<'a> {
+    /// `context` is used in panic messages to describe the function
+    /// which panicked.
+    pub(super) fn new(policy: &'a PanicPolicy, context: String) -> Self {
+        Self { policy, context }
+    }
+
+    /// The return type of the Rust function which C++ calls, given the
+    /// return type of the function which it wraps. If that function returns
+    /// a `Result`, its error is converted to a string.
+    pub(super) fn rust_ret_type(&self, ret: &ReturnType) -> ReturnType {
+        match (self.policy, Self::result_ok_type(ret)) {
+            (_, Some(ok)) => {
+                parse_quote! { -> ::core::result::Result<#ok, ::std::string::String> }
+            }
+            (PanicPolicy::Abort, None) => ret.clone(),
+            (PanicPolicy::Rethrow, None) => {
+                let ok = Self::ok_type(ret);
+                parse_quote! { -> ::core::result::Result<#ok, autocxx::unwind::RustPanic> }
+            }
+        }
+    }
+
+    /// The return type to declare for this function in the `extern "Rust"`
+    /// section of the `cxx::bridge`.
+    pub(super) fn bridge_ret_type(&self, ret: &ReturnType) -> ReturnType {
+        match (self.policy, Self::result_ok_type(ret)) {
+            (_, Some(ok)) => parse_quote! { -> Result<#ok> },
+            (PanicPolicy::Abort, None) => ret.clone(),
+            (PanicPolicy::Rethrow, None) => {
+                let ok = Self::ok_type(ret);
+                parse_quote! { -> Result<#ok> }
+            }
+        }
+    }
+
+    /// Wraps a function body such that panics are caught. `ret` is the
+    /// return type of the body.
+    pub(super) fn wrap_body(&self, body: TokenStream, ret: &ReturnType) -> TokenStream {
+        let context = &self.context;
+        let returns_result = Self::result_ok_type(ret).is_some();
+        match (self.policy, returns_result) {
+            (PanicPolicy::Abort, false) => quote! {
+                autocxx::unwind::abort_on_panic(#context, move || { #body })
+            },
+            (PanicPolicy::Abort, true) => quote! {
+                autocxx::unwind::abort_on_panic(#context, move || { #body })
+                    .map_err(|e| ::std::string::ToString::to_string(&e))
+            },
+            (PanicPolicy::Rethrow, false) => quote! {
+                autocxx::unwind::catch_panic(#context, move || { #body })
+            },
+            (PanicPolicy::Rethrow, true) => quote! {
+                autocxx::unwind::catch_panic_or_error(#context, move || { #body })
+            },
+        }
+    }
+
+    fn ok_type(ret: &ReturnType) -> Type {
+        match ret {
+            ReturnType::Default => parse_quote! { () },
+            ReturnType::Type(_, ty) => (**ty).clone(),
+        }
+    }
+
+    fn result_ok_type(ret: &ReturnType) -> Option<&Type> {
+        match ret {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) => extract_result_ok_type(ty),
+        }
+    }
+}
+
+/// Types in the `extern "Rust"` section of a `cxx::bridge` may refer to
+/// things which `cxx` brings into scope itself, such as `UniquePtr`, and
+/// to C++ types in any namespace by their unqualified names.
+/// When we generate a Rust function outside the bridge with the same
+/// signature, those need to be qualified.
+pub(super) struct BridgeTypeQualifier<'a> {
+    /// Paths to C++ types within namespaces, keyed by their final identifier.
+    namespaced_types: &'a HashMap<Ident, TypePath>,
+}
+
+impl<'a> BridgeTypeQualifier<'a> {
+    pub(super) fn new(namespaced_types: &'a HashMap<Ident, TypePath>) -> Self {
+        Self { namespaced_types }
+    }
+}
+
+impl<'a> VisitMut for BridgeTypeQualifier<'a> {
+    fn visit_type_path_mut(&mut self, tp: &mut TypePath) {
+        if tp.qself.is_none() && tp.path.leading_colon.is_none() && tp.path.segments.len() == 1 {
+            let seg = tp.path.segments.first_mut().unwrap();
+            if let Some(namespaced) = self.namespaced_types.get(&seg.ident) {
+                *tp = namespaced.clone();
+                return;
+            }
+            let prefix: Option<syn::Path> = match seg.ident.to_string().as_str() {
+                "Pin" => Some(parse_quote! { ::core::pin }),
+                "UniquePtr" | "SharedPtr" | "WeakPtr" | "CxxString" | "CxxVector" => {
+                    Some(parse_quote! { cxx })
+                }
+                _ => None,
+            };
+            if let Some(mut prefix) = prefix {
+                prefix.segments.push(seg.clone());
+                tp.path = prefix;
+            }
+        }
+        syn::visit_mut::visit_type_path_mut(self, tp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::map::IndexMap as HashMap;
+    use quote::{quote, ToTokens};
+    use syn::{parse_quote, visit_mut::VisitMut, Type};
+
+    use super::BridgeTypeQualifier;
+
+    #[test]
+    fn test_qualify_bridge_types() {
+        let mut namespaced_types = HashMap::new();
+        namespaced_types.insert(parse_quote! { B }, parse_quote! { ns::B });
+        let mut ty: Type = parse_quote! { Pin<&mut UniquePtr<A> > };
+        BridgeTypeQualifier::new(&namespaced_types).visit_type_mut(&mut ty);
+        assert_eq!(
+            ty.to_token_stream().to_string(),
+            quote! { ::core::pin::Pin<&mut cxx::UniquePtr<A> > }.to_string()
+        );
+        let mut ty: Type = parse_quote! { Box<B> };
+        BridgeTypeQualifier::new(&namespaced_types).visit_type_mut(&mut ty);
+        assert_eq!(
+            ty.to_token_stream().to_string(),
+            quote! { Box<ns::B> }.to_string()
+        );
+    }
+}
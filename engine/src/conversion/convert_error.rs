@@ -39,6 +39,8 @@ pub enum ConvertError {
 pub enum ConvertErrorFromCpp {
     #[error("An item was requested using 'generate_pod' which was not safe to hold by value in Rust. {0}")]
     UnsafePodType(String),
+    #[error("The type {} was requested using 'derive_serde', or is used by a field of such a type, but its field '{1}' can't be serialized. Pointers, unions, bitfields and arrays of arrays longer than 32 elements are not supported.", .0.to_cpp_name())]
+    UnserializableField(QualifiedName, String),
     #[error("Bindgen generated some unexpected code in a foreign mod section. You may have specified something in a 'generate' directive which is not currently compatible with autocxx.")]
     UnexpectedForeignItem,
     #[error("Bindgen generated some unexpected code in its outermost mod section. You may have specified something in a 'generate' directive which is not currently compatible with autocxx.")]
@@ -113,9 +115,13 @@ pub enum ConvertErrorFromCpp {
     NonPublicNestedType,
     #[error("This function returns an rvalue reference (&&) which is not yet supported.")]
     RValueReturn,
+    #[error(
+        "This function returns a std::weak_ptr to a const type, which can't be returned to Rust."
+    )]
+    WeakPtrToConstReturn,
     #[error("This method is private")]
     PrivateMethod,
-    #[error("autocxx does not know how to generate bindings to operator=")]
+    #[error("autocxx can only generate bindings to copy and move assignment operators")]
     AssignmentOperator,
     #[error("This function was marked =delete")]
     Deleted,
@@ -137,6 +143,12 @@ pub enum ConvertErrorFromCpp {
     InvalidTypeForCppPtr(QualifiedName),
     #[error("A C++ std::vector was found containing some type that cxx can't accommodate as a vector element ({})", .0.to_cpp_name())]
     InvalidTypeForCppVector(QualifiedName),
+    #[error("A std::chrono type ({}) was found somewhere other than a parameter passed by value or const reference, or a return value. autocxx can only convert std::chrono types in those positions.", .0.to_cpp_name())]
+    UnsupportedChronoUsage(QualifiedName),
+    #[error("A std::pair or std::tuple ({}) was found somewhere other than a parameter passed by value or const reference, or a return value. autocxx can only convert such types in those positions.", .0.to_cpp_name())]
+    UnsupportedTupleUsage(QualifiedName),
+    #[error("A std::pair or std::tuple ({}) contained an element which isn't POD, so can't be converted to a Rust tuple.", .0.to_cpp_name())]
+    TupleContainingNonPodType(QualifiedName),
     #[error("Variadic functions are not supported by cxx or autocxx.")]
     Variadic,
     #[error("A type had a template inside a std::vector, which is not supported.")]
@@ -171,7 +183,7 @@ pub enum ConvertErrorFromRust {
     ExternRustFunRequiresFullyQualifiedReceiver,
     #[error("extern_rust_function cannot support &mut T references; instead use Pin<&mut T> (see cxx documentation for more details")]
     PinnedReferencesRequiredForExternFun,
-    #[error("extern_rust_function cannot currently support qualified type paths (that is, foo::bar::Baz). All type paths must be within the current module, imported using 'use'. This restriction may be lifted in future.")]
+    #[error("extern_rust_function only supports type paths within the current crate (that is, Baz, crate::foo::Baz, super::Baz or self::foo::Baz). Types elsewhere must be imported using 'use'.")]
     NamespacesNotSupportedForExternFun,
     #[error("extern_rust_function signatures must never reference Self: instead, spell out the type explicitly.")]
     ExplicitSelf,
@@ -99,6 +99,73 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B>(
                 rs_definition,
                 cpp_definition,
             }))),
+            Api::Tuple {
+                name,
+                elements,
+                cpp_definition,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::Tuple {
+                name,
+                elements,
+                cpp_definition,
+                deps,
+            }))),
+            Api::Variant {
+                name,
+                alternatives,
+                cpp_definition,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::Variant {
+                name,
+                alternatives,
+                cpp_definition,
+                deps,
+            }))),
+            Api::AssociativeContainer {
+                name,
+                container_type,
+                elements,
+                cpp_definition,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::AssociativeContainer {
+                name,
+                container_type,
+                elements,
+                cpp_definition,
+                deps,
+            }))),
+            Api::SmartPointer {
+                name,
+                details,
+                pointee,
+                cpp_definition,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::SmartPointer {
+                name,
+                details,
+                pointee,
+                cpp_definition,
+                deps,
+            }))),
+            Api::UniquePtrWithDeleter {
+                name,
+                pointee,
+                deleter,
+                cpp_definition,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::UniquePtrWithDeleter {
+                name,
+                pointee,
+                deleter,
+                cpp_definition,
+                deps,
+            }))),
+            Api::ContainerElement { name, element } => {
+                Ok(Box::new(std::iter::once(Api::ContainerElement {
+                    name,
+                    element,
+                })))
+            }
             Api::ForwardDeclaration { name, err } => {
                 Ok(Box::new(std::iter::once(Api::ForwardDeclaration {
                     name,
@@ -133,6 +200,15 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B>(
                 details,
                 deps,
             }))),
+            Api::RustTrait {
+                name,
+                details,
+                deps,
+            } => Ok(Box::new(std::iter::once(Api::RustTrait {
+                name,
+                details,
+                deps,
+            }))),
             Api::RustSubclassFn {
                 name,
                 subclass,
@@ -37,6 +37,8 @@ use self::{
         casts::add_casts,
         check_names,
         constructor_deps::decorate_types_with_constructor_deps,
+        conversion_operators::add_conversion_operators,
+        field_accessors::add_field_accessors,
         gc::filter_apis_by_following_edges_from_allowlist,
         pod::analyze_pod_apis,
         remove_ignored::filter_apis_by_ignored_dependents,
@@ -46,7 +48,7 @@ use self::{
     api::AnalysisPhase,
     apivec::ApiVec,
     codegen_rs::RsCodeGenerator,
-    parse::ParseBindgen,
+    parse::{scan_declarations, ParseBindgen},
 };
 
 const LOG_APIS: bool = true;
@@ -114,7 +116,8 @@ impl<'a> BridgeConverter<'a> {
             None => Err(ConvertError::NoContent),
             Some((_, items)) => {
                 // Parse the bindgen mod.
-                let items_to_process = std::mem::take(items);
+                let mut items_to_process = std::mem::take(items);
+                scan_declarations(&mut items_to_process);
                 let parser = ParseBindgen::new(self.config);
                 let apis = parser.parse_items(items_to_process, source_file_contents)?;
                 Self::dump_apis("parsing", &apis);
@@ -137,6 +140,8 @@ impl<'a> BridgeConverter<'a> {
                 let analyzed_apis = replace_hopeless_typedef_targets(self.config, analyzed_apis);
                 let analyzed_apis = add_casts(analyzed_apis);
                 let analyzed_apis = create_alloc_and_frees(analyzed_apis);
+                let analyzed_apis = add_field_accessors(analyzed_apis);
+                let analyzed_apis = add_conversion_operators(analyzed_apis, &unsafe_policy);
                 // Next, figure out how we materialize different functions.
                 // Some will be simple entries in the cxx::bridge module; others will
                 // require C++ wrapper functions. This is probably the most complex
@@ -197,7 +202,8 @@ impl<'a> BridgeConverter<'a> {
                     bindgen_mod,
                     self.config,
                     cpp.as_ref().map(|file_pair| file_pair.header_name.clone()),
-                );
+                )
+                .map_err(ConvertError::Cpp)?;
                 Ok(CodegenResults {
                     rs,
                     cpp,
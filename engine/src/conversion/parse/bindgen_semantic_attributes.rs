@@ -6,15 +6,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use indexmap::set::IndexSet as HashSet;
 use proc_macro2::{Ident, TokenStream};
 use syn::{
     parenthesized,
     parse::{Parse, Parser},
-    Attribute, LitStr,
+    Attribute, LitInt, LitStr, Token,
 };
 
 use crate::conversion::{
-    api::{CppVisibility, DeletedOrDefaulted, Layout, References, SpecialMemberKind, Virtualness},
+    api::{
+        ConversionOperator, CppVisibility, DeletedOrDefaulted, Layout, References,
+        SpecialMemberKind, Virtualness,
+    },
     convert_error::{ConvertErrorWithContext, ErrorContext},
     ConvertErrorFromCpp,
 };
@@ -120,6 +124,32 @@ impl BindgenSemanticAttributes {
         ls.map(|ls| ls.value())
     }
 
+    /// Any conversion operators which we found in the declaration of a
+    /// struct.
+    pub(super) fn get_conversion_operators(&self) -> Vec<ConversionOperator> {
+        self.0
+            .iter()
+            .filter(|a| a.is_ident("conversion_operator"))
+            .filter_map(
+                |a| match a.parse_args::<Ident>().ok()?.to_string().as_str() {
+                    "bool" => Some(ConversionOperator::Bool),
+                    "string" => Some(ConversionOperator::String),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// The fields of a struct which we found aren't `const`.
+    pub(super) fn get_non_const_fields(&self) -> HashSet<String> {
+        self.0
+            .iter()
+            .filter(|a| a.is_ident("non_const_field"))
+            .filter_map(|a| a.parse_args::<LitStr>().ok())
+            .map(|field| field.value())
+            .collect()
+    }
+
     /// The in-memory layout of the item.
     pub(super) fn get_layout(&self) -> Option<Layout> {
         self.parse_if_present("layout")
@@ -130,6 +160,16 @@ impl BindgenSemanticAttributes {
         self.string_if_present("original_name")
     }
 
+    /// Where the item was declared, as the file name and the byte offset
+    /// of its name within that file.
+    pub(super) fn get_source_location(&self) -> Option<(String, usize)> {
+        let location: SourceLocation = self.parse_if_present("source_location")?;
+        Some((
+            location.file.value(),
+            location.byte_offset.base10_parse().ok()?,
+        ))
+    }
+
     /// Whether this is a move constructor or other special member.
     pub(super) fn special_member_kind(&self) -> Option<SpecialMemberKind> {
         self.string_if_present("special_member")
@@ -167,6 +207,26 @@ impl BindgenSemanticAttributes {
     }
 }
 
+/// The contents of a `source_location(file, line, column, byte_offset)`
+/// annotation.
+struct SourceLocation {
+    file: LitStr,
+    byte_offset: LitInt,
+}
+
+impl Parse for SourceLocation {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let file = input.parse()?;
+        input.parse::<Token![,]>()?;
+        input.parse::<LitInt>()?;
+        input.parse::<Token![,]>()?;
+        input.parse::<LitInt>()?;
+        input.parse::<Token![,]>()?;
+        let byte_offset = input.parse()?;
+        Ok(Self { file, byte_offset })
+    }
+}
+
 #[derive(Debug)]
 struct BindgenSemanticAttribute {
     annotation_name: Ident,
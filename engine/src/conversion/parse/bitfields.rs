@@ -0,0 +1,264 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+
+use syn::{
+    visit_mut::{visit_expr_mut, VisitMut},
+    Expr, ExprMethodCall, FnArg, ImplItem, ImplItemFn, Item, ItemStruct, Lit, Member, ReturnType,
+    Type, Visibility,
+};
+
+use crate::conversion::api::Bitfield;
+
+/// bindgen represents each run of C++ bitfields as a field of type
+/// `__BindgenBitfieldUnit<[u8; N]>`, and generates a getter and setter
+/// for each bitfield in an `impl` block for the struct. Find all such
+/// accessors within the items of a mod, keyed by the struct name.
+pub(super) fn find_bitfields(items: &[Item]) -> HashMap<syn::Ident, Vec<Bitfield>> {
+    let mut results = HashMap::new();
+    for item in items {
+        if let Item::Impl(imp) = item {
+            let ty_id = match &*imp.self_ty {
+                Type::Path(typ) => typ.path.segments.last().unwrap().ident.clone(),
+                _ => continue,
+            };
+            let mut getters = Vec::new();
+            let mut setters = HashMap::new();
+            for i in &imp.items {
+                if let ImplItem::Fn(itm) = i {
+                    match find_unit_access(itm) {
+                        Some(UnitAccess::Get(access, int_ty)) => {
+                            getters.push((itm, access, int_ty))
+                        }
+                        Some(UnitAccess::Set(access)) => {
+                            setters.insert(access, itm.sig.ident.clone());
+                        }
+                        None => {}
+                    }
+                }
+            }
+            let bitfields: Vec<_> = getters
+                .into_iter()
+                .filter(|(itm, _, _)| matches!(itm.vis, Visibility::Public(_)))
+                .filter_map(|(itm, access, int_ty)| {
+                    let ty = match &itm.sig.output {
+                        ReturnType::Type(_, ty) => ty.as_ref().clone(),
+                        ReturnType::Default => return None,
+                    };
+                    let setter = setters.get(&access)?.clone();
+                    Some(Bitfield {
+                        getter: itm.sig.ident.clone().into(),
+                        setter: setter.into(),
+                        ty: ty.into(),
+                        int_ty: (*int_ty).into(),
+                        storage: access.storage.into(),
+                        bit_offset: access.bit_offset,
+                        bit_width: access.bit_width,
+                    })
+                })
+                .collect();
+            if !bitfields.is_empty() {
+                results
+                    .entry(ty_id)
+                    .or_insert_with(Vec::new)
+                    .extend(bitfields);
+            }
+        }
+    }
+    results
+}
+
+/// Replace each `__BindgenBitfieldUnit<[u8; N]>` field within a struct
+/// by the `[u8; N]` which it wraps. The unit is a `#[repr(C)]` struct with
+/// a single field, so this doesn't change the layout, but it does mean
+/// we don't need to pass bindgen's helper type through to our output.
+pub(super) fn replace_bitfield_units(s: &mut ItemStruct) {
+    for f in s.fields.iter_mut() {
+        if let Type::Path(typ) = &f.ty {
+            let last_seg = typ.path.segments.last().unwrap();
+            if last_seg.ident == "__BindgenBitfieldUnit" {
+                if let syn::PathArguments::AngleBracketed(args) = &last_seg.arguments {
+                    if let Some(syn::GenericArgument::Type(storage)) = args.args.first() {
+                        f.ty = storage.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct BitfieldAccess {
+    storage: syn::Ident,
+    bit_offset: usize,
+    bit_width: u8,
+}
+
+enum UnitAccess {
+    /// A call to `get` on a bitfield unit, and the integer type to which
+    /// the result is cast.
+    Get(BitfieldAccess, Box<Type>),
+    Set(BitfieldAccess),
+}
+
+fn find_unit_access(itm: &ImplItemFn) -> Option<UnitAccess> {
+    // Bitfield accessors always take self by reference.
+    if !matches!(itm.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return None;
+    }
+    let mut finder = UnitAccessFinder(None);
+    finder.visit_block_mut(&mut itm.block.clone());
+    finder.0
+}
+
+struct UnitAccessFinder(Option<UnitAccess>);
+
+impl VisitMut for UnitAccessFinder {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        if self.0.is_some() {
+            return;
+        }
+        match e {
+            Expr::Cast(cast) => {
+                if let Expr::MethodCall(mc) = &*cast.expr {
+                    if mc.method == "get" {
+                        if let Some(access) = get_bitfield_access(mc) {
+                            self.0 = Some(UnitAccess::Get(access, cast.ty.clone()));
+                            return;
+                        }
+                    }
+                }
+            }
+            Expr::MethodCall(mc) if mc.method == "set" => {
+                if let Some(access) = get_bitfield_access(mc) {
+                    self.0 = Some(UnitAccess::Set(access));
+                    return;
+                }
+            }
+            _ => {}
+        }
+        visit_expr_mut(self, e)
+    }
+}
+
+/// Spot `self._bitfield_N.get(OFFSET, WIDTH)` or
+/// `self._bitfield_N.set(OFFSET, WIDTH, val)`.
+fn get_bitfield_access(mc: &ExprMethodCall) -> Option<BitfieldAccess> {
+    let storage = match &*mc.receiver {
+        Expr::Field(field) => match (&*field.base, &field.member) {
+            (Expr::Path(base), Member::Named(id)) if base.path.is_ident("self") => id.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut args = mc.args.iter();
+    let bit_offset = get_int_lit(args.next()?)?;
+    let bit_width = get_int_lit(args.next()?)?;
+    Some(BitfieldAccess {
+        storage,
+        bit_offset,
+        bit_width,
+    })
+}
+
+fn get_int_lit<T: std::str::FromStr>(e: &Expr) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    match e {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(i) => i.base10_parse().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_bitfields, replace_bitfield_units};
+    use quote::{quote, ToTokens};
+    use syn::{parse_quote, Item, ItemStruct};
+
+    #[test]
+    fn test_find_bitfields() {
+        let items: Vec<Item> = vec![parse_quote! {
+            impl A {
+                #[inline]
+                pub fn a(&self) -> ::std::os::raw::c_uint {
+                    unsafe { ::std::mem::transmute(self._bitfield_1.get(0usize, 3u8) as u32) }
+                }
+                #[inline]
+                pub fn set_a(&mut self, val: ::std::os::raw::c_uint) {
+                    unsafe {
+                        let val: u32 = ::std::mem::transmute(val);
+                        self._bitfield_1.set(0usize, 3u8, val as u64)
+                    }
+                }
+                #[inline]
+                pub fn b(&self) -> bool {
+                    unsafe { ::std::mem::transmute(self._bitfield_1.get(3usize, 1u8) as u8) }
+                }
+                #[inline]
+                pub fn set_b(&mut self, val: bool) {
+                    unsafe {
+                        let val: u8 = ::std::mem::transmute(val);
+                        self._bitfield_1.set(3usize, 1u8, val as u64)
+                    }
+                }
+                #[inline]
+                pub fn new_bitfield_1(
+                    a: ::std::os::raw::c_uint,
+                    b: bool,
+                ) -> __BindgenBitfieldUnit<[u8; 1usize]> {
+                    let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 1usize]> =
+                        Default::default();
+                    __bindgen_bitfield_unit
+                }
+            }
+        }];
+        let bitfields = find_bitfields(&items);
+        let bitfields = bitfields.get(&syn::Ident::new("A", proc_macro2::Span::call_site()));
+        let bitfields = bitfields.unwrap();
+        assert_eq!(bitfields.len(), 2);
+        assert_eq!(bitfields[0].getter.to_string(), "a");
+        assert_eq!(bitfields[0].setter.to_string(), "set_a");
+        assert_eq!(
+            bitfields[0].ty.to_token_stream().to_string(),
+            quote! { ::std::os::raw::c_uint }.to_string()
+        );
+        assert_eq!(bitfields[0].int_ty.to_token_stream().to_string(), "u32");
+        assert_eq!(bitfields[0].storage.to_string(), "_bitfield_1");
+        assert_eq!(bitfields[1].bit_offset, 3);
+        assert_eq!(bitfields[1].bit_width, 1);
+    }
+
+    #[test]
+    fn test_replace_bitfield_units() {
+        let mut s: ItemStruct = parse_quote! {
+            pub struct A {
+                pub _bitfield_align_1: [u8; 0],
+                pub _bitfield_1: root::__BindgenBitfieldUnit<[u8; 1usize]>,
+                pub c: u32,
+            }
+        };
+        replace_bitfield_units(&mut s);
+        let expected: ItemStruct = parse_quote! {
+            pub struct A {
+                pub _bitfield_align_1: [u8; 0],
+                pub _bitfield_1: [u8; 1usize],
+                pub c: u32,
+            }
+        };
+        assert_eq!(
+            s.to_token_stream().to_string(),
+            expected.to_token_stream().to_string()
+        );
+    }
+}
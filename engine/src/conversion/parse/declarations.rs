@@ -0,0 +1,815 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+use indexmap::set::IndexSet as HashSet;
+use syn::{
+    parse_quote, FnArg, ForeignItem, ForeignItemFn, Item, ItemStruct, ReturnType, Type, TypeArray,
+    TypePtr, TypeReference,
+};
+
+use super::BindgenSemanticAttributes;
+use crate::{conversion::api::ConversionOperator, types::make_ident};
+
+/// A C++ character type which bindgen represents as a plain integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharType {
+    Char8,
+    Char32,
+    WChar,
+}
+
+impl CharType {
+    fn from_keyword(keyword: &[u8]) -> Option<Self> {
+        match keyword {
+            b"char8_t" => Some(Self::Char8),
+            b"char32_t" => Some(Self::Char32),
+            b"wchar_t" => Some(Self::WChar),
+            _ => None,
+        }
+    }
+
+    fn to_type(self) -> Type {
+        match self {
+            Self::Char8 => parse_quote! { autocxx::c_char8_t },
+            Self::Char32 => parse_quote! { autocxx::c_char32_t },
+            Self::WChar => parse_quote! { autocxx::c_wchar_t },
+        }
+    }
+}
+
+/// What we learn from the declaration of a function: the character types
+/// used by each parameter and by its return type, whether it returns a
+/// `std::weak_ptr` to `const`, and whether it's `explicit`.
+#[derive(Debug, PartialEq, Eq)]
+struct FunctionDeclaration {
+    params: Vec<Option<CharType>>,
+    ret: Option<CharType>,
+    ret_weak_ptr_to_const: bool,
+    explicit: bool,
+}
+
+/// What we learn from the definition of a struct: the fields declared with
+/// character types, the fields which aren't `const`, and its conversion
+/// operators.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct StructDeclaration {
+    char_fields: HashMap<String, CharType>,
+    non_const_fields: HashSet<String>,
+    conversion_operators: Vec<ConversionOperator>,
+}
+
+/// Fills in some details which bindgen loses, by looking at the
+/// declarations in the headers. bindgen does tell us where each function
+/// and struct was declared.
+///
+/// bindgen represents `char8_t`, `char32_t` and `wchar_t` as plain integers
+/// (unlike `char16_t`) so we can't otherwise tell them apart from other
+/// integers. Wherever a parameter, return type or field is declared using
+/// one of these character types we replace bindgen's integer with our own
+/// newtype.
+///
+/// bindgen also drops `const` from template arguments, so we mark any
+/// function which returns a `std::weak_ptr<const T>`, which can't be
+/// returned to Rust. Nor does it tell us which constructors are `explicit`,
+/// so we mark those too, or which fields are `const`, or about conversion
+/// operators, so we mark each struct with its non-`const` fields and any
+/// conversion operators which we can represent.
+///
+/// Types hidden behind typedefs or template parameters aren't spotted.
+pub(crate) fn scan_declarations(items: &mut [Item]) {
+    DeclarationScanner::default().scan(items)
+}
+
+#[derive(Default)]
+struct DeclarationScanner {
+    /// Each header we've read, prepared by `blank_non_code`.
+    sources: HashMap<String, Option<Vec<u8>>>,
+}
+
+impl DeclarationScanner {
+    fn scan(&mut self, items: &mut [Item]) {
+        for item in items {
+            match item {
+                Item::Mod(itm) => {
+                    if let Some((_, items)) = &mut itm.content {
+                        self.scan(items)
+                    }
+                }
+                Item::ForeignMod(fm) => {
+                    for fi in &mut fm.items {
+                        if let ForeignItem::Fn(fun) = fi {
+                            self.scan_fn(fun)
+                        }
+                    }
+                }
+                Item::Struct(s) => self.scan_struct(s),
+                _ => {}
+            }
+        }
+    }
+
+    /// Finds the source of the header in which an item was declared, and
+    /// the offset of its name.
+    fn find_source(&mut self, attrs: &[syn::Attribute]) -> Option<(&[u8], usize)> {
+        let (file, offset) = BindgenSemanticAttributes::new(attrs).get_source_location()?;
+        let source = self
+            .sources
+            .entry(file)
+            .or_insert_with_key(|file| std::fs::read(file).ok().map(|s| blank_non_code(&s)));
+        Some((source.as_deref()?, offset))
+    }
+
+    fn scan_fn(&mut self, fun: &mut ForeignItemFn) {
+        let Some(declaration) = self
+            .find_source(&fun.attrs)
+            .and_then(|(source, offset)| scan_function(source, offset))
+        else {
+            return;
+        };
+        if declaration.ret_weak_ptr_to_const {
+            fun.attrs
+                .push(parse_quote! { #[cpp_semantics(ret_type_weak_ptr_to_const)] });
+        }
+        if declaration.explicit {
+            fun.attrs.push(parse_quote! { #[cpp_semantics(explicit)] });
+        }
+        // bindgen adds a `this` parameter to methods, so match up the
+        // parameters from the end.
+        let mut param_types: Vec<&mut Type> = fun
+            .sig
+            .inputs
+            .iter_mut()
+            .filter_map(|arg| match arg {
+                FnArg::Typed(pt) => Some(&mut *pt.ty),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let Some(extra_params) = param_types.len().checked_sub(declaration.params.len()) else {
+            return;
+        };
+        for (ty, char_type) in param_types
+            .iter_mut()
+            .skip(extra_params)
+            .zip(declaration.params)
+        {
+            if let Some(char_type) = char_type {
+                replace_integer(ty, char_type)
+            }
+        }
+        if let (ReturnType::Type(_, ty), Some(char_type)) = (&mut fun.sig.output, declaration.ret) {
+            replace_integer(ty, char_type)
+        }
+    }
+
+    fn scan_struct(&mut self, s: &mut ItemStruct) {
+        let Some(declaration) = self
+            .find_source(&s.attrs)
+            .and_then(|(source, offset)| scan_struct_definition(source, offset))
+        else {
+            return;
+        };
+        for conversion_operator in declaration.conversion_operators {
+            let target = make_ident(match conversion_operator {
+                ConversionOperator::Bool => "bool",
+                ConversionOperator::String => "string",
+            });
+            s.attrs
+                .push(parse_quote! { #[cpp_semantics(conversion_operator(#target))] });
+        }
+        for field in declaration.non_const_fields {
+            s.attrs
+                .push(parse_quote! { #[cpp_semantics(non_const_field(#field))] });
+        }
+        let char_types = declaration.char_fields;
+        for field in s.fields.iter_mut() {
+            let Some(name) = field.ident.as_ref().map(|id| id.to_string()) else {
+                continue;
+            };
+            // bindgen adds an underscore to fields named after Rust keywords.
+            let char_type = char_types
+                .get(&name)
+                .or_else(|| name.strip_suffix('_').and_then(|name| char_types.get(name)));
+            if let Some(char_type) = char_type {
+                replace_integer(&mut field.ty, *char_type)
+            }
+        }
+    }
+}
+
+/// Replaces the integer at the heart of a type (perhaps behind pointers or
+/// within an array) with the newtype for a character type. Anything else,
+/// such as a function pointer, is left alone.
+fn replace_integer(ty: &mut Type, char_type: CharType) {
+    match ty {
+        Type::Ptr(TypePtr { elem, .. })
+        | Type::Reference(TypeReference { elem, .. })
+        | Type::Array(TypeArray { elem, .. }) => replace_integer(elem, char_type),
+        Type::Path(tp)
+            if tp.qself.is_none()
+                && ["u8", "u16", "u32"].iter().any(|int| tp.path.is_ident(int)) =>
+        {
+            *ty = char_type.to_type()
+        }
+        _ => {}
+    }
+}
+
+/// Replaces comments, string and character literals, and preprocessor
+/// directives with spaces, so that we can look for declarations without
+/// tripping over them, while keeping the byte offsets of everything else.
+fn blank_non_code(source: &[u8]) -> Vec<u8> {
+    let mut result = source.to_vec();
+    let mut i = 0;
+    let mut at_line_start = true;
+    while i < source.len() {
+        let end = match &source[i..] {
+            [b'/', b'/', ..] => find_line_end(source, i, false),
+            [b'/', b'*', ..] => find_from(source, i + 2, b"*/").map_or(source.len(), |e| e + 2),
+            [b'#', ..] if at_line_start => find_line_end(source, i, true),
+            [quote @ (b'"' | b'\''), ..]
+                if !(*quote == b'\'' && i > 0 && source[i - 1].is_ascii_digit()) =>
+            {
+                find_literal_end(source, i + 1, *quote)
+            }
+            [b, ..] => {
+                if *b == b'\n' {
+                    at_line_start = true;
+                } else if !b.is_ascii_whitespace() {
+                    at_line_start = false;
+                }
+                i += 1;
+                continue;
+            }
+            [] => unreachable!(),
+        };
+        for b in &mut result[i..end] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+        i = end;
+    }
+    result
+}
+
+fn find_from(source: &[u8], start: usize, needle: &[u8]) -> Option<usize> {
+    source[start..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| start + pos)
+}
+
+/// Finds the end of the line, optionally following backslash continuations.
+fn find_line_end(source: &[u8], start: usize, continuations: bool) -> usize {
+    let mut i = start;
+    while i < source.len() && source[i] != b'\n' {
+        if continuations && source[i] == b'\\' && source.get(i + 1) == Some(&b'\n') {
+            i += 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+fn find_literal_end(source: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start;
+    while i < source.len() && source[i] != quote && source[i] != b'\n' {
+        if source[i] == b'\\' {
+            i += 1;
+        }
+        i += 1;
+    }
+    (i + 1).min(source.len())
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// How nesting changes at this byte: `(`, `[`, `{` and `<` open, and their
+/// counterparts close.
+fn nesting(b: u8) -> isize {
+    match b {
+        b'(' | b'[' | b'{' | b'<' => 1,
+        b')' | b']' | b'}' | b'>' => -1,
+        _ => 0,
+    }
+}
+
+/// The identifiers in some code which aren't within brackets of any kind,
+/// so we don't find those in template arguments, for instance.
+fn top_level_identifiers(code: &[u8]) -> Vec<&[u8]> {
+    top_level_identifier_offsets(code)
+        .into_iter()
+        .map(|(_, identifier)| identifier)
+        .collect()
+}
+
+/// As `top_level_identifiers`, along with the offset of each.
+fn top_level_identifier_offsets(code: &[u8]) -> Vec<(usize, &[u8])> {
+    let mut identifiers = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < code.len() {
+        if is_ident_byte(code[i]) {
+            let len = code[i..].iter().take_while(|b| is_ident_byte(**b)).count();
+            if depth == 0 {
+                identifiers.push((i, &code[i..i + len]));
+            }
+            i += len;
+        } else {
+            depth += nesting(code[i]);
+            i += 1;
+        }
+    }
+    identifiers
+}
+
+fn char_type_in(code: &[u8]) -> Option<CharType> {
+    top_level_identifiers(code)
+        .into_iter()
+        .find_map(CharType::from_keyword)
+}
+
+/// Whether some code names a `std::weak_ptr` to a `const` type, other than
+/// within the template arguments of some other type.
+fn is_weak_ptr_to_const(code: &[u8]) -> bool {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < code.len() {
+        if is_ident_byte(code[i]) {
+            let len = code[i..].iter().take_while(|b| is_ident_byte(**b)).count();
+            if depth == 0 && &code[i..i + len] == b"weak_ptr" {
+                let args = code[i + len..].trim_ascii_start();
+                if args.starts_with(b"<") {
+                    let args = split_top_level(&args[1..], b'>')[0];
+                    return top_level_identifiers(args).contains(&&b"const"[..]);
+                }
+            }
+            i += len;
+        } else {
+            depth += nesting(code[i]);
+            i += 1;
+        }
+    }
+    false
+}
+
+/// Splits code at each top level occurrence of `separator`.
+fn split_top_level(code: &[u8], separator: u8) -> Vec<&[u8]> {
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, b) in code.iter().enumerate() {
+        if depth == 0 && *b == separator {
+            pieces.push(&code[start..i]);
+            start = i + 1;
+        }
+        depth += nesting(*b);
+    }
+    pieces.push(&code[start..]);
+    pieces
+}
+
+/// Finds the bracket which closes the one at `open`.
+fn matching_close(source: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, b) in source.iter().enumerate().skip(open) {
+        if matches!(b, b'(' | b'[' | b'{') {
+            depth += 1;
+        } else if matches!(b, b')' | b']' | b'}') {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Removes any leading access specifiers, such as `public:`.
+fn strip_access_specifiers(code: &[u8]) -> &[u8] {
+    split_access_specifiers(code).1
+}
+
+/// Splits any leading access specifiers from some code, returning
+/// whether the last of them was `public`.
+fn split_access_specifiers(mut code: &[u8]) -> (Option<bool>, &[u8]) {
+    let mut is_public = None;
+    loop {
+        let trimmed = code.trim_ascii_start();
+        let Some((specifier, rest)) = [&b"public"[..], b"protected", b"private"]
+            .iter()
+            .find_map(|specifier| Some((*specifier, trimmed.strip_prefix(*specifier)?)))
+        else {
+            return (is_public, code);
+        };
+        match rest.trim_ascii_start().strip_prefix(b":") {
+            Some(rest) if !rest.starts_with(b":") => {
+                is_public = Some(specifier == b"public");
+                code = rest
+            }
+            _ => return (is_public, code),
+        }
+    }
+}
+
+/// Looks at the declaration of a function whose name starts at `offset`.
+fn scan_function(source: &[u8], offset: usize) -> Option<FunctionDeclaration> {
+    let name_len = source
+        .get(offset..)?
+        .iter()
+        .take_while(|b| is_ident_byte(**b))
+        .count();
+    let open = offset
+        + name_len
+        + source[offset + name_len..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+    if name_len == 0 || source.get(open) != Some(&b'(') {
+        return None;
+    }
+    let params = &source[open + 1..matching_close(source, open)?];
+    let params = if matches!(params.trim_ascii(), b"" | b"void") {
+        Vec::new()
+    } else {
+        split_top_level(params, b',')
+            .into_iter()
+            .map(|param| char_type_in(split_top_level(param, b'=')[0]))
+            .collect()
+    };
+    let decl_start = source[..offset]
+        .iter()
+        .rposition(|b| matches!(b, b';' | b'{' | b'}'))
+        .map_or(0, |pos| pos + 1);
+    let ret = strip_access_specifiers(&source[decl_start..offset]);
+    Some(FunctionDeclaration {
+        params,
+        ret: char_type_in(ret),
+        ret_weak_ptr_to_const: is_weak_ptr_to_const(ret),
+        explicit: top_level_identifiers(ret).contains(&&b"explicit"[..]),
+    })
+}
+
+/// Looks at the definition of a struct whose name starts at `offset`, and
+/// finds any fields declared with character types and any public
+/// conversion operators.
+fn scan_struct_definition(source: &[u8], offset: usize) -> Option<StructDeclaration> {
+    // Members of a class are private unless stated otherwise.
+    let keyword_end = source[..offset].trim_ascii_end();
+    let mut is_public = !keyword_end.ends_with(b"class")
+        || keyword_end
+            .len()
+            .checked_sub(b"class".len() + 1)
+            .is_some_and(|before| is_ident_byte(keyword_end[before]));
+    let open = offset
+        + source
+            .get(offset..)?
+            .iter()
+            .position(|b| matches!(b, b'{' | b';'))?;
+    if source[open] != b'{' {
+        return None;
+    }
+    let body = &source[open + 1..matching_close(source, open)?];
+    let mut declaration = StructDeclaration::default();
+    for member in split_members(body) {
+        let (access, member) = split_access_specifiers(member);
+        is_public = access.unwrap_or(is_public);
+        if is_public {
+            declaration
+                .conversion_operators
+                .extend(conversion_operator_in(member));
+        }
+        let identifiers = top_level_identifiers(member);
+        let is_data_member = !member.contains(&b'(')
+            && !identifiers.first().is_some_and(|first| {
+                [
+                    &b"typedef"[..],
+                    b"using",
+                    b"friend",
+                    b"static",
+                    b"enum",
+                    b"struct",
+                    b"class",
+                    b"union",
+                    b"template",
+                ]
+                .contains(first)
+            });
+        if !is_data_member {
+            continue;
+        }
+        let char_type = char_type_in(member);
+        let is_const = identifiers.contains(&&b"const"[..]);
+        for declarator in split_top_level(member, b',') {
+            let name = declarator
+                .split(|b| matches!(b, b'=' | b'[' | b'{'))
+                .next()
+                .map(strip_bitfield_width)
+                .and_then(|declarator| top_level_identifiers(declarator).pop());
+            let Some(name) = name.map(|name| String::from_utf8_lossy(name).into_owned()) else {
+                continue;
+            };
+            if !is_const {
+                declaration.non_const_fields.insert(name.clone());
+            }
+            if let Some(char_type) = char_type {
+                declaration.char_fields.insert(name, char_type);
+            }
+        }
+    }
+    Some(declaration)
+}
+
+/// Removes any bitfield width from a data member declarator, leaving alone
+/// the `::` of qualified type names.
+fn strip_bitfield_width(declarator: &[u8]) -> &[u8] {
+    let colon = (0..declarator.len()).find(|&i| {
+        declarator[i] == b':'
+            && declarator.get(i + 1) != Some(&b':')
+            && (i == 0 || declarator[i - 1] != b':')
+    });
+    &declarator[..colon.unwrap_or(declarator.len())]
+}
+
+/// Finds the conversion operator which a struct member declares, if it's one
+/// we can represent: a `const`, non-template conversion to `bool` or
+/// `std::string`, which isn't deleted.
+fn conversion_operator_in(member: &[u8]) -> Option<ConversionOperator> {
+    let identifiers = top_level_identifier_offsets(member);
+    if identifiers
+        .first()
+        .is_some_and(|(_, first)| [&b"template"[..], b"friend", b"static"].contains(first))
+    {
+        return None;
+    }
+    let (start, _) = identifiers.iter().find(|(_, id)| *id == b"operator")?;
+    let after_keyword = start + b"operator".len();
+    let open = after_keyword + member[after_keyword..].iter().position(|b| *b == b'(')?;
+    let target: Vec<u8> = member[after_keyword..open]
+        .iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .copied()
+        .collect();
+    let conversion_operator = match target.as_slice() {
+        b"bool" => ConversionOperator::Bool,
+        b"std::string" | b"::std::string" => ConversionOperator::String,
+        _ => return None,
+    };
+    let close = matching_close(member, open)?;
+    if !matches!(member[open + 1..close].trim_ascii(), b"" | b"void") {
+        return None;
+    }
+    // Everything after the parameters and before any body, such as
+    // `const`, `noexcept` or `= delete`.
+    let qualifiers = &member[close + 1..];
+    let qualifiers = qualifiers
+        .iter()
+        .position(|b| *b == b'{')
+        .map_or(qualifiers, |body| &qualifiers[..body]);
+    let (qualifiers, definition) = match qualifiers.iter().position(|b| *b == b'=') {
+        Some(equals) => (&qualifiers[..equals], Some(&qualifiers[equals + 1..])),
+        None => (qualifiers, None),
+    };
+    let is_const = top_level_identifiers(qualifiers).contains(&&b"const"[..]);
+    // An rvalue-reference-qualified operator can't be called on a `const&`.
+    let is_ref_qualified = qualifiers.contains(&b'&');
+    let is_deleted = definition.is_some_and(|definition| definition.trim_ascii() == b"delete");
+    (is_const && !is_ref_qualified && !is_deleted).then_some(conversion_operator)
+}
+
+/// Splits the body of a struct into its members, which end either with a
+/// `;` or with a closing brace, as for an inline function definition.
+fn split_members(body: &[u8]) -> Vec<&[u8]> {
+    let mut members = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, b) in body.iter().enumerate() {
+        match b {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 && *b == b'}' {
+                    members.push(&body[start..=i]);
+                    start = i + 1;
+                }
+            }
+            b';' if depth == 0 => {
+                members.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::{quote, ToTokens};
+    use syn::{parse_quote, Item};
+
+    use super::{
+        blank_non_code, scan_declarations, scan_function, scan_struct_definition, CharType,
+        FunctionDeclaration,
+    };
+    use crate::conversion::api::ConversionOperator;
+
+    fn scan_function_named(source: &str, name: &str) -> Option<FunctionDeclaration> {
+        let source = blank_non_code(source.as_bytes());
+        let offset = std::str::from_utf8(&source).unwrap().find(name).unwrap();
+        scan_function(&source, offset)
+    }
+
+    #[test]
+    fn test_scan_function() {
+        assert_eq!(
+            scan_function_named(
+                "// wchar_t a();\ninline const char32_t* f(wchar_t a, int b = 'x', std::vector<char32_t> c);",
+                "f("
+            ),
+            Some(FunctionDeclaration {
+                params: vec![Some(CharType::WChar), None, None],
+                ret: Some(CharType::Char32),
+                ret_weak_ptr_to_const: false,
+                explicit: false,
+            })
+        );
+        assert_eq!(
+            scan_function_named(
+                "class A {\npublic:\n  std::basic_string<wchar_t> g(void) const;\n};",
+                "g("
+            ),
+            Some(FunctionDeclaration {
+                params: vec![],
+                ret: None,
+                ret_weak_ptr_to_const: false,
+                explicit: false,
+            })
+        );
+        assert_eq!(
+            scan_function_named(
+                "std::weak_ptr<A> f(std::weak_ptr<const A> a);\nstd::weak_ptr<A const> g();",
+                "g("
+            )
+            .map(|declaration| declaration.ret_weak_ptr_to_const),
+            Some(true)
+        );
+        assert_eq!(
+            scan_function_named("const std::weak_ptr<A> f(std::weak_ptr<const A> a);", "f(")
+                .map(|declaration| declaration.ret_weak_ptr_to_const),
+            Some(false)
+        );
+        assert_eq!(
+            scan_function_named("std::vector<std::weak_ptr<const A>> f();", "f(")
+                .map(|declaration| declaration.ret_weak_ptr_to_const),
+            Some(false)
+        );
+        assert_eq!(
+            scan_function_named("struct A { bool operator==(wchar_t); };", "operator"),
+            None
+        );
+        assert_eq!(
+            scan_function_named(
+                "class A {\npublic:\n  A();\n  explicit A(int a);\n};",
+                "A(int"
+            )
+            .map(|declaration| declaration.explicit),
+            Some(true)
+        );
+        assert_eq!(
+            scan_function_named("struct A {\n  explicit A();\n  A(int a);\n};", "A(int")
+                .map(|declaration| declaration.explicit),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_scan_struct_definition() {
+        let source = blank_non_code(
+            indoc::indoc! {"
+                struct A : public B<wchar_t> {
+                  void f(wchar_t c) { c_ = c; }
+                  wchar_t c_, d[4];
+                  char32_t e = U'\\x1F600';
+                  int g;
+                  unsigned m : 3;
+                private:
+                  const char8_t* h;
+                  static wchar_t i;
+                  std::vector<wchar_t> j;
+                  struct C { wchar_t k; } l;
+                };
+            "}
+            .as_bytes(),
+        );
+        let declaration = scan_struct_definition(&source, 7).unwrap();
+        assert_eq!(
+            declaration.char_fields.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("c_".to_string(), CharType::WChar),
+                ("d".to_string(), CharType::WChar),
+                ("e".to_string(), CharType::Char32),
+                ("h".to_string(), CharType::Char8),
+            ]
+        );
+        assert_eq!(
+            declaration.non_const_fields.into_iter().collect::<Vec<_>>(),
+            vec!["c_", "d", "e", "g", "m", "j", "l"]
+        );
+    }
+
+    #[test]
+    fn test_scan_conversion_operators() {
+        let source = blank_non_code(
+            indoc::indoc! {"
+                class A {
+                  operator int() const;
+                public:
+                  explicit operator bool() const { return true; }
+                  operator std::string() = delete;
+                  operator std::string() const &&;
+                  operator std :: string () const noexcept;
+                  operator bool();
+                  template <typename T> operator T() const;
+                };
+            "}
+            .as_bytes(),
+        );
+        assert_eq!(
+            scan_struct_definition(&source, 6)
+                .unwrap()
+                .conversion_operators,
+            vec![ConversionOperator::Bool, ConversionOperator::String]
+        );
+        let source = blank_non_code(b"class B {\n  operator bool() const;\n};\n");
+        assert_eq!(
+            scan_struct_definition(&source, 6)
+                .unwrap()
+                .conversion_operators,
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_scan_declarations() {
+        let dir = std::env::temp_dir().join(format!("autocxx_declarations_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let header = dir.join("chars.h");
+        let source = "struct A {\n  wchar_t a;\n  int b;\n  void set(int x, char32_t c);\n};\n";
+        std::fs::write(&header, source).unwrap();
+        let header = header.to_str().unwrap();
+        let struct_offset = source.find('A').unwrap();
+        let fn_offset = source.find("set").unwrap();
+        let mut items: Vec<Item> = vec![parse_quote! {
+            pub mod root {
+                #[cpp_semantics(source_location(#header, 1, 8, #struct_offset))]
+                pub struct A {
+                    pub a: u32,
+                    pub b: ::std::os::raw::c_int,
+                }
+                extern "C" {
+                    #[cpp_semantics(source_location(#header, 4, 8, #fn_offset))]
+                    pub fn A_set(this: *mut root::A, x: ::std::os::raw::c_int, c: u32);
+                }
+            }
+        }];
+        scan_declarations(&mut items);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let Item::Mod(root) = &items[0] else {
+            panic!("expected a mod");
+        };
+        let root_items = &root.content.as_ref().unwrap().1;
+        let Item::Struct(a) = &root_items[0] else {
+            panic!("expected a struct");
+        };
+        assert_eq!(
+            a.fields
+                .iter()
+                .next()
+                .unwrap()
+                .ty
+                .to_token_stream()
+                .to_string(),
+            quote! { autocxx::c_wchar_t }.to_string()
+        );
+        let Item::ForeignMod(fm) = &root_items[1] else {
+            panic!("expected an extern block");
+        };
+        let syn::ForeignItem::Fn(fun) = &fm.items[0] else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            fun.sig.inputs.to_token_stream().to_string(),
+            quote! { this: *mut root::A, x: ::std::os::raw::c_int, c: autocxx::c_char32_t }
+                .to_string()
+        );
+    }
+}
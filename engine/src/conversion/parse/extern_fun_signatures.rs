@@ -8,21 +8,34 @@
 
 use indexmap::IndexSet as HashSet;
 
+use proc_macro2::Ident;
 use syn::{
-    spanned::Spanned, AngleBracketedGenericArguments, GenericArgument, PatType, PathArguments,
-    PathSegment, ReturnType, Signature, Type, TypePath, TypeReference,
+    spanned::Spanned, AngleBracketedGenericArguments, GenericArgument, PatType, Path,
+    PathArguments, PathSegment, ReturnType, Signature, Type, TypePath, TypeReference, TypeSlice,
 };
 
 use crate::{
-    conversion::convert_error::{ConvertErrorFromRust, LocatedConvertErrorFromRust},
-    types::QualifiedName,
+    conversion::{
+        convert_error::{ConvertErrorFromRust, LocatedConvertErrorFromRust},
+        type_helpers::{extract_option_box_inner_type, extract_result_ok_type},
+    },
+    types::{Namespace, QualifiedName},
 };
 
+/// Works out which types an `extern_rust_function` depends upon, or
+/// raises an appropriate error if its signature can't be supported.
+/// `mod_name` is the name of the mod generated by `include_cpp!`, so that
+/// we can recognize C++ types referred to as (for instance) `ffi::ns::A`.
 pub(super) fn assemble_extern_fun_deps(
     sig: &Signature,
+    mod_name: &Ident,
     file: &str,
 ) -> Result<Vec<QualifiedName>, LocatedConvertErrorFromRust> {
-    let mut deps = HashSet::new();
+    let mut assembler = DepsAssembler {
+        deps: HashSet::new(),
+        mod_name,
+        file,
+    };
     // It's possible that this will need to be implemented using TypeConverter
     // and the encountered_types field on its annotated results.
     // But the design of that code is intended to convert from C++ types
@@ -33,7 +46,17 @@ pub(super) fn assemble_extern_fun_deps(
     // meaningful errors about types which can't be supported within
     // extern_rust_fun.
     if let ReturnType::Type(_, ty) = &sig.output {
-        add_type_to_deps(ty, &mut deps, file)?;
+        // A Result is only supported as a return type, since cxx
+        // turns its error into a C++ exception. The error type may be
+        // anything which implements Display.
+        match extract_result_ok_type(ty) {
+            Some(Type::Tuple(tt)) if tt.elems.is_empty() => {}
+            Some(ok) if extract_option_box_inner_type(ok).is_some() => {
+                return Err(assembler.unsupported(ok))
+            }
+            Some(ok) => assembler.add_type(ok)?,
+            None => assembler.add_type(ty)?,
+        }
     }
     for input in &sig.inputs {
         match input {
@@ -44,157 +67,183 @@ pub(super) fn assemble_extern_fun_deps(
                     file,
                 ))
             }
-            syn::FnArg::Typed(PatType { ty, .. }) => add_type_to_deps(ty, &mut deps, file)?,
+            syn::FnArg::Typed(PatType { ty, .. }) => assembler.add_type(ty)?,
         }
     }
-    Ok(deps.into_iter().collect())
+    Ok(assembler.deps.into_iter().collect())
 }
 
-/// For all types within an extern_rust_function signature, add them to the deps
-/// hash, or raise an appropriate error.
-fn add_type_to_deps(
-    ty: &Type,
-    deps: &mut HashSet<QualifiedName>,
-    file: &str,
-) -> Result<(), LocatedConvertErrorFromRust> {
-    match ty {
-        Type::Reference(TypeReference {
-            mutability: Some(_),
-            ..
-        }) => {
-            return Err(LocatedConvertErrorFromRust::new(
-                ConvertErrorFromRust::PinnedReferencesRequiredForExternFun,
-                &ty.span(),
-                file,
-            ))
-        }
-        Type::Reference(TypeReference { elem, .. }) => match &**elem {
-            Type::Path(tp) => add_path_to_deps(tp, deps, file)?,
-            _ => {
-                return Err(LocatedConvertErrorFromRust::new(
-                    ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                    &ty.span(),
-                    file,
-                ))
-            }
-        },
-        Type::Path(tp) => {
-            if tp.path.segments.len() != 1 {
-                return Err(LocatedConvertErrorFromRust::new(
-                    ConvertErrorFromRust::NamespacesNotSupportedForExternFun,
-                    &tp.span(),
-                    file,
-                ));
-            }
-            if let Some(PathSegment {
-                ident,
-                arguments:
-                    PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }),
-            }) = tp.path.segments.last()
-            {
-                if ident == "Pin" {
-                    if args.len() != 1 {
-                        return Err(LocatedConvertErrorFromRust::new(
-                            ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                            &tp.span(),
-                            file,
-                        ));
-                    }
+struct DepsAssembler<'a> {
+    deps: HashSet<QualifiedName>,
+    mod_name: &'a Ident,
+    file: &'a str,
+}
 
-                    if let Some(GenericArgument::Type(Type::Reference(TypeReference {
-                        mutability: Some(_),
-                        elem,
-                        ..
-                    }))) = args.first()
-                    {
-                        if let Type::Path(tp) = &**elem {
-                            add_path_to_deps(tp, deps, file)?
+impl<'a> DepsAssembler<'a> {
+    fn unsupported(&self, span: &impl Spanned) -> LocatedConvertErrorFromRust {
+        LocatedConvertErrorFromRust::new(
+            ConvertErrorFromRust::UnsupportedTypeForExternFun,
+            &span.span(),
+            self.file,
+        )
+    }
+
+    /// For all types within an extern_rust_function signature, add them to the deps
+    /// hash, or raise an appropriate error.
+    fn add_type(&mut self, ty: &Type) -> Result<(), LocatedConvertErrorFromRust> {
+        match ty {
+            Type::Reference(TypeReference {
+                mutability: Some(_),
+                elem,
+                ..
+            }) => match &**elem {
+                Type::Slice(TypeSlice { elem, .. }) => self.add_slice_element_type(elem)?,
+                _ => {
+                    return Err(LocatedConvertErrorFromRust::new(
+                        ConvertErrorFromRust::PinnedReferencesRequiredForExternFun,
+                        &ty.span(),
+                        self.file,
+                    ))
+                }
+            },
+            Type::Reference(TypeReference { elem, .. }) => match &**elem {
+                Type::Path(tp) => self.add_path(tp)?,
+                Type::Slice(TypeSlice { elem, .. }) => self.add_slice_element_type(elem)?,
+                _ => return Err(self.unsupported(ty)),
+            },
+            Type::Path(tp) if tp.path.segments.len() != 1 => self.add_path(tp)?,
+            Type::Path(tp) => {
+                if let Some(PathSegment {
+                    ident,
+                    arguments:
+                        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }),
+                }) = tp.path.segments.last()
+                {
+                    if ident == "Pin" {
+                        if args.len() != 1 {
+                            return Err(self.unsupported(tp));
+                        }
+
+                        if let Some(GenericArgument::Type(Type::Reference(TypeReference {
+                            mutability: Some(_),
+                            elem,
+                            ..
+                        }))) = args.first()
+                        {
+                            if let Type::Path(tp) = &**elem {
+                                self.add_path(tp)?
+                            } else {
+                                return Err(self.unsupported(elem));
+                            }
                         } else {
-                            return Err(LocatedConvertErrorFromRust::new(
-                                ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                                &elem.span(),
-                                file,
-                            ));
+                            return Err(self.unsupported(ty));
+                        }
+                    } else if ident == "Box" || ident == "Vec" {
+                        if args.len() != 1 {
+                            return Err(self.unsupported(tp));
+                        }
+                        if let Some(GenericArgument::Type(Type::Path(tp))) = args.first() {
+                            self.add_path(tp)?
+                        } else {
+                            return Err(self.unsupported(ty));
+                        }
+                    } else if ident == "Option" {
+                        // Only Option<Box<T>> is supported, which is passed
+                        // to C++ as a nullable pointer.
+                        match extract_option_box_inner_type(ty) {
+                            Some(Type::Path(tp)) => self.add_path(tp)?,
+                            _ => return Err(self.unsupported(ty)),
                         }
                     } else {
-                        return Err(LocatedConvertErrorFromRust::new(
-                            ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                            &ty.span(),
-                            file,
-                        ));
-                    }
-                } else if ident == "Box" || ident == "Vec" {
-                    if args.len() != 1 {
-                        return Err(LocatedConvertErrorFromRust::new(
-                            ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                            &tp.span(),
-                            file,
-                        ));
-                    }
-                    if let Some(GenericArgument::Type(Type::Path(tp))) = args.first() {
-                        add_path_to_deps(tp, deps, file)?
-                    } else {
-                        return Err(LocatedConvertErrorFromRust::new(
-                            ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                            &ty.span(),
-                            file,
-                        ));
+                        return Err(self.unsupported(ident));
                     }
                 } else {
-                    return Err(LocatedConvertErrorFromRust::new(
-                        ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                        &ident.span(),
-                        file,
-                    ));
+                    self.add_path(tp)?
                 }
-            } else {
-                add_path_to_deps(tp, deps, file)?
             }
+            _ => return Err(self.unsupported(ty)),
+        };
+        Ok(())
+    }
+
+    /// Slices may contain only simple types, such as primitives.
+    fn add_slice_element_type(&mut self, elem: &Type) -> Result<(), LocatedConvertErrorFromRust> {
+        match elem {
+            Type::Path(tp) => self.add_path(tp),
+            _ => Err(self.unsupported(elem)),
+        }
+    }
+
+    fn add_path(&mut self, type_path: &TypePath) -> Result<(), LocatedConvertErrorFromRust> {
+        if type_path
+            .path
+            .segments
+            .iter()
+            .any(|seg| !seg.arguments.is_none())
+        {
+            return Err(self.unsupported(type_path));
         }
-        _ => {
+        let qn = match self.resolve_path(type_path) {
+            Some(qn) => qn,
+            None => {
+                return Err(LocatedConvertErrorFromRust::new(
+                    ConvertErrorFromRust::NamespacesNotSupportedForExternFun,
+                    &type_path.span(),
+                    self.file,
+                ))
+            }
+        };
+        if qn.get_final_item() == "Self" {
             return Err(LocatedConvertErrorFromRust::new(
-                ConvertErrorFromRust::UnsupportedTypeForExternFun,
-                &ty.span(),
-                file,
-            ))
+                ConvertErrorFromRust::ExplicitSelf,
+                &type_path.span(),
+                self.file,
+            ));
         }
-    };
-    Ok(())
-}
-
-fn add_path_to_deps(
-    type_path: &TypePath,
-    deps: &mut HashSet<QualifiedName>,
-    file: &str,
-) -> Result<(), LocatedConvertErrorFromRust> {
-    if let Some(PathSegment {
-        arguments: PathArguments::AngleBracketed(..) | PathArguments::Parenthesized(..),
-        ..
-    }) = type_path.path.segments.last()
-    {
-        return Err(LocatedConvertErrorFromRust::new(
-            ConvertErrorFromRust::UnsupportedTypeForExternFun,
-            &type_path.span(),
-            file,
-        ));
-    }
-    let qn = QualifiedName::from_type_path(type_path);
-    if !qn.get_namespace().is_empty() {
-        return Err(LocatedConvertErrorFromRust::new(
-            ConvertErrorFromRust::NamespacesNotSupportedForExternFun,
-            &type_path.span(),
-            file,
-        ));
+        self.deps.insert(qn);
+        Ok(())
     }
-    if qn.get_final_item() == "Self" {
-        return Err(LocatedConvertErrorFromRust::new(
-            ConvertErrorFromRust::ExplicitSelf,
-            &type_path.span(),
-            file,
-        ));
+
+    /// Works out the name of the type referred to by a path. This may be
+    /// a plain identifier; a path relative to this crate such as
+    /// `crate::a::Foo`, `super::Foo` or `self::a::Foo`; or a C++ type within
+    /// the `include_cpp!` mod such as `ffi::ns::Foo`. Other paths may refer to
+    /// types in other crates, which can't be used in a cxx::bridge.
+    fn resolve_path(&self, type_path: &TypePath) -> Option<QualifiedName> {
+        if type_path.qself.is_some() || type_path.path.leading_colon.is_some() {
+            return None;
+        }
+        let Path { segments, .. } = &type_path.path;
+        let final_seg = segments.last()?;
+        let mut mod_segs = segments.iter().take(segments.len() - 1).peekable();
+        let mut within_crate = false;
+        while let Some(seg) = mod_segs.peek() {
+            if seg.ident == "crate" || seg.ident == "super" || seg.ident == "self" {
+                within_crate = true;
+                mod_segs.next();
+            } else {
+                break;
+            }
+        }
+        let final_ident = final_seg.ident.clone();
+        match mod_segs.next() {
+            None => Some(QualifiedName::new(&Namespace::new(), final_ident.into())),
+            // A C++ type, so the rest of the path is its namespace.
+            Some(seg) if seg.ident == *self.mod_name => {
+                let ns = mod_segs.fold(Namespace::new(), |ns, seg| ns.push(seg.ident.to_string()));
+                Some(QualifiedName::new(&ns, final_ident.into()))
+            }
+            // Types within the cxx::bridge live in a single flat namespace
+            // (types from the C++ side, and extern_rust_types which are
+            // brought in by 'use' statements) so we refer to a Rust type by
+            // its final identifier wherever it lives within our crate.
+            // Discovery rejects two Rust types with the same identifier.
+            Some(_) if within_crate => {
+                Some(QualifiedName::new(&Namespace::new(), final_ident.into()))
+            }
+            Some(_) => None,
+        }
     }
-    deps.insert(qn);
-    Ok(())
 }
 
 #[cfg(test)]
@@ -209,13 +258,13 @@ mod tests {
             .cloned()
             .map(QualifiedName::new_from_cpp_name)
             .collect();
-        let result = assemble_extern_fun_deps(&sig, "").unwrap();
+        let result = assemble_extern_fun_deps(&sig, &parse_quote! { ffi }, "").unwrap();
         let actual_as_set: HashSet<QualifiedName> = result.into_iter().collect();
         assert_eq!(expected_as_set, actual_as_set);
     }
 
     fn run_test_expect_fail(sig: Signature) {
-        assert!(assemble_extern_fun_deps(&sig, "").is_err())
+        assert!(assemble_extern_fun_deps(&sig, &parse_quote! { ffi }, "").is_err())
     }
 
     #[test]
@@ -235,16 +284,53 @@ mod tests {
         run_test_expect_fail(parse_quote! { fn function() -> A::B});
         run_test_expect_fail(parse_quote! { fn function() -> &A::B});
         run_test_expect_fail(parse_quote! { fn function(a: ())});
-        run_test_expect_fail(parse_quote! { fn function(a: &[A])});
         run_test_expect_fail(parse_quote! { fn function(a: Bob<A>)});
         run_test_expect_fail(parse_quote! { fn function(a: Box<A, B>)});
         run_test_expect_fail(parse_quote! { fn function(a: a::Pin<&mut A>)});
         run_test_expect_fail(parse_quote! { fn function(a: Pin<&A>)});
+        run_test_expect_fail(parse_quote! { fn function(a: ::a::A)});
+        run_test_expect_fail(parse_quote! { fn function(a: std::a::A)});
+        run_test_expect_fail(parse_quote! { fn function(a: crate::a::A<B>)});
+        run_test_expect_fail(parse_quote! { fn function(a: crate::Self)});
+        run_test_expect_fail(parse_quote! { fn function(a: Result<A, B>)});
+        run_test_expect_fail(parse_quote! { fn function() -> Result<A>});
+        run_test_expect_fail(parse_quote! { fn function(a: Option<A>)});
+        run_test_expect_fail(parse_quote! { fn function() -> Result<Option<Box<A>>, B>});
+        run_test_expect_fail(parse_quote! { fn function(a: Option<Vec<A>>)});
+        run_test_expect_fail(parse_quote! { fn function(a: &mut str)});
+        run_test_expect_fail(parse_quote! { fn function(a: &[Vec<A>])});
+        run_test_expect_fail(parse_quote! { fn function(a: &other::ns::A)});
         run_test_expect_ok(parse_quote! { fn function(a: A, b: B)}, &["A", "B"]);
         run_test_expect_ok(parse_quote! { fn function(a: Box<A>)}, &["A"]);
         run_test_expect_ok(parse_quote! { fn function(a: Vec<A>)}, &["A"]);
         run_test_expect_ok(parse_quote! { fn function(a: &A)}, &["A"]);
         run_test_expect_ok(parse_quote! { fn function(a: Pin<&mut A>)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function(a: crate::a::A)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function(a: &super::A)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function(a: Box<self::a::A>)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function(a: Pin<&mut super::A>)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function() -> Result<A, B>}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function() -> Result<(), B::C>}, &[]);
+        run_test_expect_ok(parse_quote! { fn function(a: Option<Box<A>>)}, &["A"]);
+        run_test_expect_ok(parse_quote! { fn function() -> Option<Box<A>>}, &["A"]);
+        run_test_expect_ok(
+            parse_quote! { fn function(a: &str) -> String},
+            &["str", "String"],
+        );
+        run_test_expect_ok(
+            parse_quote! { fn function(a: &[u8], b: &mut [A])},
+            &["u8", "A"],
+        );
+        run_test_expect_ok(parse_quote! { fn function(a: Vec<String>)}, &["String"]);
+        run_test_expect_ok(parse_quote! { fn function(a: &ffi::A)}, &["A"]);
+        run_test_expect_ok(
+            parse_quote! { fn function(a: &crate::ffi::ns::A)},
+            &["ns::A"],
+        );
+        run_test_expect_ok(
+            parse_quote! { fn function(a: Pin<&mut ffi::ns::A>)},
+            &["ns::A"],
+        );
         run_test_expect_ok(
             parse_quote! { fn function(a: A, b: B) -> Box<C>},
             &["A", "B", "C"],
@@ -7,9 +7,13 @@
 // except according to those terms.
 
 mod bindgen_semantic_attributes;
+mod bitfields;
+mod declarations;
 mod extern_fun_signatures;
 mod parse_bindgen;
 mod parse_foreign_mod;
+mod unions;
 
 pub(crate) use bindgen_semantic_attributes::BindgenSemanticAttributes;
+pub(crate) use declarations::scan_declarations;
 pub(crate) use parse_bindgen::ParseBindgen;
@@ -11,7 +11,10 @@ use indexmap::set::IndexSet as HashSet;
 
 use crate::{
     conversion::{
-        api::{Api, ApiName, NullPhase, StructDetails, SubclassName, TypedefKind, UnanalyzedApi},
+        api::{
+            Api, ApiName, Bitfield, NullPhase, StructDetails, SubclassName, TypedefKind,
+            UnanalyzedApi,
+        },
         apivec::ApiVec,
         convert_error::LocatedConvertErrorFromRust,
         ConvertError, ConvertErrorFromCpp,
@@ -27,13 +30,17 @@ use crate::{
     types::validate_ident_ok_for_cxx,
 };
 use autocxx_parser::{IncludeCppConfig, RustPath};
-use syn::{parse_quote, Fields, Ident, Item, Type, TypePath, UseTree};
+use syn::{parse_quote, Fields, Ident, Item, ItemStruct, Type, TypePath, UseTree};
 
 use super::{
     super::utilities::generate_utilities, bindgen_semantic_attributes::BindgenSemanticAttributes,
 };
 
+use super::bitfields::{find_bitfields, replace_bitfield_units};
 use super::parse_foreign_mod::ParseForeignMod;
+use super::unions::{
+    find_anonymous_unions, is_anonymous_union, take_anonymous_unions, union_to_struct,
+};
 
 /// Parses a bindgen mod in order to understand the APIs within it.
 pub(crate) struct ParseBindgen<'a> {
@@ -109,10 +116,30 @@ impl<'a> ParseBindgen<'a> {
                 details: fun.clone(),
                 deps: super::extern_fun_signatures::assemble_extern_fun_deps(
                     &fun.sig,
+                    &self.config.get_mod_name(),
                     source_file_contents,
                 )?,
             })
         }
+        for rust_trait in &self.config.extern_rust_traits {
+            let holder = QualifiedName::new(&Namespace::new(), rust_trait.holder().into());
+            let mut deps = HashSet::new();
+            for method in &rust_trait.methods {
+                deps.extend(super::extern_fun_signatures::assemble_extern_fun_deps(
+                    &method.sig,
+                    &self.config.get_mod_name(),
+                    source_file_contents,
+                )?);
+            }
+            // Each method's receiver refers to the holder, which is the
+            // type this API itself generates.
+            deps.remove(&holder);
+            self.apis.push(Api::RustTrait {
+                name: ApiName::new_from_qualified_name(holder),
+                details: rust_trait.clone(),
+                deps: deps.into_iter().collect(),
+            })
+        }
         let unique_rust_types: HashSet<&RustPath> = self.config.rust_types.iter().collect();
         self.apis.extend(unique_rust_types.into_iter().map(|path| {
             let id = path.get_final_ident();
@@ -189,10 +216,18 @@ impl<'a> ParseBindgen<'a> {
         // This object maintains some state specific to this namespace, i.e.
         // this particular mod.
         let mut mod_converter = ParseForeignMod::new(ns.clone());
+        let mut bitfields = find_bitfields(&items);
+        let mut anonymous_unions = find_anonymous_unions(&items);
         let mut more_apis = ApiVec::new();
         for item in items {
             report_any_error(&ns, &mut more_apis, || {
-                self.parse_item(item, &mut mod_converter, &ns)
+                self.parse_item(
+                    item,
+                    &mut mod_converter,
+                    &mut bitfields,
+                    &mut anonymous_unions,
+                    &ns,
+                )
             });
         }
         self.apis.append(&mut more_apis);
@@ -203,6 +238,8 @@ impl<'a> ParseBindgen<'a> {
         &mut self,
         item: Item,
         mod_converter: &mut ParseForeignMod,
+        bitfields: &mut HashMap<Ident, Vec<Bitfield>>,
+        anonymous_unions: &mut HashMap<Ident, ItemStruct>,
         ns: &Namespace,
     ) -> Result<(), ConvertErrorWithContext> {
         match item {
@@ -244,12 +281,19 @@ impl<'a> ParseBindgen<'a> {
                     let has_rvalue_reference_fields = s.fields.iter().any(|f| {
                         BindgenSemanticAttributes::new(&f.attrs).has_attr("rvalue_reference")
                     });
+                    let mut s = s;
+                    replace_bitfield_units(&mut s);
                     Some(UnanalyzedApi::Struct {
                         name,
                         details: Box::new(StructDetails {
                             layout: annotations.get_layout(),
+                            bitfields: bitfields.swap_remove(&s.ident).unwrap_or_default(),
+                            anonymous_unions: take_anonymous_unions(&s, anonymous_unions),
                             item: s.into(),
                             has_rvalue_reference_fields,
+                            is_union: false,
+                            conversion_operators: annotations.get_conversion_operators(),
+                            non_const_fields: annotations.get_non_const_fields(),
                         }),
                         analysis: (),
                     })
@@ -261,6 +305,34 @@ impl<'a> ParseBindgen<'a> {
                 }
                 Ok(())
             }
+            Item::Union(u) => {
+                if is_anonymous_union(&u) {
+                    // Handled as part of the struct which contains it.
+                    return Ok(());
+                }
+                let annotations = BindgenSemanticAttributes::new(&u.attrs);
+                let name = api_name_qualified(ns, u.ident.clone(), &annotations)?;
+                annotations.check_for_fatal_attrs(&u.ident)?;
+                let s = union_to_struct(u);
+                let api = UnanalyzedApi::Struct {
+                    name,
+                    details: Box::new(StructDetails {
+                        layout: annotations.get_layout(),
+                        bitfields: bitfields.swap_remove(&s.ident).unwrap_or_default(),
+                        anonymous_unions: take_anonymous_unions(&s, anonymous_unions),
+                        item: s.into(),
+                        has_rvalue_reference_fields: false,
+                        is_union: true,
+                        conversion_operators: Vec::new(),
+                        non_const_fields: HashSet::new(),
+                    }),
+                    analysis: (),
+                };
+                if !self.config.is_on_blocklist(&api.name().to_cpp_name()) {
+                    self.apis.push(api);
+                }
+                Ok(())
+            }
             Item::Enum(e) => {
                 let annotations = BindgenSemanticAttributes::new(&e.attrs);
                 let api = UnanalyzedApi::Enum {
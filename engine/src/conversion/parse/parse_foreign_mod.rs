@@ -84,6 +84,8 @@ impl ParseForeignMod {
                     unused_template_param: annotations
                         .has_attr("incomprehensible_param_in_arg_or_return"),
                     references: annotations.get_reference_parameters_and_return(),
+                    returns_weak_ptr_to_const: annotations.has_attr("ret_type_weak_ptr_to_const"),
+                    is_explicit: annotations.has_attr("explicit"),
                     original_name: annotations.get_original_name(),
                     synthesized_this_type: None,
                     add_to_trait: None,
@@ -0,0 +1,160 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+
+use syn::{Fields, Item, ItemStruct, ItemUnion, Token, Type};
+
+use crate::conversion::{api::AnonymousUnion, type_helpers::extract_manually_drop_inner_type};
+
+use super::bitfields::replace_bitfield_units;
+
+/// Types which bindgen may use for union members and which are `Copy`,
+/// so don't need to be wrapped in `ManuallyDrop`.
+const COPY_TYPES: &[&str] = &[
+    "bool",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+    "c_char",
+    "c_schar",
+    "c_uchar",
+    "c_short",
+    "c_ushort",
+    "c_int",
+    "c_uint",
+    "c_long",
+    "c_ulong",
+    "c_longlong",
+    "c_ulonglong",
+];
+
+fn is_copy(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(_) => true,
+        Type::Array(arr) => is_copy(&arr.elem),
+        Type::Path(typ) => typ
+            .path
+            .segments
+            .last()
+            .map(|seg| seg.arguments.is_empty() && COPY_TYPES.iter().any(|t| seg.ident == t))
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
+
+/// Represent a union as a struct with the same fields, so that we can
+/// analyze it in the same way as any other struct. bindgen wraps every
+/// union member in `ManuallyDrop`; we remove that for members of primitive
+/// types, because Rust allows those within unions as they are.
+pub(super) fn union_to_struct(u: ItemUnion) -> ItemStruct {
+    let mut fields = u.fields;
+    for f in fields.named.iter_mut() {
+        if let Some(inner) = extract_manually_drop_inner_type(&f.ty) {
+            if is_copy(inner) {
+                f.ty = inner.clone();
+            }
+        }
+    }
+    let mut s = ItemStruct {
+        attrs: u.attrs,
+        vis: u.vis,
+        struct_token: Token![struct](u.union_token.span),
+        ident: u.ident,
+        generics: u.generics,
+        fields: Fields::Named(fields),
+        semi_token: None,
+    };
+    replace_bitfield_units(&mut s);
+    s
+}
+
+/// bindgen synthesizes a name for each anonymous union within a struct.
+/// C++ has no name for such a union, so we can't tell cxx about it.
+pub(super) fn is_anonymous_union(u: &ItemUnion) -> bool {
+    u.ident.to_string().contains("__bindgen_ty_")
+}
+
+/// Find all the anonymous unions within the items of a mod, keyed by
+/// the name bindgen has given them.
+pub(super) fn find_anonymous_unions(items: &[Item]) -> HashMap<syn::Ident, ItemStruct> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Union(u) if is_anonymous_union(u) => {
+                Some((u.ident.clone(), union_to_struct(u.clone())))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Find any fields of a struct which hold anonymous unions.
+pub(super) fn take_anonymous_unions(
+    s: &ItemStruct,
+    anonymous_unions: &mut HashMap<syn::Ident, ItemStruct>,
+) -> Vec<AnonymousUnion> {
+    s.fields
+        .iter()
+        .filter_map(|f| {
+            let field = f.ident.as_ref()?;
+            let ty_id = match &f.ty {
+                Type::Path(typ) => &typ.path.segments.last()?.ident,
+                _ => return None,
+            };
+            anonymous_unions
+                .swap_remove(ty_id)
+                .map(|item| AnonymousUnion {
+                    field: field.clone().into(),
+                    item: item.into(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::union_to_struct;
+    use quote::ToTokens;
+    use syn::{parse_quote, ItemStruct, ItemUnion};
+
+    #[test]
+    fn test_union_to_struct() {
+        let u: ItemUnion = parse_quote! {
+            #[repr(C)]
+            pub union U {
+                pub a: ::std::mem::ManuallyDrop<::std::os::raw::c_int>,
+                pub b: ::std::mem::ManuallyDrop<*mut root::B>,
+                pub c: ::std::mem::ManuallyDrop<root::C>,
+            }
+        };
+        let expected: ItemStruct = parse_quote! {
+            #[repr(C)]
+            pub struct U {
+                pub a: ::std::os::raw::c_int,
+                pub b: *mut root::B,
+                pub c: ::std::mem::ManuallyDrop<root::C>,
+            }
+        };
+        assert_eq!(
+            union_to_struct(u).to_token_stream().to_string(),
+            expected.to_token_stream().to_string()
+        );
+    }
+}
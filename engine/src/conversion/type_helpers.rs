@@ -56,3 +56,80 @@ fn is_pin(tp: &TypePath) -> bool {
     }
     true
 }
+
+/// Looks in a `Result<T, E>` and returns the `T` if it's found.
+/// `extern_rust_function`s may return such a type, in which case the
+/// error is thrown as a C++ exception.
+pub(crate) fn extract_result_ok_type(ty: &Type) -> Option<&Type> {
+    match generic_type_args(ty, "Result")?.as_slice() {
+        [ok, _] => Some(ok),
+        _ => None,
+    }
+}
+
+/// Looks in an `Option<Box<T>>` and returns the `T` if it's found.
+/// `extern_rust_function`s pass such types to and from C++ as a possibly
+/// null owning pointer.
+pub(crate) fn extract_option_box_inner_type(ty: &Type) -> Option<&Type> {
+    match generic_type_args(ty, "Option")?.as_slice() {
+        [boxed] => match generic_type_args(boxed, "Box")?.as_slice() {
+            [inner] => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Looks in a `std::mem::ManuallyDrop<T>` and returns the `T` if it's found.
+/// bindgen wraps the members of unions in this.
+pub(crate) fn extract_manually_drop_inner_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            let mut segs = path.segments.iter();
+            match (segs.next(), segs.next(), segs.next(), segs.next()) {
+                (Some(krate), Some(module), Some(last), None)
+                    if (krate.ident == "std" || krate.ident == "core")
+                        && module.ident == "mem"
+                        && last.ident == "ManuallyDrop" =>
+                {
+                    match &last.arguments {
+                        PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                            args,
+                            ..
+                        }) if args.len() == 1 => match args.first() {
+                            Some(GenericArgument::Type(inner)) => Some(inner),
+                            _ => None,
+                        },
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If this type is a single identifier `name` with generic type
+/// arguments, e.g. `Box<A>` when `name` is "Box", returns those arguments.
+fn generic_type_args<'a>(ty: &'a Type, name: &str) -> Option<Vec<&'a Type>> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) if path.segments.len() == 1 => {
+            match path.segments.first() {
+                Some(PathSegment {
+                    ident,
+                    arguments:
+                        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }),
+                }) if ident == name => args
+                    .iter()
+                    .map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -25,8 +25,43 @@ enum Behavior {
     CByValueVecSafe,
     CVariableLengthByValue,
     CVoid,
-    CChar16,
+    CCharType,
     RustContainerByValueSafe,
+    Chrono(ChronoType),
+    AssociativeContainer(AssociativeContainerType),
+}
+
+/// A `std::chrono` type which we convert to and from a Rust equivalent
+/// by passing a count of nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChronoType {
+    Duration,
+    TimePoint,
+}
+
+/// A C++ associative container. Each instantiation becomes an opaque type
+/// implementing one of the traits in `autocxx::map`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AssociativeContainerType {
+    Map,
+    UnorderedMap,
+    Set,
+}
+
+impl AssociativeContainerType {
+    /// The standard header which defines this container.
+    pub(crate) fn header(&self) -> &'static str {
+        match self {
+            AssociativeContainerType::Map => "map",
+            AssociativeContainerType::UnorderedMap => "unordered_map",
+            AssociativeContainerType::Set => "set",
+        }
+    }
+
+    /// Whether this maps keys to values, rather than just holding keys.
+    pub(crate) fn has_values(&self) -> bool {
+        !matches!(self, AssociativeContainerType::Set)
+    }
 }
 
 /// Details about known special types, mostly primitives.
@@ -92,6 +127,58 @@ impl TypeDetails {
                     self.cpp_name, templating, cxx_name, payload
                 ))
             }
+            Behavior::Chrono(chrono_type) => {
+                // bindgen doesn't need to see the real types, but as they
+                // may be embedded in structs, the replacements must have the
+                // same layout: a duration holds just a count of its `Rep`, and
+                // a time point holds just a duration.
+                let (templating, name, payload) = match chrono_type {
+                    ChronoType::Duration => (
+                        "template<typename Rep, typename Period>",
+                        "autocxx_chrono_duration_replacement",
+                        "Rep count",
+                    ),
+                    ChronoType::TimePoint => (
+                        "template<typename Clock, typename Duration>",
+                        "autocxx_chrono_time_point_replacement",
+                        "Duration since_epoch",
+                    ),
+                };
+                Some(format!(
+                    indoc! {"
+                    /**
+                    * <div rustbindgen=\"true\" replaces=\"{}\">
+                    */
+                    {} class {} {{
+                        {};
+                    }};
+                    "},
+                    self.cpp_name, templating, name, payload
+                ))
+            }
+            Behavior::AssociativeContainer(container_type) => {
+                // As with the cxx containers, we only need bindgen to tell
+                // us the key and value types.
+                let (templating, payload) = if container_type.has_values() {
+                    ("template<typename K, typename V>", "K* key; V* value;")
+                } else {
+                    ("template<typename K>", "K* key;")
+                };
+                Some(format!(
+                    indoc! {"
+                    /**
+                    * <div rustbindgen=\"true\" replaces=\"{}\">
+                    */
+                    {} class autocxx_{}_replacement {{
+                        {}
+                    }};
+                    "},
+                    self.cpp_name,
+                    templating,
+                    container_type.header(),
+                    payload
+                ))
+            }
             _ => None,
         }
     }
@@ -207,11 +294,13 @@ impl TypeDatabase {
                         | Behavior::CByValueVecSafe
                         | Behavior::CByValue
                         | Behavior::CVariableLengthByValue
-                        | Behavior::CChar16
+                        | Behavior::CCharType
                         | Behavior::RustContainerByValueSafe => true,
-                        Behavior::CxxString | Behavior::CxxContainerVector | Behavior::CVoid => {
-                            false
-                        }
+                        Behavior::CxxString
+                        | Behavior::CxxContainerVector
+                        | Behavior::CVoid
+                        | Behavior::Chrono(_)
+                        | Behavior::AssociativeContainer(_) => false,
                     },
                 )
             })
@@ -288,7 +377,7 @@ impl TypeDatabase {
             .map(|td| {
                 matches!(
                     td.behavior,
-                    Behavior::CVariableLengthByValue | Behavior::CVoid | Behavior::CChar16
+                    Behavior::CVariableLengthByValue | Behavior::CVoid | Behavior::CCharType
                 )
             })
             .unwrap_or(false)
@@ -325,6 +414,108 @@ impl TypeDatabase {
             .unwrap_or(true)
     }
 
+    /// Whether this can be the pointee of a `std::shared_ptr` or
+    /// `std::weak_ptr`. Unlike `std::unique_ptr`, cxx supports these
+    /// for the fixed-width primitive types.
+    pub(crate) fn permissible_within_shared_ptr(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| {
+                matches!(
+                    x.behavior,
+                    Behavior::CxxString
+                        | Behavior::CxxContainerVector
+                        | Behavior::CByValue
+                        | Behavior::CByValueVecSafe
+                )
+            })
+            .unwrap_or(true)
+    }
+
+    /// Whether this is a C type which cxx can't hold within the given
+    /// container (a `std::vector` or smart pointer), but which could be
+    /// held by a wrapper type generated for each `include_cpp!` block.
+    pub(crate) fn needs_container_element(
+        &self,
+        container: &QualifiedName,
+        ty: &QualifiedName,
+    ) -> bool {
+        let Some(x) = self.get(ty) else {
+            return false;
+        };
+        match self.cxx_generic_behavior(container) {
+            // There's no way to handle std::vector<bool>, which is special.
+            CxxGenericType::CppVector => match x.behavior {
+                Behavior::CVariableLengthByValue => true,
+                Behavior::CByValue => x.rs_name != "bool",
+                _ => false,
+            },
+            CxxGenericType::CppPtr if self.is_shared_ptr(container) => {
+                matches!(x.behavior, Behavior::CVariableLengthByValue)
+            }
+            CxxGenericType::CppPtr => matches!(
+                x.behavior,
+                Behavior::CVariableLengthByValue | Behavior::CByValue | Behavior::CByValueVecSafe
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether this is a `std::unique_ptr`. cxx supports only those with the
+    /// default deleter; we represent those with any other deleter ourselves.
+    pub(crate) fn is_unique_ptr(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| x.rs_name == "cxx::UniquePtr")
+            .unwrap_or_default()
+    }
+
+    /// Whether this is a `std::shared_ptr` or `std::weak_ptr`. bindgen
+    /// doesn't tell us whether the pointee of such a pointer is `const`,
+    /// so we always represent it as non-`const`, and the C++ we generate
+    /// stops pointers to `const` objects reaching C++ which expects
+    /// pointers to non-`const`.
+    pub(crate) fn is_shared_ptr(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| matches!(x.rs_name.as_str(), "cxx::SharedPtr" | "cxx::WeakPtr"))
+            .unwrap_or(false)
+    }
+
+    /// Whether this can be an element of a `std::pair` or `std::tuple`
+    /// which we pass by value. Such elements must be trivial in C++.
+    pub(crate) fn permissible_within_tuple(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| {
+                matches!(
+                    x.behavior,
+                    Behavior::CByValue
+                        | Behavior::CByValueVecSafe
+                        | Behavior::CVariableLengthByValue
+                        | Behavior::CCharType
+                )
+            })
+            .unwrap_or(true)
+    }
+
+    /// Whether this is a `std::chrono` type which we convert to a Rust
+    /// equivalent, and if so which.
+    pub(crate) fn chrono_type(&self, ty: &QualifiedName) -> Option<ChronoType> {
+        self.get(ty).and_then(|x| match x.behavior {
+            Behavior::Chrono(chrono_type) => Some(chrono_type),
+            _ => None,
+        })
+    }
+
+    /// Whether this is a C++ associative container for which we generate
+    /// an opaque type with methods, and if so which.
+    pub(crate) fn associative_container_type(
+        &self,
+        ty: &QualifiedName,
+    ) -> Option<AssociativeContainerType> {
+        self.get(ty).and_then(|x| match x.behavior {
+            Behavior::AssociativeContainer(container_type) => Some(container_type),
+            _ => None,
+        })
+    }
+
     pub(crate) fn conflicts_with_built_in_type(&self, ty: &QualifiedName) -> bool {
         self.get(ty).is_some()
     }
@@ -355,7 +546,10 @@ impl TypeDatabase {
             .filter(|tn| {
                 !matches!(
                     self.get(tn).unwrap().behavior,
-                    Behavior::CxxString | Behavior::CxxContainerVector
+                    Behavior::CxxString
+                        | Behavior::CxxContainerVector
+                        | Behavior::Chrono(_)
+                        | Behavior::AssociativeContainer(_)
                 )
             })
             .cloned()
@@ -380,6 +574,30 @@ fn create_type_database() -> TypeDatabase {
         false,
         true,
     ));
+    db.insert(TypeDetails::new(
+        "autocxx::CxxMap",
+        "std::map",
+        Behavior::AssociativeContainer(AssociativeContainerType::Map),
+        None,
+        true,
+        true,
+    ));
+    db.insert(TypeDetails::new(
+        "autocxx::CxxUnorderedMap",
+        "std::unordered_map",
+        Behavior::AssociativeContainer(AssociativeContainerType::UnorderedMap),
+        None,
+        true,
+        true,
+    ));
+    db.insert(TypeDetails::new(
+        "autocxx::CxxSet",
+        "std::set",
+        Behavior::AssociativeContainer(AssociativeContainerType::Set),
+        None,
+        true,
+        true,
+    ));
     db.insert(TypeDetails::new(
         "cxx::SharedPtr",
         "std::shared_ptr",
@@ -468,6 +686,23 @@ fn create_type_database() -> TypeDatabase {
         true,
     ));
 
+    db.insert(TypeDetails::new(
+        "::std::time::Duration",
+        "std::chrono::duration",
+        Behavior::Chrono(ChronoType::Duration),
+        None,
+        true,
+        true,
+    ));
+    db.insert(TypeDetails::new(
+        "autocxx::chrono::TimePoint",
+        "std::chrono::time_point",
+        Behavior::Chrono(ChronoType::TimePoint),
+        None,
+        true,
+        true,
+    ));
+
     db.insert(TypeDetails::new(
         "core::pin::Pin",
         "Pin",
@@ -502,6 +737,25 @@ fn create_type_database() -> TypeDatabase {
     insert_ctype("short");
     insert_ctype("long long");
 
+    // cxx has no support for 128-bit integers, so we wrap them in the
+    // same way as the variable length types above.
+    db.insert(TypeDetails::new(
+        "autocxx::c_int128",
+        "__int128",
+        Behavior::CVariableLengthByValue,
+        Some("i128".into()),
+        true,
+        true,
+    ));
+    db.insert(TypeDetails::new(
+        "autocxx::c_uint128",
+        "unsigned __int128",
+        Behavior::CVariableLengthByValue,
+        Some("u128".into()),
+        true,
+        true,
+    ));
+
     db.insert(TypeDetails::new(
         "f32",
         "float",
@@ -542,13 +796,15 @@ fn create_type_database() -> TypeDatabase {
         false,
         false,
     ));
-    db.insert(TypeDetails::new(
-        "autocxx::c_char16_t",
-        "char16_t",
-        Behavior::CChar16,
-        Some("c_char16_t".into()),
-        false,
-        false,
-    ));
+    for char_type in ["char8_t", "char16_t", "char32_t", "wchar_t"] {
+        db.insert(TypeDetails::new(
+            format!("autocxx::c_{char_type}"),
+            char_type,
+            Behavior::CCharType,
+            Some(format!("c_{char_type}")),
+            false,
+            false,
+        ));
+    }
     db
 }
@@ -166,6 +166,13 @@ pub trait RebuildDependencyRecorder: std::fmt::Debug {
     /// Records that this autocxx build depends on the given
     /// header file. Full paths will be provided.
     fn record_header_file_dependency(&self, filename: &str);
+
+    /// Records that this autocxx build depends on the given Rust file,
+    /// which was read to find the contents of an out-of-line `mod`.
+    /// By default, this is recorded just like a header file.
+    fn record_rust_file_dependency(&self, filename: &str) {
+        self.record_header_file_dependency(filename)
+    }
 }
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
@@ -350,6 +357,10 @@ impl IncludeCppEngine {
             .cpp_semantic_attributes(true)
             .represent_cxx_operators(true)
             .use_distinct_char16_t(true)
+            // Without this, bindgen represents unions (whose members can't
+            // be shown to be Copy, since we don't derive Copy) using
+            // its own __BindgenUnionField type, which we can't pass on.
+            .default_non_copy_union_style(bindgen::NonCopyUnionStyle::ManuallyDrop)
             .layout_tests(false); // TODO revisit later
         for item in known_types().get_initial_blocklist() {
             builder = builder.blocklist_item(item);
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::ast_discoverer::{Discoveries, DiscoveryErr};
+use crate::ast_discoverer::{crate_mod_path_for_file, Discoveries, DiscoveryErr, ModDirs};
 use crate::output_generators::RsOutput;
 use crate::{
     cxxbridge::CxxBridge, Error as EngineError, GeneratedCpp, IncludeCppEngine,
@@ -67,19 +67,27 @@ pub fn parse_file<P1: AsRef<Path>>(
     auto_allowlist: bool,
 ) -> Result<ParsedFile, ParseError> {
     let mut source_code = String::new();
-    let mut file = std::fs::File::open(rs_file).map_err(ParseError::FileOpen)?;
+    let mut file = std::fs::File::open(rs_file.as_ref()).map_err(ParseError::FileOpen)?;
     file.read_to_string(&mut source_code)
         .map_err(ParseError::FileRead)?;
     proc_macro2::fallback::force();
     let source = syn::parse_file(&source_code)
         .map_err(|e| ParseError::Syntax(LocatedSynError::new(e, &source_code)))?;
-    parse_file_contents(source, auto_allowlist, &source_code)
+    parse_file_contents(
+        source,
+        auto_allowlist,
+        &source_code,
+        ModDirs::for_file(rs_file.as_ref()),
+        crate_mod_path_for_file(rs_file.as_ref()),
+    )
 }
 
 fn parse_file_contents(
     source: syn::File,
     auto_allowlist: bool,
     file_contents: &str,
+    mod_dirs: ModDirs,
+    file_mod_path: RustPath,
 ) -> Result<ParsedFile, ParseError> {
     #[derive(Default)]
     struct State {
@@ -93,7 +101,8 @@ fn parse_file_contents(
         fn parse_item(
             &mut self,
             item: Item,
-            mod_path: Option<RustPath>,
+            mod_path: &RustPath,
+            mod_dirs: &ModDirs,
             file_contents: Rc<String>,
         ) -> Result<(), ParseError> {
             let result = match item {
@@ -118,29 +127,29 @@ fn parse_file_contents(
                 {
                     Segment::Cxx(CxxBridge::from(itm))
                 }
+                Item::Mod(ref itm) if itm.content.is_none() => {
+                    // Out-of-line mods are in different files, so we don't
+                    // look for include_cpp! within them, but we do look
+                    // for extern_rust_functions and the like.
+                    self.discoveries
+                        .search_item(&item, Some(mod_path.clone()), Some(mod_dirs))
+                        .map_err(ParseError::Discovery)?;
+                    Segment::Other
+                }
                 Item::Mod(itm) => {
-                    if let Some((_, items)) = itm.content {
-                        let mut mod_state = State {
-                            auto_allowlist: self.auto_allowlist,
-                            ..Default::default()
-                        };
-                        let mod_path = match &mod_path {
-                            None => RustPath::new_from_ident(itm.ident.clone()),
-                            Some(mod_path) => mod_path.append(itm.ident.clone()),
-                        };
-                        for item in items {
-                            mod_state.parse_item(
-                                item,
-                                Some(mod_path.clone()),
-                                file_contents.clone(),
-                            )?
-                        }
-                        self.extra_superclasses.extend(mod_state.extra_superclasses);
-                        self.discoveries.extend(mod_state.discoveries);
-                        Segment::Mod(mod_state.results)
-                    } else {
-                        Segment::Other
+                    let (_, items) = itm.content.unwrap();
+                    let mut mod_state = State {
+                        auto_allowlist: self.auto_allowlist,
+                        ..Default::default()
+                    };
+                    let mod_dirs = mod_dirs.for_inline_mod(&itm.ident);
+                    let mod_path = mod_path.append(itm.ident.clone());
+                    for item in items {
+                        mod_state.parse_item(item, &mod_path, &mod_dirs, file_contents.clone())?
                     }
+                    self.extra_superclasses.extend(mod_state.extra_superclasses);
+                    self.discoveries.extend(mod_state.discoveries);
+                    Segment::Mod(mod_state.results)
                 }
                 Item::Struct(ref its) => {
                     let attrs = &its.attrs;
@@ -178,13 +187,13 @@ fn parse_file_contents(
                         }
                     }
                     self.discoveries
-                        .search_item(&item, mod_path)
+                        .search_item(&item, Some(mod_path.clone()), Some(mod_dirs))
                         .map_err(ParseError::Discovery)?;
                     Segment::Other
                 }
                 _ => {
                     self.discoveries
-                        .search_item(&item, mod_path)
+                        .search_item(&item, Some(mod_path.clone()), Some(mod_dirs))
                         .map_err(ParseError::Discovery)?;
                     Segment::Other
                 }
@@ -198,7 +207,7 @@ fn parse_file_contents(
         ..Default::default()
     };
     for item in source.items {
-        state.parse_item(item, None, file_contents.clone())?
+        state.parse_item(item, &file_mod_path, &mod_dirs, file_contents.clone())?
     }
     let State {
         auto_allowlist,
@@ -206,6 +215,9 @@ fn parse_file_contents(
         mut extra_superclasses,
         mut discoveries,
     } = state;
+    discoveries
+        .check_names_unique()
+        .map_err(ParseError::Discovery)?;
 
     let must_handle_discovered_things = discoveries.found_rust()
         || !extra_superclasses.is_empty()
@@ -248,6 +260,10 @@ fn parse_file_contents(
                     .config_mut()
                     .rust_types
                     .append(&mut discoveries.extern_rust_types);
+                engine
+                    .config_mut()
+                    .extern_rust_traits
+                    .append(&mut discoveries.extern_rust_traits);
             }
         }
         if autocxx_seg_iterator.next().is_some() {
@@ -261,14 +277,15 @@ fn parse_file_contents(
     for seg in autocxx_seg_iterator {
         seg.config.confirm_complete();
     }
-    Ok(ParsedFile(results))
+    Ok(ParsedFile(results, discoveries.mod_files))
 }
 
 /// A Rust file parsed by autocxx. May contain zero or more autocxx 'engines',
 /// i.e. the `IncludeCpp` class, corresponding to zero or more include_cpp
 /// macros within this file. Also contains `syn::Item` structures for all
 /// the rest of the Rust code, such that it can be reconstituted if necessary.
-pub struct ParsedFile(Vec<Segment>);
+/// Also lists any other Rust files which were read for out-of-line mods.
+pub struct ParsedFile(Vec<Segment>, Vec<PathBuf>);
 
 #[allow(clippy::large_enum_variant)]
 enum Segment {
@@ -378,6 +395,11 @@ impl ParsedFile {
         let mut mods_found = HashSet::new();
         let inner_dep_recorder: Option<Rc<dyn RebuildDependencyRecorder>> =
             dep_recorder.map(Rc::from);
+        if let Some(inner_dep_recorder) = &inner_dep_recorder {
+            for mod_file in &self.1 {
+                inner_dep_recorder.record_rust_file_dependency(&mod_file.to_string_lossy());
+            }
+        }
         for include_cpp in self.get_autocxxes_mut() {
             #[allow(clippy::manual_map)] // because of dyn shenanigans
             let dep_recorder: Option<Box<dyn RebuildDependencyRecorder>> = match &inner_dep_recorder
@@ -420,4 +442,8 @@ impl RebuildDependencyRecorder for CompositeDepRecorder {
     fn record_header_file_dependency(&self, filename: &str) {
         self.0.record_header_file_dependency(filename);
     }
+
+    fn record_rust_file_dependency(&self, filename: &str) {
+        self.0.record_rust_file_dependency(filename);
+    }
 }
@@ -236,9 +236,9 @@ impl std::fmt::Debug for QualifiedName {
 /// cxx.
 #[derive(Error, Clone, Debug)]
 pub enum InvalidIdentError {
-    #[error("Union are not supported by autocxx (and their bindgen names have __ so are not acceptable to cxx)")]
+    #[error("This union could not be represented as a Rust union by bindgen (and bindgen's fallback union type has __ in its name so is not acceptable to cxx)")]
     Union,
-    #[error("Bitfields are not supported by autocxx (and their bindgen names have __ so are not acceptable to cxx)")]
+    #[error("bindgen's bitfield storage type is not used directly by autocxx (and its name has __ so is not acceptable to cxx); bitfields are instead exposed via accessor methods on POD structs")]
     Bitfield,
     #[error("Names containing __ are reserved by C++ so not acceptable to cxx")]
     TooManyUnderscores,
@@ -150,7 +150,6 @@ fn test_nested_module() {
 }
 
 #[test]
-#[ignore] // https://github.com/google/autocxx/issues/681
 #[cfg(target_pointer_width = "64")]
 fn test_return_big_ints() {
     let cxx = indoc! {"
@@ -172,25 +171,40 @@ fn test_return_big_ints() {
         inline __int128 give_i128() {
             return 5;
         }
+        inline unsigned __int128 give_u128() {
+            return static_cast<unsigned __int128>(1) << 100;
+        }
+        inline bool take_i128(__int128 a) {
+            return a == -5;
+        }
     "};
     let rs = quote! {
         assert_eq!(ffi::give_u32(), 5);
         assert_eq!(ffi::give_u64(), 5);
         assert_eq!(ffi::give_i32(), 5);
         assert_eq!(ffi::give_i64(), 5);
-        assert_eq!(ffi::give_i128(), 5);
+        assert_eq!(ffi::give_i128(), autocxx::c_int128(5));
+        assert_eq!(ffi::give_u128(), autocxx::c_uint128(1 << 100));
+        assert!(ffi::take_i128(autocxx::c_int128(-5)));
     };
     run_test(
         cxx,
         hdr,
         rs,
-        &["give_u32", "give_u64", "give_i32", "give_i64", "give_i128"],
+        &[
+            "give_u32",
+            "give_u64",
+            "give_i32",
+            "give_i64",
+            "give_i128",
+            "give_u128",
+            "take_i128",
+        ],
         &[],
     );
 }
 
 #[test]
-#[ignore] // because cxx doesn't support unique_ptrs to primitives.
 fn test_give_up_int() {
     let cxx = indoc! {"
         std::unique_ptr<uint32_t> give_up() {
@@ -203,13 +217,12 @@ fn test_give_up_int() {
         std::unique_ptr<uint32_t> give_up();
     "};
     let rs = quote! {
-        assert_eq!(ffi::give_up().as_ref().unwrap(), 12);
+        assert_eq!(*ffi::give_up().as_ref().unwrap(), 12u32);
     };
     run_test(cxx, hdr, rs, &["give_up"], &[]);
 }
 
 #[test]
-#[ignore] // because we don't yet implement UniquePtr etc. for autocxx::c_int and friends
 fn test_give_up_ctype() {
     let cxx = indoc! {"
         std::unique_ptr<int> give_up() {
@@ -221,11 +234,37 @@ fn test_give_up_ctype() {
         std::unique_ptr<int> give_up();
     "};
     let rs = quote! {
-        assert_eq!(ffi::give_up().as_ref().unwrap(), autocxx::c_int(12));
+        assert_eq!(*ffi::give_up().as_ref().unwrap(), autocxx::c_int(12));
     };
     run_test(cxx, hdr, rs, &["give_up"], &[]);
 }
 
+#[test]
+fn test_take_up_ctype() {
+    let cxx = indoc! {"
+        long take_up(std::unique_ptr<long> a) {
+            return *a + 1;
+        }
+        std::vector<unsigned short> give_shorts() {
+            return std::vector<unsigned short> { 3, 4 };
+        }
+    "};
+    let hdr = indoc! {"
+        #include <memory>
+        #include <vector>
+        long take_up(std::unique_ptr<long> a);
+        std::vector<unsigned short> give_shorts();
+    "};
+    let rs = quote! {
+        let a = cxx::UniquePtr::new(autocxx::c_long(12).into());
+        assert_eq!(ffi::take_up(a), autocxx::c_long(13));
+        let mut shorts = ffi::give_shorts();
+        shorts.pin_mut().push(autocxx::c_ushort(5).into());
+        assert_eq!(shorts.as_slice(), &[autocxx::c_ushort(3), autocxx::c_ushort(4), autocxx::c_ushort(5)]);
+    };
+    run_test(cxx, hdr, rs, &["take_up", "give_shorts"], &[]);
+}
+
 #[test]
 fn test_give_string_up() {
     let cxx = indoc! {"
@@ -408,6 +447,105 @@ fn test_give_pod_class_by_value() {
     run_test(cxx, hdr, rs, &["give_bob"], &["Bob"]);
 }
 
+#[test]
+fn test_pod_with_bitfields() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Flags {
+            uint32_t a : 3;
+            uint32_t b : 5;
+            bool c : 1;
+            uint32_t d;
+        };
+        inline Flags make_flags() {
+            Flags f;
+            f.a = 5;
+            f.b = 17;
+            f.c = true;
+            f.d = 42;
+            return f;
+        }
+        inline uint32_t sum_flags(Flags f) {
+            return f.a + f.b + (f.c ? 100 : 0) + f.d;
+        }
+    "};
+    let rs = quote! {
+        let mut f = ffi::make_flags();
+        assert_eq!(f.a(), 5);
+        assert_eq!(f.b(), 17);
+        assert!(f.c());
+        assert_eq!(f.d, 42);
+        f.set_a(2);
+        f.set_c(false);
+        assert_eq!(f.a(), 2);
+        assert_eq!(f.b(), 17);
+        assert_eq!(ffi::sum_flags(f), 2 + 17 + 42);
+    };
+    run_test("", hdr, rs, &["make_flags", "sum_flags"], &["Flags"]);
+}
+
+#[test]
+fn test_pod_union() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        union Value {
+            uint32_t i;
+            float f;
+            Point p;
+        };
+        inline Value make_value() {
+            Value v;
+            v.i = 3;
+            return v;
+        }
+        inline uint32_t get_y(Value v) {
+            return v.p.y;
+        }
+    "};
+    let rs = quote! {
+        let mut v = ffi::make_value();
+        assert_eq!(unsafe { v.i }, 3);
+        v.p = ::std::mem::ManuallyDrop::new(ffi::Point { x: 1, y: 2 });
+        assert_eq!(ffi::get_y(v), 2);
+    };
+    run_test("", hdr, rs, &["make_value", "get_y"], &["Value", "Point"]);
+}
+
+#[test]
+fn test_pod_anonymous_union() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Packet {
+            uint32_t kind;
+            union {
+                uint32_t number;
+                float real;
+            };
+        };
+        inline Packet make_packet() {
+            Packet p;
+            p.kind = 1;
+            p.number = 42;
+            return p;
+        }
+        inline float get_real(Packet p) {
+            return p.real;
+        }
+    "};
+    let rs = quote! {
+        let mut p = ffi::make_packet();
+        assert_eq!(p.kind, 1);
+        assert_eq!(unsafe { *p.number() }, 42);
+        unsafe { *p.real_mut() = 1.5 };
+        assert_eq!(ffi::get_real(p), 1.5);
+    };
+    run_test("", hdr, rs, &["make_packet", "get_real"], &["Packet"]);
+}
+
 #[test]
 fn test_give_pod_by_up() {
     let cxx = indoc! {"
@@ -1071,7 +1209,6 @@ fn test_make_up_with_args() {
 }
 
 #[test]
-#[ignore] // because we don't support unique_ptrs to primitives
 fn test_make_up_int() {
     let cxx = indoc! {"
         Bob::Bob(uint32_t a) : b(a) {
@@ -1087,7 +1224,7 @@ fn test_make_up_int() {
     "};
     let rs = quote! {
         let a = ffi::Bob::new(3).within_unique_ptr();
-        assert_eq!(a.as_ref().unwrap().b, 3);
+        assert_eq!(a.as_ref().unwrap().b(), 3);
     };
     run_test(cxx, hdr, rs, &["Bob"], &[]);
 }
@@ -1187,6 +1324,219 @@ fn test_enum_with_funcs_as_pod() {
     run_test(cxx, hdr, rs, &["give_bob"], &["Bob"]);
 }
 
+#[test]
+fn test_enum_bitflags() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace ns {
+            enum Perms : uint32_t {
+                kRead = 1,
+                kWrite = 2,
+                kExec = 4,
+            };
+            inline Perms operator|(Perms a, Perms b) {
+                return static_cast<Perms>(static_cast<uint32_t>(a) | static_cast<uint32_t>(b));
+            }
+            inline Perms read_write() {
+                return kRead | kWrite;
+            }
+            inline bool can_exec(Perms p) {
+                return (p & kExec) != 0;
+            }
+        }
+    "};
+    let rs = quote! {
+        let rw = ffi::ns::read_write();
+        assert_eq!(rw, ffi::ns::Perms::kRead | ffi::ns::Perms::kWrite);
+        assert!(rw.contains(ffi::ns::Perms::kWrite));
+        assert!(!rw.contains(ffi::ns::Perms::kExec));
+        assert_eq!((rw & !ffi::ns::Perms::kRead).bits(), 2);
+        assert!(!ffi::ns::can_exec(rw));
+        assert!(ffi::ns::can_exec(rw | ffi::ns::Perms::kExec));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("ns::read_write")
+            generate!("ns::can_exec")
+            bitflags!("ns::Perms")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_enum_non_exhaustive() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum Color : int32_t {
+            Red = -1,
+            Green = 2,
+        };
+        inline Color make_color(int32_t val) {
+            return static_cast<Color>(val);
+        }
+        inline bool is_green(Color c) {
+            return c == Green;
+        }
+    "};
+    let rs = quote! {
+        use std::convert::TryFrom;
+        let green = ffi::make_color(2);
+        assert_eq!(green, ffi::Color::Green);
+        assert_eq!(ffi::ColorEnum::try_from(green), Ok(ffi::ColorEnum::Green));
+        let unknown = ffi::make_color(7);
+        assert_eq!(unknown.0, 7);
+        assert_eq!(ffi::ColorEnum::try_from(unknown), Err(unknown));
+        assert!(ffi::is_green(ffi::ColorEnum::Green.into()));
+        assert!(!ffi::is_green(ffi::Color::Red));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("make_color")
+            generate!("is_green")
+            non_exhaustive_enum!("Color")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_derive_serde() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace ns {
+            enum class Mode { Fast, Slow };
+            struct Limits {
+                uint32_t min;
+                uint32_t max;
+            };
+            typedef Limits LimitsAlias;
+            struct Config {
+                Mode mode;
+                LimitsAlias limits[2];
+                double scale;
+            };
+        }
+    "};
+    let rs = quote! {
+        fn assert_serde<T>()
+        where
+            T: autocxx::serde::Serialize + for<'de> autocxx::serde::Deserialize<'de>,
+        {
+        }
+        assert_serde::<ffi::ns::Config>();
+        assert_serde::<ffi::ns::Limits>();
+        assert_serde::<ffi::ns::Mode>();
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_pod!("ns::Config")
+            generate_pod!("ns::Limits")
+            derive_serde!("ns::Config")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_derive_serde_long_array_and_padding() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct alignas(64) Named {
+            char name[40];
+            uint32_t id;
+        };
+    "};
+    let rs = quote! {
+        fn assert_serde<T>()
+        where
+            T: autocxx::serde::Serialize + for<'de> autocxx::serde::Deserialize<'de>,
+        {
+        }
+        assert_serde::<ffi::Named>();
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_pod!("Named")
+            derive_serde!("Named")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_derive_serde_char_64() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Named {
+            char name[64];
+            uint32_t id;
+        };
+    "};
+    let rs = quote! {
+        fn assert_serde<T>()
+        where
+            T: autocxx::serde::Serialize + for<'de> autocxx::serde::Deserialize<'de>,
+        {
+        }
+        assert_serde::<ffi::Named>();
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_pod!("Named")
+            derive_serde!("Named")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_derive_serde_rejects_nested_long_array() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Names {
+            char names[2][64];
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        quote! {
+            generate_pod!("Names")
+            derive_serde!("Names")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
 #[test] // works, but causes compile warnings
 fn test_take_pod_class_by_value() {
     let cxx = indoc! {"
@@ -5084,8 +5434,11 @@ fn test_union_pod() {
         float b;
     };
     "};
-    let rs = quote! {};
-    run_test_expect_fail("", hdr, rs, &[], &["A"]);
+    let rs = quote! {
+        let a = ffi::A { a: 3 };
+        assert_eq!(unsafe { a.a }, 3);
+    };
+    run_test("", hdr, rs, &[], &["A"]);
 }
 
 #[test]
@@ -6424,7 +6777,6 @@ fn test_cint_vector() {
 }
 
 #[test]
-#[ignore] // https://github.com/google/autocxx/issues/422
 fn test_int_vector() {
     let hdr = indoc! {"
         #include <vector>
@@ -7073,7 +7425,6 @@ fn test_shared_ptr() {
 }
 
 #[test]
-#[ignore] // https://github.com/google/autocxx/issues/799
 fn test_shared_ptr_const() {
     let hdr = indoc! {"
         #include <memory>
@@ -7092,78 +7443,624 @@ fn test_shared_ptr_const() {
 }
 
 #[test]
-fn test_rust_reference() {
+fn test_shared_and_weak_ptr_const_struct() {
     let hdr = indoc! {"
-    #include <cstdint>
-
-    struct RustType;
-    inline uint32_t take_rust_reference(const RustType&) {
-        return 4;
-    }
+        #include <memory>
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+            uint32_t get() const { return a; }
+        };
+        inline std::shared_ptr<const A> make_a() {
+            return std::make_shared<const A>(A { 4 });
+        }
+        inline std::shared_ptr<A> make_mutable_a() {
+            return std::make_shared<A>(A { 5 });
+        }
+        inline uint32_t take_a(const std::shared_ptr<const A>& a) {
+            return a->get();
+        }
+        inline uint32_t take_weak_a(std::weak_ptr<const A> a) {
+            return a.lock()->get();
+        }
+        inline uint32_t increment_a(std::shared_ptr<A> a) {
+            return ++a->a;
+        }
+        inline void replace_a(std::shared_ptr<A>& a) {
+            a = std::make_shared<A>(A { 7 });
+        }
     "};
     let rs = quote! {
-        let foo = RustType(3);
-        assert_eq!(ffi::take_rust_reference(&foo), 4);
+        let a = ffi::make_a();
+        assert_eq!(a.get(), 4);
+        assert_eq!(ffi::take_a(&a), 4);
+        let w = a.downgrade();
+        assert_eq!(w.upgrade().as_ref().unwrap().get(), 4);
+        assert_eq!(ffi::take_weak_a(w.clone()), 4);
+        drop(a);
+        assert!(w.upgrade().is_null());
+        let b = ffi::make_mutable_a();
+        assert_eq!(ffi::increment_a(b.clone()), 6);
+        assert_eq!(ffi::take_a(&b), 6);
+        let mut b = b;
+        ffi::replace_a(std::pin::Pin::new(&mut b));
+        assert_eq!(b.get(), 7);
     };
-    run_test_ex(
+    run_test(
         "",
         hdr,
         rs,
-        quote! {
-            generate!("take_rust_reference")
-            extern_rust_type!(RustType)
-        },
-        None,
-        None,
-        Some(quote! {
-            pub struct RustType(i32);
-        }),
+        &[
+            "make_a",
+            "make_mutable_a",
+            "take_a",
+            "take_weak_a",
+            "increment_a",
+            "replace_a",
+        ],
+        &["A"],
     );
 }
 
 #[test]
-fn test_rust_reference_autodiscover() {
+fn test_weak_ptr_const_return() {
     let hdr = indoc! {"
-    #include <cstdint>
-
-    struct RustType;
-    inline uint32_t take_rust_reference(const RustType&) {
-        return 4;
-    }
+        #include <memory>
+        #include <cstdint>
+        struct A {
+            uint32_t a;
+        };
+        inline std::weak_ptr<const A> weaken(std::shared_ptr<const A> a) {
+            return a;
+        }
     "};
-    let rs = quote! {
-        let foo = RustType(3);
-        let result = ffi::take_rust_reference(&foo);
-        assert_eq!(result, 4);
-    };
+    let rs = quote! {};
     run_test_ex(
         "",
         hdr,
         rs,
-        quote! {},
-        Some(Box::new(EnableAutodiscover)),
+        quote! { generate!("weaken") generate_pod!("A") },
+        None,
+        Some(make_error_finder("weaken")),
         None,
-        Some(quote! {
-            #[autocxx::extern_rust::extern_rust_type]
-            pub struct RustType(i32);
-        }),
     );
 }
 
 #[test]
-fn test_pass_thru_rust_reference() {
+fn test_smart_pointer() {
     let hdr = indoc! {"
-    #include <cstdint>
-
-    struct RustType;
-    inline const RustType& pass_rust_reference(const RustType& a) {
-        return a;
-    }
-    "};
-    let rs = quote! {
-        let foo = RustType(3);
-        assert_eq!(ffi::pass_rust_reference(&foo).0, 3);
-    };
+        #include <cstdint>
+        class Foo {
+        public:
+            Foo(uint32_t value) : value(value), refs(0) {}
+            void AddRef() const { refs++; }
+            void Release() const { if (--refs == 0) delete this; }
+            uint32_t get_value() const { return value; }
+            uint32_t get_refs() const { return refs; }
+        private:
+            uint32_t value;
+            mutable uint32_t refs;
+        };
+        namespace base {
+        template <typename T> class scoped_refptr {
+        public:
+            scoped_refptr(T* ptr) : ptr(ptr) { if (ptr) ptr->AddRef(); }
+            scoped_refptr(const scoped_refptr& other) : scoped_refptr(other.ptr) {}
+            ~scoped_refptr() { if (ptr) ptr->Release(); }
+            T* get() const { return ptr; }
+        private:
+            T* ptr;
+        };
+        }
+        inline base::scoped_refptr<Foo> make_foo(uint32_t value) {
+            return base::scoped_refptr<Foo>(new Foo(value));
+        }
+        inline uint32_t get_refs(const base::scoped_refptr<Foo>& foo) {
+            return foo.get()->get_refs();
+        }
+        inline uint32_t take_foo(base::scoped_refptr<Foo> foo) {
+            return foo.get()->get_value();
+        }
+    "};
+    let rs = quote! {
+        let foo = ffi::make_foo(4);
+        assert_eq!(foo.get_value(), 4);
+        assert_eq!(foo.get_refs(), 1);
+        assert_eq!(ffi::get_refs(foo.clone()), 2);
+        assert_eq!(foo.get_refs(), 1);
+        assert_eq!(ffi::take_foo(foo.clone()), 4);
+        assert_eq!(foo.get_refs(), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("make_foo")
+            generate!("get_refs")
+            generate!("take_foo")
+            generate!("Foo")
+            smart_pointer!("base::scoped_refptr")
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_unique_ptr_with_deleter() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        struct Handle {
+            uint32_t value;
+        };
+        struct HandleDeleter {
+            void operator()(Handle* handle) const;
+        };
+        inline uint32_t& destroyed() {
+            static uint32_t count = 0;
+            return count;
+        }
+        inline void HandleDeleter::operator()(Handle* handle) const {
+            destroyed()++;
+            delete handle;
+        }
+        inline uint32_t get_destroyed() {
+            return destroyed();
+        }
+        inline std::unique_ptr<Handle, HandleDeleter> make_handle(uint32_t value) {
+            return std::unique_ptr<Handle, HandleDeleter>(new Handle { value });
+        }
+        inline uint32_t take_handle(std::unique_ptr<Handle, HandleDeleter> handle) {
+            return handle->value;
+        }
+    "};
+    let rs = quote! {
+        let handle = ffi::make_handle(3);
+        assert_eq!(handle.value, 3);
+        assert_eq!(ffi::get_destroyed(), 0);
+        assert_eq!(ffi::take_handle(handle), 3);
+        assert_eq!(ffi::get_destroyed(), 1);
+        drop(ffi::make_handle(4));
+        assert_eq!(ffi::get_destroyed(), 2);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &[
+            "make_handle",
+            "take_handle",
+            "get_destroyed",
+            "HandleDeleter",
+        ],
+        &["Handle"],
+    );
+}
+
+#[test]
+fn test_unique_ptr_with_stateful_deleter() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        struct Handle {
+            uint32_t value;
+        };
+        struct HandleDeleter {
+            uint32_t pool;
+            void operator()(Handle* handle) const { delete handle; }
+        };
+        inline std::unique_ptr<Handle, HandleDeleter> make_handle(uint32_t value) {
+            return std::unique_ptr<Handle, HandleDeleter>(new Handle { value }, HandleDeleter { 2 });
+        }
+    "};
+    let rs = quote! {};
+    run_test_expect_fail("", hdr, rs, &["make_handle", "HandleDeleter"], &["Handle"]);
+}
+
+#[test]
+fn test_within_shared_ptr() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct A {
+            A() : value(3) {}
+            A(uint32_t v) : value(v) {}
+            uint32_t get() const { return value; }
+            std::string name;
+        private:
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let a = ffi::A::new().within_shared_ptr();
+        assert_eq!(a.get(), 3);
+        let b = a.clone();
+        assert_eq!(b.get(), 3);
+        let c = ffi::A::new1(4).within_unique_ptr().into_shared_ptr();
+        assert_eq!(c.get(), 4);
+        let d = ffi::A::new1(5).within_shared_ptr();
+        assert_eq!(d.get(), 5);
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_within_shared_ptr_uses_make_shared() {
+    // std::make_shared allocates using std::allocator rather than the
+    // class's own operator new.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cstddef>
+        #include <new>
+        #include <string>
+        struct A {
+            A(uint32_t v) : value(v) {}
+            static void* operator new(size_t size) {
+                class_allocations()++;
+                return ::operator new(size);
+            }
+            static void operator delete(void* p) { ::operator delete(p); }
+            static uint32_t& class_allocations() {
+                static uint32_t count = 0;
+                return count;
+            }
+            static uint32_t get_class_allocations() { return class_allocations(); }
+            uint32_t value;
+            std::string name;
+        };
+    "};
+    let rs = quote! {
+        let a = ffi::A::new(3).within_shared_ptr();
+        assert_eq!(a.value, 3);
+        assert_eq!(ffi::A::get_class_allocations(), 0);
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_no_shared_ptr_for_private_destructor() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        class A {
+        public:
+            static A* create() { return new A(); }
+            static void destroy(A* a) { delete a; }
+            uint32_t get() const { return 3; }
+            std::string name;
+        private:
+            A() {}
+            ~A() {}
+        };
+    "};
+    let rs = quote! {};
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_converting_constructor() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Meters {
+            Meters(uint32_t v) : value(v) {}
+            Meters(uint32_t km, uint32_t m) : value(km * 1000 + m) {}
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let a: ffi::Meters = 3u32.into();
+        assert_eq!(a.value, 3);
+        let b = ffi::Meters::from(4u32);
+        assert_eq!(b.value, 4);
+    };
+    run_test("", hdr, rs, &[], &["Meters"]);
+}
+
+#[test]
+fn test_converting_constructor_non_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct Label {
+            Label(uint32_t v) : value(v) {}
+            uint32_t get() const { return value; }
+            std::string text;
+        private:
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let a: ::core::pin::Pin<Box<ffi::Label>> = 3u32.into();
+        assert_eq!(a.get(), 3);
+    };
+    run_test("", hdr, rs, &["Label"], &[]);
+}
+
+#[test]
+fn test_explicit_constructor_is_not_converting() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Meters {
+            explicit Meters(uint32_t v) : value(v) {}
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let a: ffi::Meters = 3u32.into();
+        assert_eq!(a.value, 3);
+    };
+    run_test_expect_fail("", hdr, rs, &[], &["Meters"]);
+}
+
+#[test]
+fn test_conversion_operators() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        class Handle {
+        public:
+            Handle(uint32_t v) : value(v) {}
+            explicit operator bool() const { return value != 0; }
+            operator std::string() const { return std::to_string(value); }
+            std::string label;
+        private:
+            uint32_t value;
+        };
+        struct Flag {
+            uint32_t bits;
+            operator bool() const { return bits != 0; }
+        };
+    "};
+    let rs = quote! {
+        let a = ffi::Handle::new(3).within_box();
+        assert!(bool::from(a.as_ref().get_ref()));
+        let s: cxx::UniquePtr<cxx::CxxString> = a.as_ref().get_ref().into();
+        assert_eq!(s.to_str().unwrap(), "3");
+        let b = ffi::Handle::new(0).within_box();
+        assert!(!bool::from(b.as_ref().get_ref()));
+        let f = ffi::Flag { bits: 2 };
+        let is_set: bool = (&f).into();
+        assert!(is_set);
+    };
+    run_test("", hdr, rs, &["Handle"], &["Flag"]);
+}
+
+#[test]
+fn test_converting_constructors_to_same_rust_type() {
+    // char and int8_t are distinct C++ types but both become i8 (on
+    // platforms where char is signed), so only the first of these is
+    // represented as From<i8>.
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Code {
+            Code(int8_t v) : value(v) {}
+            Code(char c) : value(c + 1) {}
+            int32_t value;
+        };
+    "};
+    let rs = quote! {
+        let a: ffi::Code = 3i8.into();
+        assert_eq!(a.value, 3);
+    };
+    run_test("", hdr, rs, &[], &["Code"]);
+}
+
+#[test]
+fn test_non_pod_field_accessors() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        enum class Shape { Circle, Square };
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        struct Inner {
+            std::string name;
+        };
+        class Widget {
+        public:
+            Widget() : count(1), shape(Shape::Square), origin{2, 3}, secret(4) {}
+            uint32_t count;
+            Shape shape;
+            Point origin;
+            std::string label;
+            Inner inner;
+            uint32_t get_secret() const { return secret; }
+        private:
+            uint32_t secret;
+        };
+    "};
+    let rs = quote! {
+        let mut w = ffi::Widget::new().within_unique_ptr();
+        assert_eq!(w.count(), 1);
+        w.pin_mut().set_count(5);
+        assert_eq!(w.count(), 5);
+        assert!(w.shape() == ffi::Shape::Square);
+        w.pin_mut().set_shape(ffi::Shape::Circle);
+        assert!(w.shape() == ffi::Shape::Circle);
+        assert_eq!(w.origin().y, 3);
+        w.pin_mut().set_origin(ffi::Point { x: 6, y: 7 });
+        assert_eq!(w.origin().x, 6);
+        w.pin_mut().label_mut().push_str("hello");
+        assert_eq!(w.label().to_str().unwrap(), "hello");
+        w.pin_mut().inner_mut().name_mut().push_str("inner");
+        assert_eq!(w.inner().name().to_str().unwrap(), "inner");
+        assert_eq!(w.get_secret(), 4);
+    };
+    run_test("", hdr, rs, &["Widget", "Inner"], &["Point"]);
+}
+
+#[test]
+fn test_non_pod_const_field_accessors() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        class Base {
+        public:
+            uint32_t base_value = 1;
+        };
+        class Config : public Base {
+        public:
+            Config() : id(2), name(\"config\"), _base_url(\"url\") {}
+            const uint32_t id;
+            const std::string name;
+            std::string _base_url;
+        };
+    "};
+    let rs = quote! {
+        let c = ffi::Config::new().within_unique_ptr();
+        assert_eq!(c.id(), 2);
+        assert_eq!(c.name().to_str().unwrap(), "config");
+        assert_eq!(c._base_url().to_str().unwrap(), "url");
+    };
+    run_test("", hdr, rs, &["Config"], &[]);
+}
+
+#[test]
+fn test_non_pod_const_field_has_no_setter() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Config {
+        public:
+            Config() : id(2) {}
+            const uint32_t id;
+        };
+    "};
+    let rs = quote! {
+        let mut c = ffi::Config::new().within_unique_ptr();
+        c.pin_mut().set_id(3);
+    };
+    run_test_expect_fail("", hdr, rs, &["Config"], &[]);
+}
+
+#[test]
+fn test_non_pod_field_accessor_named_like_method() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Counter {
+        public:
+            Counter() : count(1), total(2) {}
+            uint32_t count;
+            uint32_t total;
+            void set_count(uint32_t value) { count = value * 10; }
+            uint32_t total_of(uint32_t extra) const { return total + extra; }
+        };
+    "};
+    let rs = quote! {
+        let mut c = ffi::Counter::new().within_unique_ptr();
+        c.pin_mut().set_count(2);
+        assert_eq!(c.count(), 20);
+        assert_eq!(c.total_of(1), 3);
+        assert_eq!(c.total(), 2);
+    };
+    run_test("", hdr, rs, &["Counter"], &[]);
+}
+
+#[test]
+fn test_pod_default_debug_partial_eq() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum class Color { Red, Green };
+        struct Point {
+            uint32_t x = 3;
+            int32_t y;
+            Color color = Color::Green;
+        };
+    "};
+    let rs = quote! {
+        let a = ffi::Point::default();
+        assert_eq!(a.x, 3);
+        assert_eq!(a.y, 0);
+        assert_eq!(a.color, ffi::Color::Green);
+        let mut b = ffi::Point::default();
+        assert_eq!(a, b);
+        b.y = -2;
+        assert_ne!(a, b);
+        assert_eq!(
+            format!("{:?}", b),
+            "Point { x: 3, y: -2, color: Green }"
+        );
+    };
+    run_test("", hdr, rs, &["Color"], &["Point"]);
+}
+
+#[test]
+fn test_rust_reference() {
+    let hdr = indoc! {"
+    #include <cstdint>
+
+    struct RustType;
+    inline uint32_t take_rust_reference(const RustType&) {
+        return 4;
+    }
+    "};
+    let rs = quote! {
+        let foo = RustType(3);
+        assert_eq!(ffi::take_rust_reference(&foo), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("take_rust_reference")
+            extern_rust_type!(RustType)
+        },
+        None,
+        None,
+        Some(quote! {
+            pub struct RustType(i32);
+        }),
+    );
+}
+
+#[test]
+fn test_rust_reference_autodiscover() {
+    let hdr = indoc! {"
+    #include <cstdint>
+
+    struct RustType;
+    inline uint32_t take_rust_reference(const RustType&) {
+        return 4;
+    }
+    "};
+    let rs = quote! {
+        let foo = RustType(3);
+        let result = ffi::take_rust_reference(&foo);
+        assert_eq!(result, 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {},
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_type]
+            pub struct RustType(i32);
+        }),
+    );
+}
+
+#[test]
+fn test_pass_thru_rust_reference() {
+    let hdr = indoc! {"
+    #include <cstdint>
+
+    struct RustType;
+    inline const RustType& pass_rust_reference(const RustType& a) {
+        return a;
+    }
+    "};
+    let rs = quote! {
+        let foo = RustType(3);
+        assert_eq!(ffi::pass_rust_reference(&foo).0, 3);
+    };
     run_test_ex(
         "",
         hdr,
@@ -7502,105 +8399,504 @@ fn test_extern_rust_fn_simple() {
             my_rust_fun();
         }
     "};
-    let hdr = indoc! {"
-        #include <cxx.h>
-        inline void do_thing() {}
-    "};
+    let hdr = indoc! {"
+        #include <cxx.h>
+        inline void do_thing() {}
+    "};
+    run_test_ex(
+        cpp,
+        hdr,
+        quote! {
+            ffi::do_thing();
+        },
+        quote! {
+            generate!("do_thing")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_function]
+            fn my_rust_fun() {
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_fn_in_mod() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        inline void do_thing() {}
+    "};
+    run_test_ex(
+        "",
+        hdr,
+        quote! {},
+        quote! {
+            generate!("do_thing")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            mod bar {
+                #[autocxx::extern_rust::extern_rust_function]
+                pub fn my_rust_fun() {
+
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_method_in_nested_mod() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        #include <cstdint>
+        uint32_t call_rust();
+    "};
+    let cpp = indoc! {"
+        uint32_t call_rust() {
+            return make_field()->count_goats();
+        }
+    "};
+    run_test_ex(
+        cpp,
+        hdr,
+        quote! {
+            assert_eq!(ffi::call_rust(), 7);
+        },
+        quote! {
+            generate!("call_rust")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            pub(crate) mod farm {
+                pub(crate) mod field {
+                    #[autocxx::extern_rust::extern_rust_type]
+                    pub struct Field(u32);
+                    impl Field {
+                        #[autocxx::extern_rust::extern_rust_function]
+                        pub(crate) fn count_goats(&self) -> u32 {
+                            self.0
+                        }
+                    }
+                    #[autocxx::extern_rust::extern_rust_function]
+                    pub(crate) fn make_field() -> Box<Field> {
+                        Box::new(Field(7))
+                    }
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_trait() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        #include <cstdint>
+        struct PluginHolder;
+        uint32_t run_plugin(rust::Box<PluginHolder> plugin);
+    "};
+    let cpp = indoc! {"
+        uint32_t run_plugin(rust::Box<PluginHolder> plugin) {
+            PluginCpp handle(std::move(plugin));
+            Plugin& p = handle;
+            p.add(3);
+            return p.total() + p.name().size();
+        }
+    "};
+    run_test_ex(
+        cpp,
+        hdr,
+        quote! {
+            let plugin = ffi::PluginHolder::new(Counter(4));
+            assert_eq!(ffi::run_plugin(plugin), 14);
+        },
+        quote! {
+            generate!("run_plugin")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_trait]
+            pub trait Plugin {
+                fn name(&self) -> String;
+                fn add(&mut self, n: u32);
+                fn total(&self) -> u32;
+            }
+            pub struct Counter(u32);
+            impl Plugin for Counter {
+                fn name(&self) -> String {
+                    "counter".into()
+                }
+                fn add(&mut self, n: u32) {
+                    self.0 += n;
+                }
+                fn total(&self) -> u32 {
+                    self.0
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_trait_cpp_types() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        #include <cstdint>
+        namespace ns {
+        struct Point {
+            uint32_t x;
+        };
+        }
+        struct PluginHolder;
+        inline uint32_t x_of(const ns::Point& p) { return p.x; }
+        uint32_t run_plugin(rust::Box<PluginHolder> plugin);
+    "};
+    let cpp = indoc! {"
+        uint32_t run_plugin(rust::Box<PluginHolder> plugin) {
+            PluginCpp handle(std::move(plugin));
+            const Plugin& p = handle;
+            ns::Point point { 5 };
+            uint32_t result = p.measure(point);
+            try {
+                p.measure(ns::Point { 0 });
+            } catch (const rust::Error&) {
+                result += 1;
+            }
+            return result;
+        }
+    "};
+    run_test_ex(
+        cpp,
+        hdr,
+        quote! {
+            assert_eq!(ffi::run_plugin(ffi::PluginHolder::new(Ruler)), 11);
+        },
+        quote! {
+            generate!("run_plugin")
+            generate!("x_of")
+            generate!("ns::Point")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_trait]
+            pub trait Plugin {
+                fn measure(&self, point: &ffi::ns::Point) -> Result<u32, String>;
+            }
+            pub struct Ruler;
+            impl Plugin for Ruler {
+                fn measure(&self, point: &ffi::ns::Point) -> Result<u32, String> {
+                    match ffi::x_of(point) {
+                        0 => Err("empty".into()),
+                        x => Ok(x * 2),
+                    }
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_fn_qualified_types() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        inline void do_thing() {}
+    "};
+    run_test_ex(
+        "",
+        hdr,
+        quote! {},
+        quote! {
+            generate!("do_thing")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            mod bar {
+                #[autocxx::extern_rust::extern_rust_type]
+                pub struct RustType(pub i32);
+            }
+            impl crate::bar::RustType {
+                #[autocxx::extern_rust::extern_rust_function]
+                pub fn get(&self) -> i32 {
+                    self.0
+                }
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            pub fn make_rust_type(a: i32) -> Box<crate::bar::RustType> {
+                Box::new(bar::RustType(a))
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            pub fn get_value(a: &self::bar::RustType) -> i32 {
+                a.0
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_issue_956() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline void take_int(int&) {}
+        inline void take_uint16(uint16_t) {}
+        inline void take_us(unsigned short) {}
+        inline void take_char16(char16_t) {}
+        inline void take_uint16_ref(uint16_t&) {}
+        inline void take_char16_ref(char16_t &) {}
+    "};
+    run_test(
+        "",
+        hdr,
+        quote! {},
+        &[
+            "take_int",
+            "take_uint16",
+            "take_char16",
+            "take_uint16_ref",
+            "take_char16_ref",
+            "take_us",
+        ],
+        &[],
+    );
+}
+
+#[test]
+fn test_extended_char_types() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline char32_t next_char32(char32_t c) { return c + 1; }
+        inline uint32_t char32_to_int(const char32_t* c) { return *c; }
+        inline wchar_t next_wchar(wchar_t c) { return c + 1; }
+        struct Glyph {
+            char32_t code;
+            uint32_t width;
+        };
+    "};
+    let rs = quote! {
+        let c = ffi::next_char32(autocxx::c_char32_t(0x1F600));
+        assert_eq!(c.0, 0x1F601);
+        assert_eq!(ffi::char32_to_int(&c), 0x1F601);
+        assert_eq!(ffi::next_wchar(autocxx::c_wchar_t(65)).0, 66);
+        let g = ffi::Glyph {
+            code: autocxx::c_char32_t(65),
+            width: 2,
+        };
+        assert_eq!(g.code.0, 65);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["next_char32", "char32_to_int", "next_wchar"],
+        &["Glyph"],
+    );
+}
+
+#[test]
+fn test_extern_rust_fn_no_autodiscover() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+    "};
+    let cpp = indoc! {"
+        void call_it() {
+            my_rust_fun();
+        }
+    "};
+    run_test_ex(
+        cpp,
+        hdr,
+        quote! {},
+        quote! {},
+        None,
+        None,
+        Some(quote! {
+            mod bar {
+                #[autocxx::extern_rust::extern_rust_function]
+                pub fn my_rust_fun() {
+
+                }
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_extern_rust_fn_panic_rethrow() {
+    let hdr = indoc! {"
+        #include <cxx.h>
+        bool call_and_catch();
+    "};
+    let cpp = indoc! {"
+        #include <string>
+        bool call_and_catch() {
+            try {
+                my_rust_fun(3);
+            } catch (const rust::Error& e) {
+                return std::string(e.what()).find(\"oh no\") != std::string::npos;
+            }
+            return false;
+        }
+    "};
     run_test_ex(
         cpp,
         hdr,
         quote! {
-            ffi::do_thing();
+            assert!(ffi::call_and_catch());
         },
         quote! {
-            generate!("do_thing")
+            generate!("call_and_catch")
+            panic_policy!(rethrow)
         },
         Some(Box::new(EnableAutodiscover)),
         None,
         Some(quote! {
             #[autocxx::extern_rust::extern_rust_function]
-            fn my_rust_fun() {
+            fn my_rust_fun(_a: u32) -> u32 {
+                panic!("oh no")
             }
         }),
     );
 }
 
 #[test]
-fn test_extern_rust_fn_in_mod() {
+fn test_extern_rust_fn_std_types() {
     let hdr = indoc! {"
         #include <cxx.h>
-        inline void do_thing() {}
+        #include <cstdint>
+        bool call_rust();
+    "};
+    let cpp = indoc! {"
+        #include <string>
+        bool call_rust() {
+            if (parse_num(\"12\") != 12) {
+                return false;
+            }
+            try {
+                parse_num(\"twelve\");
+                return false;
+            } catch (const rust::Error&) {
+            }
+            uint8_t nums[] = { 1, 2, 3 };
+            if (sum(rust::Slice<const uint8_t>(nums, 3)) != 6) {
+                return false;
+            }
+            return std::string(greet(\"Bob\")) == \"Hello, Bob\";
+        }
     "};
     run_test_ex(
-        "",
+        cpp,
         hdr,
-        quote! {},
         quote! {
-            generate!("do_thing")
+            assert!(ffi::call_rust());
+        },
+        quote! {
+            generate!("call_rust")
         },
         Some(Box::new(EnableAutodiscover)),
         None,
         Some(quote! {
-            mod bar {
-                #[autocxx::extern_rust::extern_rust_function]
-                pub fn my_rust_fun() {
-
-                }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn parse_num(s: &str) -> Result<u32, std::num::ParseIntError> {
+                s.parse()
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn sum(nums: &[u8]) -> u32 {
+                nums.iter().map(|n| *n as u32).sum()
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn greet(name: &str) -> String {
+                format!("Hello, {name}")
             }
         }),
     );
 }
 
 #[test]
-fn test_issue_956() {
+fn test_extern_rust_fn_option_box() {
     let hdr = indoc! {"
+        #include <cxx.h>
         #include <cstdint>
-        inline void take_int(int&) {}
-        inline void take_uint16(uint16_t) {}
-        inline void take_us(unsigned short) {}
-        inline void take_char16(char16_t) {}
-        inline void take_uint16_ref(uint16_t&) {}
-        inline void take_char16_ref(char16_t &) {}
+        bool call_rust();
     "};
-    run_test(
-        "",
+    let cpp = indoc! {"
+        bool call_rust() {
+            if (make_goat(false) != nullptr) {
+                return false;
+            }
+            Goat* goat = make_goat(true);
+            return count_horns(goat) == 2 && count_horns(nullptr) == 0;
+        }
+    "};
+    run_test_ex(
+        cpp,
         hdr,
-        quote! {},
-        &[
-            "take_int",
-            "take_uint16",
-            "take_char16",
-            "take_uint16_ref",
-            "take_char16_ref",
-            "take_us",
-        ],
-        &[],
+        quote! {
+            assert!(ffi::call_rust());
+        },
+        quote! {
+            generate!("call_rust")
+        },
+        Some(Box::new(EnableAutodiscover)),
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_type]
+            pub struct Goat {
+                horns: u32,
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn make_goat(real: bool) -> Option<Box<Goat>> {
+                real.then(|| Box::new(Goat { horns: 2 }))
+            }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn count_horns(goat: Option<Box<Goat>>) -> u32 {
+                goat.map(|goat| goat.horns).unwrap_or_default()
+            }
+        }),
     );
 }
 
 #[test]
-fn test_extern_rust_fn_no_autodiscover() {
+fn test_extern_rust_fn_cpp_type_by_reference() {
     let hdr = indoc! {"
         #include <cxx.h>
+        #include <cstdint>
+        namespace farm {
+        struct Barn {
+            uint32_t goats;
+        };
+        }
+        uint32_t call_rust();
     "};
     let cpp = indoc! {"
-        void call_it() {
-            my_rust_fun();
+        uint32_t call_rust() {
+            farm::Barn barn;
+            barn.goats = 3;
+            return count_goats(barn);
         }
     "};
     run_test_ex(
         cpp,
         hdr,
-        quote! {},
-        quote! {},
-        None,
+        quote! {
+            assert_eq!(ffi::call_rust(), 3);
+        },
+        quote! {
+            generate!("call_rust")
+            generate_pod!("farm::Barn")
+        },
+        Some(Box::new(EnableAutodiscover)),
         None,
         Some(quote! {
-            mod bar {
-                #[autocxx::extern_rust::extern_rust_function]
-                pub fn my_rust_fun() {
-
-                }
+            #[autocxx::extern_rust::extern_rust_function]
+            fn count_goats(barn: &ffi::farm::Barn) -> u32 {
+                barn.goats
             }
         }),
     );
@@ -7947,6 +9243,58 @@ fn test_pv_subclass_passed_to_fn() {
     );
 }
 
+#[test]
+fn test_pv_subclass_panic_rethrow() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <string>
+    #include <cxx.h>
+
+    class Observer {
+    public:
+        Observer() {}
+        virtual uint32_t foo() const = 0;
+        virtual ~Observer() {}
+    };
+    inline bool call_and_catch(const Observer& obs) {
+        try {
+            obs.foo();
+        } catch (const rust::Error& e) {
+            return std::string(e.what()).find(\"oh no\") != std::string::npos;
+        }
+        return false;
+    }
+    "};
+    run_test_ex(
+        "",
+        hdr,
+        quote! {
+            let o = MyObserver::new_rust_owned(MyObserver { a: 3, cpp_peer: Default::default() });
+            assert!(ffi::call_and_catch(o.borrow().as_ref()));
+        },
+        quote! {
+            generate!("call_and_catch")
+            subclass!("Observer",MyObserver)
+            panic_policy!(rethrow)
+        },
+        None,
+        None,
+        Some(quote! {
+            use autocxx::subclass::CppSubclass;
+            use ffi::Observer_methods;
+            #[autocxx::subclass::subclass]
+            pub struct MyObserver {
+                a: u32
+            }
+            impl Observer_methods for MyObserver {
+                fn foo(&self) -> u32 {
+                    panic!("oh no")
+                }
+            }
+        }),
+    );
+}
+
 #[test]
 fn test_pv_subclass_derive_defaults() {
     let hdr = indoc! {"
@@ -9729,6 +11077,63 @@ fn test_explicit_everything() {
     run_test("", hdr, rs, &["A"], &[]);
 }
 
+#[test]
+fn test_assignment_operators() {
+    let hdr = indoc! {"
+    #include <stdint.h>
+    #include <string>
+    struct A {
+        A() : a(0) {}
+        A(uint32_t val) : a(val) {}
+        A(const A& other) = default;
+        A(A&& other) : a(other.a) { other.a = 0; }
+        A& operator=(const A& other) { a = other.a; return *this; }
+        A& operator=(A&& other) { a = other.a; other.a = 0; return *this; }
+        A& operator=(uint32_t val) { a = val; return *this; }
+        uint32_t get() const { return a; }
+        uint32_t a;
+        std::string so_we_are_non_trivial;
+    };
+    "};
+    let rs = quote! {
+        let mut a = ffi::A::new().within_unique_ptr();
+        let b = ffi::A::new1(3).within_unique_ptr();
+        a.pin_mut().copy_assign(&b);
+        assert_eq!(a.get(), 3);
+        assert_eq!(b.get(), 3);
+        let c = ffi::A::new1(4).within_box();
+        a.pin_mut().move_assign(c);
+        assert_eq!(a.get(), 4);
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
+#[test]
+fn test_clone_unique_ptr_and_cpp_pin() {
+    let hdr = indoc! {"
+    #include <stdint.h>
+    #include <string>
+    struct A {
+        A(uint32_t val) : a(val) {}
+        A(const A& other) : a(other.a + 1) {}
+        uint32_t get() const { return a; }
+        uint32_t a;
+        std::string so_we_are_non_trivial;
+    };
+    "};
+    let rs = quote! {
+        let a = ffi::A::new(3).within_unique_ptr();
+        let b = a.clone();
+        assert_eq!(a.get(), 3);
+        assert_eq!(b.get(), 4);
+        assert!(UniquePtr::<ffi::A>::null().clone().is_null());
+        let c = ffi::A::new(5).within_cpp_pin();
+        let d = c.clone();
+        assert_eq!(unsafe { d.as_ref() }.get(), 6);
+    };
+    run_test("", hdr, rs, &["A"], &[]);
+}
+
 #[test]
 fn test_generate_ns() {
     let hdr = indoc! {"
@@ -10009,7 +11414,6 @@ fn test_class_having_private_method() {
 }
 
 #[test]
-#[ignore] // https://github.com/google/autocxx/issues/787
 fn test_chrono_problem() {
     let hdr = indoc! {"
     #include <chrono>
@@ -10018,11 +11422,238 @@ fn test_chrono_problem() {
     };
     struct Class {
       int a() { return 42; }
-      std::chrono::time_point<Clock> b();
+      std::chrono::time_point<Clock> b() {
+        return std::chrono::time_point<Clock>(std::chrono::nanoseconds(12));
+      }
     };
     "};
-    let rs = quote! {};
-    run_test("", hdr, rs, &[], &["Class"]);
+    let rs = quote! {
+        let mut c = ffi::Class::new().within_unique_ptr();
+        let t: autocxx::chrono::TimePoint<ffi::Clock> = c.pin_mut().b();
+        assert_eq!(t.duration_since_epoch(), std::time::Duration::from_nanos(12));
+    };
+    run_test("", hdr, rs, &["Clock"], &["Class"]);
+}
+
+#[test]
+fn test_chrono_duration() {
+    let hdr = indoc! {"
+    #include <chrono>
+    #include <cstdint>
+    inline std::chrono::milliseconds twice(std::chrono::milliseconds ms) {
+      return ms * 2;
+    }
+    inline int64_t count_seconds(const std::chrono::seconds& s) {
+      return s.count();
+    }
+    inline std::chrono::duration<double> half_a_second() {
+      return std::chrono::duration<double>(0.5);
+    }
+    inline std::chrono::steady_clock::time_point later(std::chrono::steady_clock::time_point t) {
+      return t + std::chrono::seconds(1);
+    }
+    inline std::chrono::seconds difference(std::chrono::seconds a, std::chrono::seconds b) {
+      return a - b;
+    }
+    struct Timings {
+      std::chrono::duration<int32_t, std::milli> short_interval;
+      std::chrono::duration<int64_t, std::nano> long_interval;
+      int32_t count;
+    };
+    inline size_t timings_size() { return sizeof(Timings); }
+    "};
+    let rs = quote! {
+        use autocxx::chrono::SignedDuration;
+        use std::time::Duration;
+        assert_eq!(ffi::twice(Duration::from_millis(4)), Duration::from_millis(8));
+        assert_eq!(ffi::count_seconds(Duration::from_millis(3500)), 3);
+        assert_eq!(ffi::half_a_second(), Duration::from_millis(500));
+        let t = autocxx::chrono::TimePoint::<autocxx::chrono::SteadyClock>::from_duration_since_epoch(
+            Duration::from_secs(2),
+        );
+        assert_eq!(ffi::later(t).duration_since_epoch(), Duration::from_secs(3));
+        let negative = ffi::difference(Duration::from_secs(1), Duration::from_secs(3));
+        assert_eq!(negative, SignedDuration::from_nanos(-2_000_000_000));
+        assert!(Duration::try_from(negative).is_err());
+        assert_eq!(ffi::twice(negative), SignedDuration::from_nanos(-4_000_000_000));
+        assert_eq!(std::mem::size_of::<ffi::Timings>(), ffi::timings_size());
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &[
+            "twice",
+            "count_seconds",
+            "half_a_second",
+            "later",
+            "difference",
+            "timings_size",
+            "Timings",
+        ],
+        &[],
+    );
+}
+
+#[test]
+fn test_std_pair_and_tuple() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <tuple>
+    #include <utility>
+    struct Point { int32_t x; int32_t y; };
+    inline std::pair<int, bool> divide(int a, int b) {
+      return std::make_pair(a / b, a % b == 0);
+    }
+    inline int sum(const std::pair<int, int>& p) {
+      return p.first + p.second;
+    }
+    inline std::tuple<uint32_t, double, bool> describe(std::tuple<uint32_t, double, bool> t) {
+      return std::make_tuple(std::get<0>(t) + 1, std::get<1>(t) * 2, !std::get<2>(t));
+    }
+    inline std::pair<Point, int32_t> with_sum(Point p) {
+      return std::make_pair(p, p.x + p.y);
+    }
+    "};
+    let rs = quote! {
+        use autocxx::c_int;
+        assert_eq!(ffi::divide(c_int(7), c_int(2)), (c_int(3), false));
+        assert_eq!(ffi::divide(c_int(8), c_int(2)), (c_int(4), true));
+        assert_eq!(ffi::sum((c_int(3), c_int(4))), c_int(7));
+        assert_eq!(ffi::describe((1, 1.5, false)), (2, 3.0, true));
+        let (p, sum) = ffi::with_sum(ffi::Point { x: 2, y: 3 });
+        assert_eq!(p.x, 2);
+        assert_eq!(sum, 5);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["divide", "sum", "describe", "with_sum"],
+        &["Point"],
+    );
+}
+
+#[test]
+fn test_std_variant() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <string>
+    #include <variant>
+    struct Point { int32_t x; int32_t y; };
+    inline std::variant<uint32_t, std::string, Point> make_value(int which) {
+      switch (which) {
+        case 0:
+          return uint32_t(42);
+        case 1:
+          return std::string(\"hello\");
+        default:
+          return Point { 2, 3 };
+      }
+    }
+    inline std::string describe(std::variant<uint32_t, std::string, Point> v) {
+      if (auto n = std::get_if<uint32_t>(&v)) {
+        return std::to_string(*n);
+      } else if (auto s = std::get_if<std::string>(&v)) {
+        return *s;
+      } else {
+        return \"point\";
+      }
+    }
+    "};
+    let rs = quote! {
+        use ffi::std_variant_uint32_t_std_string_Point_AutocxxConcreteEnum as Value;
+        use std::convert::TryFrom;
+        match Value::try_from(ffi::make_value(c_int(0))).unwrap() {
+            Value::u32(n) => assert_eq!(n, 42),
+            _ => panic!("wrong alternative"),
+        }
+        match Value::try_from(ffi::make_value(c_int(1))).unwrap() {
+            Value::CxxString(s) => assert_eq!(s.to_str().unwrap(), "hello"),
+            _ => panic!("wrong alternative"),
+        }
+        match Value::try_from(ffi::make_value(c_int(2))).unwrap() {
+            Value::Point(p) => assert_eq!(p.y, 3),
+            _ => panic!("wrong alternative"),
+        }
+        let v: cxx::UniquePtr<_> = Value::u32(7).into();
+        assert_eq!(ffi::describe(v).to_str().unwrap(), "7");
+        let v: cxx::UniquePtr<_> = Value::CxxString(ffi::make_string("hi")).into();
+        assert_eq!(ffi::describe(v).to_str().unwrap(), "hi");
+        let v: cxx::UniquePtr<_> = Value::Point(ffi::Point { x: 0, y: 0 }).into();
+        assert_eq!(ffi::describe(v).to_str().unwrap(), "point");
+        assert_eq!(
+            Value::try_from(cxx::UniquePtr::null()).err(),
+            Some(autocxx::VariantConversionError::Null)
+        );
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["make_value", "describe"], &["Point"], None),
+        make_cpp17_adder(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_associative_containers() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <map>
+    #include <set>
+    #include <string>
+    #include <unordered_map>
+    inline std::map<std::string, uint32_t> make_ages() {
+      return { { \"alice\", 30 }, { \"bob\", 25 } };
+    }
+    inline uint32_t total_age(const std::map<std::string, uint32_t>& ages) {
+      uint32_t total = 0;
+      for (const auto& entry : ages) {
+        total += entry.second;
+      }
+      return total;
+    }
+    inline std::unordered_map<uint32_t, uint32_t> make_squares() {
+      return { { 1, 1 }, { 2, 4 }, { 3, 9 } };
+    }
+    inline std::set<uint32_t> make_primes() {
+      return { 7, 5, 3, 2 };
+    }
+    "};
+    let rs = quote! {
+        let mut ages = ffi::make_ages();
+        assert_eq!(ages.len(), 2);
+        autocxx::cxx::let_cxx_string!(alice = "alice");
+        assert_eq!(ages.get(&alice), Some(&30));
+        assert!(ages.pin_mut().insert(ffi::make_string("carol"), 41));
+        assert!(!ages.pin_mut().insert(ffi::make_string("bob"), 26));
+        assert_eq!(ffi::total_age(&ages), 97);
+        let names: Vec<_> = ages.iter().map(|(name, _)| name.to_str().unwrap()).collect();
+        assert_eq!(names, ["alice", "bob", "carol"]);
+        assert!(ages.pin_mut().remove(&alice));
+        assert!(!ages.contains_key(&alice));
+
+        let squares = ffi::make_squares();
+        let mut entries: Vec<_> = squares.iter().map(|(k, v)| (*k, *v)).collect();
+        entries.sort();
+        assert_eq!(entries, [(1, 1), (2, 4), (3, 9)]);
+
+        let mut primes = ffi::make_primes();
+        assert!(primes.contains(&5));
+        assert!(!primes.pin_mut().insert(7));
+        assert!(primes.pin_mut().insert(11));
+        assert_eq!(primes.iter().copied().collect::<Vec<_>>(), [2, 3, 5, 7, 11]);
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &["make_ages", "total_age", "make_squares", "make_primes"],
+        &[],
+    );
 }
 
 fn size_and_alignment_test(pod: bool) {
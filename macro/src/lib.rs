@@ -115,6 +115,23 @@ pub fn extern_rust_function(attr: TokenStream, input: TokenStream) -> TokenStrea
     input
 }
 
+/// Attribute to state that a Rust trait is to be exported to C++
+/// as an abstract class.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn extern_rust_trait(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        abort!(Span::call_site(), "Expected no attributes");
+    }
+    let i: Item =
+        syn::parse(input.clone()).unwrap_or_else(|_| abort!(Span::call_site(), "Expected an item"));
+    match i {
+        Item::Trait(..) => {}
+        _ => abort!(Span::call_site(), "Expected a trait"),
+    }
+    input
+}
+
 /// Attribute which should never be encountered in real life.
 /// This is something which features in the Rust source code generated
 /// by autocxx-bindgen and passed to autocxx-engine, which should never
@@ -16,7 +16,6 @@ use itertools::Itertools;
 use proc_macro2::Span;
 use quote::ToTokens;
 
-#[cfg(feature = "reproduction_case")]
 use quote::format_ident;
 use syn::{
     parse::{Parse, ParseStream},
@@ -90,6 +89,48 @@ impl UnsafePolicy {
     }
 }
 
+/// What to do if Rust code called from C++ (for instance, an override of
+/// a C++ virtual method, or an `extern_rust_function`) panics. Unwinding
+/// across C++ frames is undefined behavior, so we must always catch the
+/// panic before it gets that far.
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Default)]
+pub enum PanicPolicy {
+    /// Report the panic message and abort the process.
+    #[default]
+    Abort,
+    /// Convert the panic into a C++ exception thrown to the C++ caller.
+    Rethrow,
+}
+
+impl Parse for PanicPolicy {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        let r = if id == "abort" {
+            Ok(PanicPolicy::Abort)
+        } else if id == "rethrow" {
+            Ok(PanicPolicy::Rethrow)
+        } else {
+            Err(syn::Error::new(id.span(), "expected abort or rethrow"))
+        };
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unexpected tokens within panic_policy directive",
+            ));
+        }
+        r
+    }
+}
+
+impl ToTokens for PanicPolicy {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            PanicPolicy::Abort => tokens.extend(quote! { abort }),
+            PanicPolicy::Rethrow => tokens.extend(quote! { rethrow }),
+        }
+    }
+}
+
 /// An entry in the allowlist.
 #[derive(Hash, Debug)]
 pub enum AllowlistEntry {
@@ -177,6 +218,33 @@ impl std::fmt::Debug for RustFun {
     }
 }
 
+/// A Rust trait which is exposed to C++ as an abstract class.
+#[derive(Debug, Clone, Hash)]
+pub struct RustTrait {
+    pub path: RustPath,
+    /// The methods of the trait. Their receivers refer to the
+    /// [`RustTrait::holder`] type rather than `Self`.
+    pub methods: Vec<RustFun>,
+}
+
+impl RustTrait {
+    /// The name of the Rust type which owns a `Box<dyn Trait>` and is
+    /// passed to C++, given the name of the trait.
+    pub fn holder_for(trait_id: &Ident) -> Ident {
+        format_ident!("{}Holder", trait_id)
+    }
+
+    pub fn holder(&self) -> Ident {
+        Self::holder_for(self.path.get_final_ident())
+    }
+
+    /// The name of the C++ class which implements the abstract class
+    /// by calling into the holder.
+    pub fn cpp_handle(&self) -> Ident {
+        format_ident!("{}Cpp", self.path.get_final_ident())
+    }
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct ExternCppType {
     pub rust_path: TypePath,
@@ -209,13 +277,52 @@ impl std::hash::Hash for ConcretesMap {
     }
 }
 
+/// How to manage a custom C++ smart pointer which holds an intrusive
+/// reference count, as specified by a `smart_pointer!` directive.
+#[derive(Debug, Clone, Hash)]
+pub struct SmartPointer {
+    /// The method on the smart pointer which returns a raw pointer.
+    pub deref: String,
+    /// The method on the pointee which adds a reference.
+    pub clone: String,
+    /// The method on the pointee which releases a reference.
+    pub release: String,
+}
+
+impl Default for SmartPointer {
+    fn default() -> Self {
+        Self {
+            deref: "get".into(),
+            clone: "AddRef".into(),
+            release: "Release".into(),
+        }
+    }
+}
+
+/// Newtype wrapper so we can implement Hash.
+#[derive(Debug, Default)]
+pub struct SmartPointersMap(pub HashMap<String, SmartPointer>);
+
+impl std::hash::Hash for SmartPointersMap {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for (k, v) in &self.0 {
+            k.hash(state);
+            v.hash(state);
+        }
+    }
+}
+
 #[derive(Debug, Default, Hash)]
 pub struct IncludeCppConfig {
     pub inclusions: Vec<String>,
     pub unsafe_policy: UnsafePolicy,
+    pub panic_policy: PanicPolicy,
     pub parse_only: bool,
     pub exclude_impls: bool,
     pub(crate) pod_requests: Vec<String>,
+    pub(crate) bitflags: Vec<String>,
+    pub(crate) non_exhaustive_enums: Vec<String>,
+    pub(crate) serde_types: Vec<String>,
     pub allowlist: Allowlist,
     pub(crate) blocklist: Vec<String>,
     pub(crate) constructor_blocklist: Vec<String>,
@@ -225,8 +332,10 @@ pub struct IncludeCppConfig {
     pub rust_types: Vec<RustPath>,
     pub subclasses: Vec<Subclass>,
     pub extern_rust_funs: Vec<RustFun>,
+    pub extern_rust_traits: Vec<RustTrait>,
     pub concretes: ConcretesMap,
     pub externs: ExternCppTypeMap,
+    pub smart_pointers: SmartPointersMap,
 }
 
 impl Parse for IncludeCppConfig {
@@ -265,6 +374,16 @@ impl Parse for IncludeCppConfig {
                 break;
             }
         }
+        if let Some(clash) = config
+            .bitflags
+            .iter()
+            .find(|name| config.non_exhaustive_enums.contains(name))
+        {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("{clash} can't be given to both bitflags! and non_exhaustive_enum!"),
+            ));
+        }
         Ok(config)
     }
 }
@@ -274,6 +393,32 @@ impl IncludeCppConfig {
         &self.pod_requests
     }
 
+    /// Whether this enum should be represented as a set of bit flags
+    /// rather than as a Rust enum.
+    pub fn is_bitflags(&self, cpp_name: &str) -> bool {
+        self.bitflags.iter().any(|item| item == cpp_name)
+    }
+
+    /// Whether this enum should be represented such that it can safely
+    /// hold values other than its enumerators.
+    pub fn is_non_exhaustive_enum(&self, cpp_name: &str) -> bool {
+        self.non_exhaustive_enums
+            .iter()
+            .any(|item| item == cpp_name)
+    }
+
+    /// Types for which we should derive serde's `Serialize` and
+    /// `Deserialize`, along with the types of their fields.
+    pub fn get_serde_types(&self) -> &[String] {
+        &self.serde_types
+    }
+
+    /// If this is a template which the user has told us is a custom
+    /// smart pointer, how to manage it.
+    pub fn get_smart_pointer(&self, cpp_name: &str) -> Option<&SmartPointer> {
+        self.smart_pointers.0.get(cpp_name)
+    }
+
     pub fn get_mod_name(&self) -> Ident {
         self.mod_name
             .as_ref()
@@ -299,10 +444,18 @@ impl IncludeCppConfig {
                         AllowlistEntry::Namespace(_) => None,
                     })
                     .chain(self.pod_requests.iter())
+                    .chain(self.bitflags.iter())
+                    .chain(self.non_exhaustive_enums.iter())
                     .cloned(),
             )
         } else {
-            Box::new(self.pod_requests.iter().cloned())
+            Box::new(
+                self.pod_requests
+                    .iter()
+                    .chain(self.bitflags.iter())
+                    .chain(self.non_exhaustive_enums.iter())
+                    .cloned(),
+            )
         }
     }
 
@@ -315,6 +468,8 @@ impl IncludeCppConfig {
                     .iter()
                     .map(AllowlistEntry::to_bindgen_item)
                     .chain(self.pod_requests.iter().cloned())
+                    .chain(self.bitflags.iter().cloned())
+                    .chain(self.non_exhaustive_enums.iter().cloned())
                     .chain(self.active_utilities())
                     .chain(self.subclasses.iter().flat_map(|sc| {
                         [
@@ -364,6 +519,7 @@ impl IncludeCppConfig {
             || self.is_subclass_cpp(cpp_name)
             || self.is_rust_fun(cpp_name)
             || self.is_rust_type_name(cpp_name)
+            || self.is_rust_trait_holder(cpp_name)
             || self.is_concrete_type(cpp_name)
             || match &self.allowlist {
                 Allowlist::Unspecified(_) => panic!("Eek no allowlist yet"),
@@ -411,7 +567,9 @@ impl IncludeCppConfig {
 
     pub fn is_rust_type(&self, id: &Ident) -> bool {
         let id_string = id.to_string();
-        self.is_rust_type_name(&id_string) || self.is_subclass_holder(&id_string)
+        self.is_rust_type_name(&id_string)
+            || self.is_subclass_holder(&id_string)
+            || self.is_rust_trait_holder(&id_string)
     }
 
     fn is_rust_type_name(&self, possible_ty: &str) -> bool {
@@ -420,6 +578,12 @@ impl IncludeCppConfig {
             .any(|rt| rt.get_final_ident() == possible_ty)
     }
 
+    fn is_rust_trait_holder(&self, possible_ty: &str) -> bool {
+        self.extern_rust_traits
+            .iter()
+            .any(|rt| rt.holder() == possible_ty)
+    }
+
     fn is_rust_fun(&self, possible_fun: &str) -> bool {
         self.extern_rust_funs
             .iter()
@@ -496,7 +660,7 @@ impl ToTokens for IncludeCppConfig {
 
 #[cfg(test)]
 mod parse_tests {
-    use crate::config::UnsafePolicy;
+    use crate::config::{PanicPolicy, UnsafePolicy};
     use syn::parse_quote;
     #[test]
     fn test_safety_unsafe() {
@@ -519,4 +683,17 @@ mod parse_tests {
         let us: UnsafePolicy = parse_quote! {};
         assert_eq!(us, UnsafePolicy::AllFunctionsUnsafe)
     }
+
+    #[test]
+    fn test_panic_policy() {
+        let pp: PanicPolicy = parse_quote! {
+            rethrow
+        };
+        assert_eq!(pp, PanicPolicy::Rethrow);
+        let pp: PanicPolicy = parse_quote! {
+            abort
+        };
+        assert_eq!(pp, PanicPolicy::Abort);
+        assert!(syn::parse2::<PanicPolicy>(quote::quote! { unwind }).is_err());
+    }
 }
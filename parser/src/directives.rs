@@ -44,6 +44,7 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
         need_exclamation.insert("generate_ns".into(), Box::new(GenerateNs));
         need_exclamation.insert("generate_all".into(), Box::new(GenerateAll));
         need_exclamation.insert("safety".into(), Box::new(Safety));
+        need_exclamation.insert("panic_policy".into(), Box::new(Panic));
         need_exclamation.insert(
             "pod".into(),
             Box::new(StringList(
@@ -51,6 +52,27 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
                 |config| &config.pod_requests,
             )),
         );
+        need_exclamation.insert(
+            "bitflags".into(),
+            Box::new(StringList(
+                |config| &mut config.bitflags,
+                |config| &config.bitflags,
+            )),
+        );
+        need_exclamation.insert(
+            "non_exhaustive_enum".into(),
+            Box::new(StringList(
+                |config| &mut config.non_exhaustive_enums,
+                |config| &config.non_exhaustive_enums,
+            )),
+        );
+        need_exclamation.insert(
+            "derive_serde".into(),
+            Box::new(StringList(
+                |config| &mut config.serde_types,
+                |config| &config.serde_types,
+            )),
+        );
         need_exclamation.insert(
             "block".into(),
             Box::new(StringList(
@@ -107,6 +129,7 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
             "extern_cpp_opaque_type".into(),
             Box::new(ExternCppType { opaque: true }),
         );
+        need_exclamation.insert("smart_pointer".into(), Box::new(SmartPointer));
 
         DirectivesMap {
             need_hexathorpe,
@@ -289,6 +312,32 @@ impl Directive for Safety {
     }
 }
 
+struct Panic;
+
+impl Directive for Panic {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.panic_policy = args.parse()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let policy = &config.panic_policy;
+        match config.panic_policy {
+            crate::PanicPolicy::Abort => Box::new(std::iter::empty()),
+            _ => Box::new(std::iter::once(policy.to_token_stream())),
+        }
+    }
+}
+
 fn allowlist_err_to_syn_err(err: AllowlistErr, span: &Span) -> syn::Error {
     syn::Error::new(*span, format!("{err}"))
 }
@@ -560,3 +609,53 @@ impl Directive for ExternCppType {
         )
     }
 }
+
+struct SmartPointer;
+
+impl Directive for SmartPointer {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let template: syn::LitStr = args.parse()?;
+        let mut details = crate::config::SmartPointer::default();
+        while !args.is_empty() {
+            args.parse::<syn::token::Comma>()?;
+            if args.is_empty() {
+                break;
+            }
+            let key: Ident = args.parse()?;
+            args.parse::<syn::token::Eq>()?;
+            let value: syn::LitStr = args.parse()?;
+            let field = match key.to_string().as_str() {
+                "deref" => &mut details.deref,
+                "clone" => &mut details.clone,
+                "release" => &mut details.release,
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "expected deref, clone or release",
+                    ))
+                }
+            };
+            *field = value.value();
+        }
+        config.smart_pointers.0.insert(template.value(), details);
+        Ok(())
+    }
+
+    #[cfg(feature = "reproduction_case")]
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.smart_pointers.0.iter().map(|(template, details)| {
+            let (deref, clone, release) = (&details.deref, &details.clone, &details.release);
+            quote! {
+                #template, deref = #deref, clone = #clone, release = #release
+            }
+        }))
+    }
+}
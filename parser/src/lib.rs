@@ -16,7 +16,8 @@ mod path;
 mod subclass_attrs;
 
 pub use config::{
-    AllowlistEntry, ExternCppType, IncludeCppConfig, RustFun, Subclass, UnsafePolicy,
+    AllowlistEntry, ExternCppType, IncludeCppConfig, PanicPolicy, RustFun, RustTrait, SmartPointer,
+    Subclass, UnsafePolicy,
 };
 use file_locations::FileLocationStrategy;
 pub use multi_bindings::{MultiBindings, MultiBindingsErr};
@@ -35,6 +36,7 @@ use syn::{
 pub mod directive_names {
     pub static EXTERN_RUST_TYPE: &str = "extern_rust_type";
     pub static EXTERN_RUST_FUN: &str = "extern_rust_function";
+    pub static EXTERN_RUST_TRAIT: &str = "extern_rust_trait";
     pub static SUBCLASS: &str = "subclass";
 }
 
@@ -80,4 +82,29 @@ mod parse_tests {
             generate_all!()
         };
     }
+
+    #[test]
+    fn test_smart_pointer() {
+        let i: IncludeCpp = parse_quote! {
+            smart_pointer!("base::scoped_refptr")
+            smart_pointer!("Ref", deref = "ptr", release = "Unref",)
+        };
+        let config = i.get_config();
+        let scoped_refptr = config.get_smart_pointer("base::scoped_refptr").unwrap();
+        assert_eq!(scoped_refptr.deref, "get");
+        assert_eq!(scoped_refptr.clone, "AddRef");
+        let r = config.get_smart_pointer("Ref").unwrap();
+        assert_eq!(r.deref, "ptr");
+        assert_eq!(r.clone, "AddRef");
+        assert_eq!(r.release, "Unref");
+    }
+
+    #[test]
+    fn test_bitflags_and_non_exhaustive_enum_clash() {
+        let i: syn::Result<IncludeCpp> = syn::parse2(quote::quote! {
+            bitflags!("ns::Perms")
+            non_exhaustive_enum!("ns::Perms")
+        });
+        assert!(i.is_err());
+    }
 }
@@ -26,6 +26,20 @@ impl RustPath {
         Self(self.0.iter().cloned().chain(std::iter::once(id)).collect())
     }
 
+    /// Constructs a path from a list of identifiers, which must not be
+    /// empty.
+    pub fn from_idents(ids: Vec<Ident>) -> Option<Self> {
+        if ids.is_empty() {
+            None
+        } else {
+            Some(Self(ids))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Ident> {
+        self.0.iter()
+    }
+
     pub fn get_final_ident(&self) -> &Ident {
         self.0.last().unwrap()
     }
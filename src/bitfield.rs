@@ -0,0 +1,79 @@
+//! Support for reading and writing C++ bitfields within POD structs.
+//! This is used by the accessor methods which autocxx generates for
+//! each bitfield; the storage for a run of bitfields is represented
+//! in Rust as a byte array.
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The bit ordering logic here exactly mirrors that used by bindgen's
+// `__BindgenBitfieldUnit`, since bindgen is what calculates the
+// offsets and widths we're given.
+
+#[inline]
+fn bit_index(index: usize) -> usize {
+    if cfg!(target_endian = "big") {
+        7 - (index % 8)
+    } else {
+        index % 8
+    }
+}
+
+#[inline]
+fn get_bit(storage: &[u8], index: usize) -> bool {
+    let mask = 1 << bit_index(index);
+    storage[index / 8] & mask == mask
+}
+
+#[inline]
+fn set_bit(storage: &mut [u8], index: usize, val: bool) {
+    let mask = 1 << bit_index(index);
+    let byte = &mut storage[index / 8];
+    if val {
+        *byte |= mask;
+    } else {
+        *byte &= !mask;
+    }
+}
+
+/// Read `bit_width` bits starting at `bit_offset` within the storage
+/// for some bitfields.
+#[inline]
+pub fn get(storage: &[u8], bit_offset: usize, bit_width: u8) -> u64 {
+    debug_assert!(bit_width <= 64);
+    debug_assert!((bit_offset + (bit_width as usize)) / 8 <= storage.len());
+    let mut val = 0;
+    for i in 0..(bit_width as usize) {
+        if get_bit(storage, i + bit_offset) {
+            let index = if cfg!(target_endian = "big") {
+                bit_width as usize - 1 - i
+            } else {
+                i
+            };
+            val |= 1 << index;
+        }
+    }
+    val
+}
+
+/// Write the low `bit_width` bits of `val` starting at `bit_offset`
+/// within the storage for some bitfields.
+#[inline]
+pub fn set(storage: &mut [u8], bit_offset: usize, bit_width: u8, val: u64) {
+    debug_assert!(bit_width <= 64);
+    debug_assert!((bit_offset + (bit_width as usize)) / 8 <= storage.len());
+    for i in 0..(bit_width as usize) {
+        let mask = 1 << i;
+        let index = if cfg!(target_endian = "big") {
+            bit_width as usize - 1 - i
+        } else {
+            i
+        };
+        set_bit(storage, index + bit_offset, val & mask == mask);
+    }
+}
@@ -0,0 +1,206 @@
+//! Support for C++ `std::chrono` types. autocxx converts a
+//! `std::chrono::duration` parameter or return value to a
+//! [`SignedDuration`], and a `std::chrono::time_point` to a
+//! [`TimePoint`]. Both are passed between Rust and C++ as a count of
+//! nanoseconds. Parameters accept a [`std::time::Duration`] too.
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A span of time obtained from, or to be passed to, a C++ API which uses a
+/// `std::chrono::duration`. Unlike [`Duration`], this may be negative, as
+/// C++ durations often are (for example, the difference between two time
+/// points). It holds a 64-bit count of nanoseconds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignedDuration {
+    nanos: i64,
+}
+
+impl SignedDuration {
+    /// Create a duration from a count of nanoseconds, which may be negative.
+    pub const fn from_nanos(nanos: i64) -> Self {
+        Self { nanos }
+    }
+
+    /// The number of nanoseconds in this duration, which may be negative.
+    pub const fn as_nanos(&self) -> i64 {
+        self.nanos
+    }
+
+    /// Whether this duration is less than zero.
+    pub const fn is_negative(&self) -> bool {
+        self.nanos < 0
+    }
+
+    /// The magnitude of this duration, ignoring its sign.
+    pub const fn unsigned_abs(&self) -> Duration {
+        Duration::from_nanos(self.nanos.unsigned_abs())
+    }
+}
+
+/// Converts a [`Duration`], saturating at the longest duration which can
+/// be represented, which is about 292 years.
+impl From<Duration> for SignedDuration {
+    fn from(duration: Duration) -> Self {
+        Self {
+            nanos: i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+impl TryFrom<SignedDuration> for Duration {
+    type Error = NegativeDurationError;
+
+    fn try_from(duration: SignedDuration) -> Result<Self, Self::Error> {
+        u64::try_from(duration.nanos)
+            .map(Duration::from_nanos)
+            .map_err(|_| NegativeDurationError(duration))
+    }
+}
+
+impl PartialEq<Duration> for SignedDuration {
+    fn eq(&self, other: &Duration) -> bool {
+        *self == SignedDuration::from(*other)
+    }
+}
+
+/// The error when converting a negative [`SignedDuration`] to a
+/// [`Duration`]. It holds the duration which couldn't be converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeDurationError(pub SignedDuration);
+
+impl std::fmt::Display for NegativeDurationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "duration of {}ns is negative so can't be a std::time::Duration",
+            self.0.nanos
+        )
+    }
+}
+
+impl std::error::Error for NegativeDurationError {}
+
+/// The C++ `std::chrono::system_clock`, for use as the clock of a
+/// [`TimePoint`].
+#[derive(Debug)]
+pub enum SystemClock {}
+
+/// The C++ `std::chrono::steady_clock`, for use as the clock of a
+/// [`TimePoint`].
+#[derive(Debug)]
+pub enum SteadyClock {}
+
+/// The C++ `std::chrono::high_resolution_clock`, for use as the clock of a
+/// [`TimePoint`]. In C++ this may be an alias for one of the other clocks,
+/// in which case the C++ API may instead be seen to use that clock.
+#[derive(Debug)]
+pub enum HighResolutionClock {}
+
+/// A point in time obtained from, or to be passed to, a C++ API which
+/// uses a `std::chrono::time_point`. C++ time points are relative to
+/// the epoch of some clock, which is given by `Clock`: one of
+/// [`SystemClock`], [`SteadyClock`] or [`HighResolutionClock`] for the
+/// standard clocks, or the generated type for any other C++ clock. So,
+/// you can't pass a time point from one clock to an API expecting another.
+pub struct TimePoint<Clock> {
+    nanos_since_epoch: i64,
+    clock: PhantomData<fn() -> Clock>,
+}
+
+impl<Clock> TimePoint<Clock> {
+    /// Create a time point which is the given duration after (or, if
+    /// negative, before) the epoch of its clock.
+    pub fn from_duration_since_epoch(duration: impl Into<SignedDuration>) -> Self {
+        Self::from_nanos(duration.into().nanos)
+    }
+
+    /// The time elapsed between the epoch of the clock and this time point.
+    /// This is negative if the time point is before the epoch.
+    pub fn duration_since_epoch(&self) -> SignedDuration {
+        SignedDuration::from_nanos(self.nanos_since_epoch)
+    }
+
+    const fn from_nanos(nanos_since_epoch: i64) -> Self {
+        Self {
+            nanos_since_epoch,
+            clock: PhantomData,
+        }
+    }
+}
+
+// These are implemented by hand, since deriving them would require the
+// clock to implement them too.
+
+impl<Clock> std::fmt::Debug for TimePoint<Clock> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimePoint")
+            .field("clock", &std::any::type_name::<Clock>())
+            .field("nanos_since_epoch", &self.nanos_since_epoch)
+            .finish()
+    }
+}
+
+impl<Clock> Clone for TimePoint<Clock> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Clock> Copy for TimePoint<Clock> {}
+
+impl<Clock> PartialEq for TimePoint<Clock> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos_since_epoch == other.nanos_since_epoch
+    }
+}
+
+impl<Clock> Eq for TimePoint<Clock> {}
+
+impl<Clock> PartialOrd for TimePoint<Clock> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Clock> Ord for TimePoint<Clock> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos_since_epoch.cmp(&other.nanos_since_epoch)
+    }
+}
+
+impl<Clock> Hash for TimePoint<Clock> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nanos_since_epoch.hash(state)
+    }
+}
+
+#[doc(hidden)]
+pub fn duration_to_nanos(duration: impl Into<SignedDuration>) -> i64 {
+    duration.into().nanos
+}
+
+#[doc(hidden)]
+pub fn duration_from_nanos(nanos: i64) -> SignedDuration {
+    SignedDuration::from_nanos(nanos)
+}
+
+#[doc(hidden)]
+pub fn time_point_to_nanos<Clock>(time_point: TimePoint<Clock>) -> i64 {
+    time_point.nanos_since_epoch
+}
+
+#[doc(hidden)]
+pub fn time_point_from_nanos<Clock>(nanos_since_epoch: i64) -> TimePoint<Clock> {
+    TimePoint::from_nanos(nanos_since_epoch)
+}
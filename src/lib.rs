@@ -16,12 +16,25 @@
 // do anything - all the magic is handled entirely by
 // autocxx_macro::include_cpp_impl.
 
+#[doc(hidden)]
+pub mod bitfield;
+pub mod chrono;
+pub mod map;
+mod ref_ptr;
 mod reference_wrapper;
 mod rvalue_param;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_support;
 pub mod subclass;
+mod unique_ptr_with_deleter;
+pub mod unwind;
 mod value_param;
 
+pub use map::{CxxMap, CxxSet, CxxUnorderedMap};
+pub use ref_ptr::{RefCounted, RefPtr};
 pub use reference_wrapper::{AsCppMutRef, AsCppRef, CppMutRef, CppPin, CppRef, CppUniquePtrPin};
+pub use unique_ptr_with_deleter::{Deleter, UniquePtrWithDeleter};
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// Include some C++ headers in your Rust project.
@@ -171,6 +184,61 @@ macro_rules! pod {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Generate a C++ enum as a set of bit flags, rather than as a Rust
+/// `enum`. Use this for enums whose values may be combined, e.g.
+/// using `operator|`: it's undefined behavior for a Rust `enum` to hold
+/// any value other than one of its variants.
+///
+/// The enum becomes a `#[repr(transparent)]` newtype around its
+/// underlying integer type, with an associated constant for each
+/// enumerator, and implementations of the bitwise operators.
+/// For example, `bitflags!("ns::Perms")`. This also acts as a
+/// [generate] directive for the enum.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! bitflags {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate a C++ enum such that it can safely hold values other than
+/// its enumerators. C++ code may return such values, and it's undefined
+/// behavior for a Rust `enum` to hold them.
+///
+/// The enum becomes a `#[repr(transparent)]` newtype around its
+/// underlying integer type, with an associated constant for each
+/// enumerator. A companion Rust `enum`, named with an `Enum` suffix,
+/// can be obtained using `TryFrom`, which fails for unknown values.
+/// For example, `non_exhaustive_enum!("ns::Color")`. This also acts
+/// as a [generate] directive for the enum.
+/// An enum can't be given to both this and [bitflags].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! non_exhaustive_enum {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Derive serde's `Serialize` and `Deserialize` for a POD struct or an
+/// enum, for example `derive_serde!("ns::Config")`. The same is done for
+/// any POD structs and enums used by its fields, and so on. All the
+/// fields must be serializable: pointers, for example, are not, nor are
+/// arrays of arrays longer than 32 elements. Arrays of any length are
+/// otherwise fine, and padding which the compiler adds is not serialized.
+///
+/// This requires the `serde` feature of `autocxx`, but your crate need not
+/// depend on serde itself. This does not act as a [generate] directive:
+/// you should also use [generate_pod] for the type.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! derive_serde {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Skip the normal generation of a `make_string` function
 /// and other utilities which we might generate normally.
 /// A directive to be included inside
@@ -278,6 +346,27 @@ macro_rules! safety {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Specifies what happens if Rust code called from C++ panics. This
+/// applies to Rust overrides of C++ virtual methods (see [`subclass`])
+/// and to functions and traits exported using
+/// [`extern_rust::extern_rust_function`] and [`extern_rust::extern_rust_trait`].
+/// A panic must never unwind into C++ frames, so autocxx wraps each such
+/// call in [`std::panic::catch_unwind`]. You can specify either:
+/// `panic_policy!(abort)`
+/// which is the default, and reports the panic message before aborting
+/// the process, or
+/// `panic_policy!(rethrow)`
+/// which converts the panic into a C++ exception (a `rust::Error`) thrown
+/// to the C++ caller. The latter requires your C++ code to be built with
+/// exceptions enabled, and means every such call from C++ may throw.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! panic_policy {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Whether to avoid generating [`cxx::UniquePtr`] and [`cxx::Vector`]
 /// implementations. This is primarily useful for reducing test cases and
 /// shouldn't be used in normal operation.
@@ -350,6 +439,28 @@ macro_rules! extern_cpp_opaque_type {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
+/// Declares that a C++ class template is a smart pointer which holds an
+/// intrusive reference count, such as Chromium's `scoped_refptr`. Such
+/// pointers are passed to and from Rust as a [`RefPtr`], which implements
+/// `Deref`, `Clone` and `Drop` by calling methods on the C++ types.
+///
+/// The syntax is:
+/// `smart_pointer!("base::scoped_refptr", deref = "get", clone = "AddRef", release = "Release")`
+///
+/// `deref` names the method on the smart pointer which returns a raw
+/// pointer, while `clone` and `release` name the methods on the pointee
+/// which add and release a reference. Each is optional, and defaults to
+/// the values shown. The smart pointer must be constructible from a raw
+/// pointer, adding a reference. A given pointee type can be used with only
+/// one such smart pointer.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! smart_pointer {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
 /// Deprecated - use [`extern_rust_type`] instead.
 #[macro_export]
 #[deprecated]
@@ -407,6 +518,8 @@ macro_rules! usage {
     };
 }
 
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
 use std::pin::Pin;
 
 #[doc(hidden)]
@@ -417,11 +530,14 @@ pub use autocxx_macro::cpp_semantics;
 
 macro_rules! ctype_wrapper {
     ($r:ident, $c:expr, $d:expr) => {
+        ctype_wrapper!($r, ::std::os::raw::$r, $c, $d);
+    };
+    ($r:ident, $t:ty, $c:expr, $d:expr) => {
         #[doc=$d]
         #[derive(Debug, Eq, Copy, Clone, PartialEq, Hash)]
         #[allow(non_camel_case_types)]
         #[repr(transparent)]
-        pub struct $r(pub ::std::os::raw::$r);
+        pub struct $r(pub $t);
 
         /// # Safety
         ///
@@ -432,13 +548,13 @@ macro_rules! ctype_wrapper {
             type Kind = cxx::kind::Trivial;
         }
 
-        impl From<::std::os::raw::$r> for $r {
-            fn from(val: ::std::os::raw::$r) -> Self {
+        impl From<$t> for $r {
+            fn from(val: $t) -> Self {
                 Self(val)
             }
         }
 
-        impl From<$r> for ::std::os::raw::$r {
+        impl From<$r> for $t {
             fn from(val: $r) -> Self {
                 val.0
             }
@@ -463,6 +579,18 @@ ctype_wrapper!(c_short, "c_short", "Newtype wrapper for an short");
 ctype_wrapper!(c_uint, "c_uint", "Newtype wrapper for an unsigned int");
 ctype_wrapper!(c_int, "c_int", "Newtype wrapper for an int");
 ctype_wrapper!(c_uchar, "c_uchar", "Newtype wrapper for an unsigned char");
+ctype_wrapper!(
+    c_uint128,
+    u128,
+    "c_uint128",
+    "Newtype wrapper for an unsigned __int128"
+);
+ctype_wrapper!(
+    c_int128,
+    i128,
+    "c_int128",
+    "Newtype wrapper for an __int128"
+);
 
 /// Newtype wrapper for a C void. Only useful as a `*c_void`
 #[allow(non_camel_case_types)]
@@ -492,6 +620,55 @@ unsafe impl cxx::ExternType for c_char16_t {
     type Kind = cxx::kind::Trivial;
 }
 
+/// A C++ `char32_t`
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+pub struct c_char32_t(pub u32);
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_char32_t {
+    type Id = cxx::type_id!(c_char32_t);
+    type Kind = cxx::kind::Trivial;
+}
+
+/// A C++ `char8_t`
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+pub struct c_char8_t(pub u8);
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_char8_t {
+    type Id = cxx::type_id!(c_char8_t);
+    type Kind = cxx::kind::Trivial;
+}
+
+/// A C++ `wchar_t`. This is 16 bits on Windows and 32 bits elsewhere.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[cfg(windows)]
+pub struct c_wchar_t(pub u16);
+
+/// A C++ `wchar_t`. This is 16 bits on Windows and 32 bits elsewhere.
+#[allow(non_camel_case_types)]
+#[repr(transparent)]
+#[cfg(not(windows))]
+pub struct c_wchar_t(pub u32);
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_wchar_t {
+    type Id = cxx::type_id!(c_wchar_t);
+    type Kind = cxx::kind::Trivial;
+}
+
 /// autocxx couldn't generate these bindings.
 /// If you come across a method, type or function which refers to this type,
 /// it indicates that autocxx couldn't generate that binding. A documentation
@@ -562,9 +739,69 @@ pub mod extern_rust {
     /// pub fn call_me_from_cpp() { }
     /// ```
     ///
+    /// # Supported types
+    ///
+    /// Parameters and return values may be:
+    /// * primitives, `String`, `&str`, slices such as `&[u8]` and `&mut [u8]`,
+    ///   `Box<T>` and `Vec<T>`, much as supported by `cxx`;
+    /// * C++ types from the same `include_cpp!`, by reference (`&T` or
+    ///   `Pin<&mut T>`). These may be named as `ffi::ns::T` or similar;
+    /// * `Option<Box<T>>`, which is passed to or from C++ as an owning
+    ///   pointer which may be null. C++ should use `rust::Box<T>::from_raw`
+    ///   to take ownership of a returned pointer. Functions taking such a
+    ///   parameter are `unsafe` to call from C++.
+    ///
+    /// Types may also be named by paths relative to this crate, such as
+    /// `crate::a::T` or `super::T`.
+    ///
+    /// # Location
+    ///
+    /// Functions (and methods in `impl` blocks for [`extern_rust_type`]s)
+    /// may be within nested modules, including modules in other files
+    /// (`mod foo;`), so long as they're visible (for instance, `pub(crate)`)
+    /// from the module containing `include_cpp!`. autocxx finds them
+    /// by following `mod` declarations from the file containing `include_cpp!`,
+    /// which should be the root of your crate.
+    ///
+    /// A function may also return `Result<T, E>` where `E` implements
+    /// [`std::fmt::Display`]; an error is thrown to C++ as a `rust::Error`
+    /// exception.
+    ///
     /// See [`extern_rust_type`] for details of how to find the generated
     /// declarations from C++.
     pub use autocxx_macro::extern_rust_function;
+
+    /// Declare that a Rust trait is to be exported to C++, such that C++
+    /// can call implementations of it written in Rust. This is used as an
+    /// attribute macro on a trait, for instance:
+    /// ```
+    /// # use autocxx_macro::extern_rust_trait as extern_rust_trait;
+    /// #[extern_rust_trait]
+    /// pub trait Plugin {
+    ///     fn name(&self) -> String;
+    ///     fn process(&mut self, input: &[u8]) -> u32;
+    /// }
+    /// ```
+    ///
+    /// For a trait `Plugin`, this generates:
+    /// * a Rust type `ffi::PluginHolder`, which owns a `Box<dyn Plugin>`.
+    ///   Create one using `ffi::PluginHolder::new(my_plugin)`, which returns
+    ///   a `Box<PluginHolder>` that can be passed to C++;
+    /// * a C++ abstract class `Plugin`, with a pure virtual method for each
+    ///   method of the trait. Methods taking `&self` are `const`;
+    /// * a C++ class `PluginCpp`, which implements `Plugin` by calling into
+    ///   a `rust::Box<PluginHolder>` passed to its constructor.
+    ///
+    /// The trait may not be generic, and may contain only methods which take
+    /// `&self` or `&mut self`. Their parameters and return types may be
+    /// anything supported by [`extern_rust_function`].
+    ///
+    /// The C++ classes are declared in the header generated by autocxx
+    /// (`autocxxgen_ffi.h` or similar), which can't be included in the headers
+    /// which autocxx itself reads. Those headers should instead forward-declare
+    /// `struct PluginHolder;` and accept a `rust::Box<PluginHolder>`, which
+    /// your C++ implementation can then wrap in a `PluginCpp`.
+    pub use autocxx_macro::extern_rust_trait;
 }
 
 /// Equivalent to [`std::convert::AsMut`], but returns a pinned mutable reference
@@ -583,6 +820,68 @@ pub trait WithinUniquePtr {
     fn within_unique_ptr(self) -> cxx::UniquePtr<Self::Inner>;
 }
 
+/// A type which can be moved from a [`cxx::UniquePtr`] into a
+/// [`cxx::SharedPtr`]. autocxx implements this for each C++ type it
+/// generates which has an accessible destructor, by calling the
+/// `std::shared_ptr` constructor in C++. Note that this allocates the
+/// `std::shared_ptr`'s control block separately from the object; to
+/// create a new object with a single allocation, use [`WithinSharedPtr`].
+pub trait UniqueToShared: UniquePtrTarget + SharedPtrTarget + Sized {
+    /// Transfer ownership of the object to a new [`cxx::SharedPtr`].
+    fn unique_to_shared(ptr: cxx::UniquePtr<Self>) -> cxx::SharedPtr<Self>;
+}
+
+/// Converts a [`cxx::UniquePtr`] into a [`cxx::SharedPtr`], as C++ allows
+/// a `std::unique_ptr` to be moved into a `std::shared_ptr`. Automatically
+/// imported by the autocxx prelude.
+pub trait IntoSharedPtr<T: UniqueToShared> {
+    /// Transfer ownership of the object to a new [`cxx::SharedPtr`].
+    fn into_shared_ptr(self) -> cxx::SharedPtr<T>;
+}
+
+impl<T: UniqueToShared> IntoSharedPtr<T> for UniquePtr<T> {
+    fn into_shared_ptr(self) -> cxx::SharedPtr<T> {
+        T::unique_to_shared(self)
+    }
+}
+
+/// Provides utility functions to create an object within a
+/// [`cxx::SharedPtr`]. Automatically imported by the autocxx prelude
+/// and implemented by the [`moveit::New`] returned by each autocxx-generated
+/// constructor for a type with an accessible destructor. The object is
+/// created in C++ using `std::make_shared`, so the object and the
+/// `std::shared_ptr`'s control block share a single allocation.
+pub trait WithinSharedPtr {
+    type Inner: SharedPtrTarget;
+    /// Create this item within a [`cxx::SharedPtr`].
+    fn within_shared_ptr(self) -> cxx::SharedPtr<Self::Inner>;
+}
+
+/// Copies a C++ object held in a [`cxx::UniquePtr`] into a new
+/// [`cxx::UniquePtr`], using its C++ copy constructor. [`cxx::UniquePtr`]
+/// can't implement [`Clone`] itself. Automatically imported by the autocxx
+/// prelude and implemented for any [`cxx::UniquePtr`] of a type which
+/// implements [`moveit::CopyNew`].
+pub trait CloneUniquePtr {
+    type Inner: UniquePtrTarget;
+    /// Copy the object into a new [`cxx::UniquePtr`]. If this is null, so
+    /// is the result.
+    fn clone(&self) -> cxx::UniquePtr<Self::Inner>;
+}
+
+impl<T> CloneUniquePtr for UniquePtr<T>
+where
+    T: UniquePtrTarget + CopyNew + MakeCppStorage,
+{
+    type Inner = T;
+    fn clone(&self) -> cxx::UniquePtr<T> {
+        match self.as_ref() {
+            Some(original) => UniquePtr::emplace(moveit::new::copy(original)),
+            None => UniquePtr::null(),
+        }
+    }
+}
+
 /// Provides utility functions to emplace any [`moveit::New`] into a
 /// [`Box`]. Automatically imported by the autocxx prelude
 /// and implemented by any (autocxx-related) [`moveit::New`].
@@ -603,6 +902,7 @@ pub trait WithinBox {
 
 use cxx::kind::Trivial;
 use cxx::ExternType;
+use moveit::CopyNew;
 use moveit::Emplace;
 use moveit::MakeCppStorage;
 
@@ -617,6 +917,68 @@ where
     }
 }
 
+/// Where an autocxx-generated constructor should create its object.
+#[doc(hidden)]
+pub enum ConstructionTarget<'a, T: SharedPtrTarget> {
+    /// Construct the object in place in this memory.
+    InPlace(Pin<&'a mut MaybeUninit<T>>),
+    /// Construct the object using `std::make_shared` and store the
+    /// resulting pointer here.
+    SharedPtr(&'a mut cxx::SharedPtr<T>),
+}
+
+/// The [`moveit::New`] returned by autocxx-generated constructors for types
+/// which can also be created using `std::make_shared`.
+#[doc(hidden)]
+pub struct CppConstructor<T, F> {
+    f: F,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, F> CppConstructor<T, F>
+where
+    T: SharedPtrTarget,
+    F: FnOnce(ConstructionTarget<'_, T>),
+{
+    /// Creates a new `CppConstructor` from a closure which constructs
+    /// the object wherever it's told to.
+    ///
+    /// # Safety
+    ///
+    /// `f` must initialize the memory given by [`ConstructionTarget::InPlace`],
+    /// as for [`moveit::new::by_raw`].
+    pub unsafe fn by_raw(f: F) -> Self {
+        Self {
+            f,
+            phantom: PhantomData,
+        }
+    }
+}
+
+unsafe impl<T, F> New for CppConstructor<T, F>
+where
+    T: SharedPtrTarget,
+    F: FnOnce(ConstructionTarget<'_, T>),
+{
+    type Output = T;
+    unsafe fn new(self, this: Pin<&mut MaybeUninit<T>>) {
+        (self.f)(ConstructionTarget::InPlace(this))
+    }
+}
+
+impl<T, F> WithinSharedPtr for CppConstructor<T, F>
+where
+    T: SharedPtrTarget,
+    F: FnOnce(ConstructionTarget<'_, T>),
+{
+    type Inner = T;
+    fn within_shared_ptr(self) -> cxx::SharedPtr<T> {
+        let mut ptr = cxx::SharedPtr::null();
+        (self.f)(ConstructionTarget::SharedPtr(&mut ptr));
+        ptr
+    }
+}
+
 impl<N, T> WithinBox for N
 where
     N: New<Output = T>,
@@ -650,6 +1012,26 @@ where
     }
 }
 
+/// Emulates the [`WithinSharedPtr`] trait, but for trivial (plain old data) types.
+/// This allows such types to behave identically if a type is changed from
+/// `generate!` to `generate_pod!`.
+///
+/// (Ideally, this would be the exact same trait as [`WithinSharedPtr`] but this runs
+/// the risk of conflicting implementations. Negative trait bounds would solve
+/// this!)
+pub trait WithinSharedPtrTrivial: SharedPtrTarget + Sized + Unpin {
+    fn within_shared_ptr(self) -> cxx::SharedPtr<Self>;
+}
+
+impl<T> WithinSharedPtrTrivial for T
+where
+    T: SharedPtrTarget + ExternType<Kind = Trivial> + Sized + Unpin,
+{
+    fn within_shared_ptr(self) -> cxx::SharedPtr<T> {
+        cxx::SharedPtr::new(self)
+    }
+}
+
 /// Emulates the [`WithinBox`] trait, but for trivial (plain old data) types.
 /// This allows such types to behave identically if a type is changed from
 /// `generate!` to `generate_pod!`.
@@ -670,6 +1052,7 @@ where
     }
 }
 
+use cxx::memory::SharedPtrTarget;
 use cxx::memory::UniquePtrTarget;
 use cxx::UniquePtr;
 use moveit::New;
@@ -681,17 +1064,43 @@ pub use value_param::as_new;
 pub use value_param::ValueParam;
 pub use value_param::ValueParamHandler;
 
+/// The reason a `std::variant` couldn't be converted to the Rust enum
+/// which autocxx generates for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantConversionError {
+    /// The `cxx::UniquePtr` holding the variant was null.
+    Null,
+    /// The variant held no value, because an exception was thrown
+    /// while its value was being replaced.
+    ValuelessByException,
+}
+
+impl std::fmt::Display for VariantConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariantConversionError::Null => write!(f, "the std::variant pointer was null"),
+            VariantConversionError::ValuelessByException => {
+                write!(f, "the std::variant was valueless by exception")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VariantConversionError {}
+
 /// Imports which you're likely to want to use.
 pub mod prelude {
     pub use crate::as_copy;
     pub use crate::as_mov;
     pub use crate::as_new;
     pub use crate::c_int;
+    pub use crate::c_int128;
     pub use crate::c_long;
     pub use crate::c_longlong;
     pub use crate::c_short;
     pub use crate::c_uchar;
     pub use crate::c_uint;
+    pub use crate::c_uint128;
     pub use crate::c_ulong;
     pub use crate::c_ulonglong;
     pub use crate::c_ushort;
@@ -700,15 +1109,24 @@ pub mod prelude {
     pub use crate::include_cpp;
     pub use crate::AsCppMutRef;
     pub use crate::AsCppRef;
+    pub use crate::CloneUniquePtr;
     pub use crate::CppMutRef;
     pub use crate::CppPin;
     pub use crate::CppRef;
     pub use crate::CppUniquePtrPin;
+    pub use crate::CxxMap;
+    pub use crate::CxxSet;
+    pub use crate::CxxUnorderedMap;
+    pub use crate::IntoSharedPtr;
     pub use crate::PinMut;
     pub use crate::RValueParam;
+    pub use crate::RefPtr;
+    pub use crate::UniquePtrWithDeleter;
     pub use crate::ValueParam;
     pub use crate::WithinBox;
     pub use crate::WithinBoxTrivial;
+    pub use crate::WithinSharedPtr;
+    pub use crate::WithinSharedPtrTrivial;
     pub use crate::WithinUniquePtr;
     pub use crate::WithinUniquePtrTrivial;
     pub use cxx::UniquePtr;
@@ -726,3 +1144,9 @@ pub use moveit;
 /// refers explicitly to ::cxx. See
 /// <https://github.com/google/autocxx/issues/36>
 pub use cxx;
+
+/// Re-export serde, with which code generated for [derive_serde]
+/// derives `Serialize` and `Deserialize`. This is only available with the
+/// `serde` feature.
+#[cfg(feature = "serde")]
+pub use serde;
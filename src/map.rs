@@ -0,0 +1,147 @@
+//! Support for C++ associative containers. Each instantiation of
+//! `std::map`, `std::unordered_map` or `std::set` becomes an opaque Rust
+//! type which implements [`CxxMap`] (and, for `std::unordered_map`,
+//! [`CxxUnorderedMap`]) or [`CxxSet`]. Each operation calls a small C++
+//! function generated for that instantiation.
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{marker::PhantomData, pin::Pin};
+
+/// A `std::map` or `std::unordered_map` with keys of type `K` and values
+/// of type `V`.
+///
+/// Keys and values which are plain old data are inserted by value; others
+/// are inserted as a [`cxx::UniquePtr`] whose contents are moved into the
+/// container. [`CxxMap::OwnedKey`] and [`CxxMap::OwnedValue`] say which.
+pub trait CxxMap<K, V> {
+    /// The type passed to [`CxxMap::insert`] to provide a key.
+    type OwnedKey;
+    /// The type passed to [`CxxMap::insert`] to provide a value.
+    type OwnedValue;
+
+    /// The number of entries in the map.
+    fn len(&self) -> usize;
+
+    /// Whether the map has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value corresponding to the given key, if any.
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// Whether the map contains the given key.
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Insert a value, replacing any existing value for the same key.
+    /// Returns whether the key was newly inserted.
+    fn insert(self: Pin<&mut Self>, key: Self::OwnedKey, value: Self::OwnedValue) -> bool;
+
+    /// Remove any entry with the given key. Returns whether there was one.
+    fn remove(self: Pin<&mut Self>, key: &K) -> bool;
+
+    /// Iterate over the entries of the map, in the order in which C++
+    /// holds them.
+    fn iter(&self) -> MapIter<'_, Self, K, V> {
+        MapIter {
+            map: self,
+            next: self.first_key(),
+            _value: PhantomData,
+        }
+    }
+
+    #[doc(hidden)]
+    fn first_key(&self) -> Option<&K>;
+
+    #[doc(hidden)]
+    fn next_key(&self, key: &K) -> Option<&K>;
+}
+
+/// A `std::unordered_map`. All its operations are provided by [`CxxMap`].
+pub trait CxxUnorderedMap<K, V>: CxxMap<K, V> {}
+
+/// A `std::set` with keys of type `K`.
+///
+/// As with [`CxxMap`], keys which aren't plain old data are inserted as a
+/// [`cxx::UniquePtr`].
+pub trait CxxSet<K> {
+    /// The type passed to [`CxxSet::insert`].
+    type OwnedKey;
+
+    /// The number of keys in the set.
+    fn len(&self) -> usize;
+
+    /// Whether the set has no keys.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the set contains the given key.
+    fn contains(&self, key: &K) -> bool;
+
+    /// Insert a key. Returns whether it was newly inserted.
+    fn insert(self: Pin<&mut Self>, key: Self::OwnedKey) -> bool;
+
+    /// Remove the given key. Returns whether it was present.
+    fn remove(self: Pin<&mut Self>, key: &K) -> bool;
+
+    /// Iterate over the keys in the set, in order.
+    fn iter(&self) -> SetIter<'_, Self, K> {
+        SetIter {
+            set: self,
+            next: self.first_key(),
+        }
+    }
+
+    #[doc(hidden)]
+    fn first_key(&self) -> Option<&K>;
+
+    #[doc(hidden)]
+    fn next_key(&self, key: &K) -> Option<&K>;
+}
+
+/// An iterator over the entries of a [`CxxMap`].
+pub struct MapIter<'a, M: CxxMap<K, V> + ?Sized, K, V> {
+    map: &'a M,
+    next: Option<&'a K>,
+    _value: PhantomData<&'a V>,
+}
+
+impl<'a, M: CxxMap<K, V> + ?Sized, K: 'a, V: 'a> Iterator for MapIter<'a, M, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.next?;
+        self.next = self.map.next_key(key);
+        let value = self
+            .map
+            .get(key)
+            .expect("key found during iteration is no longer present");
+        Some((key, value))
+    }
+}
+
+/// An iterator over the keys of a [`CxxSet`].
+pub struct SetIter<'a, S: CxxSet<K> + ?Sized, K> {
+    set: &'a S,
+    next: Option<&'a K>,
+}
+
+impl<'a, S: CxxSet<K> + ?Sized, K: 'a> Iterator for SetIter<'a, S, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.next?;
+        self.next = self.set.next_key(key);
+        Some(key)
+    }
+}
@@ -0,0 +1,126 @@
+//! Support for custom C++ smart pointers which manage an intrusive
+//! reference count, such as Chromium's `scoped_refptr`. Such pointers are
+//! declared using the [`crate::smart_pointer`] directive, and are passed
+//! to and from Rust as a [`RefPtr`].
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{fmt::Debug, ops::Deref, ptr};
+
+/// A C++ type with an intrusive reference count. autocxx implements this
+/// for the pointee of each smart pointer declared using
+/// [`crate::smart_pointer`], by calling the methods named there.
+///
+/// # Safety
+///
+/// Implementations must add and release references such that the object
+/// remains alive while any reference is outstanding.
+pub unsafe trait RefCounted {
+    /// Add a reference to the object.
+    ///
+    /// # Safety
+    ///
+    /// `this` must point to a live object.
+    unsafe fn add_ref(this: *const Self);
+
+    /// Release a reference to the object, which may destroy it.
+    ///
+    /// # Safety
+    ///
+    /// `this` must point to a live object, and the caller must own the
+    /// reference being released.
+    unsafe fn release(this: *const Self);
+}
+
+/// An owned reference to a C++ object with an intrusive reference count.
+/// This is how a C++ smart pointer declared using [`crate::smart_pointer`]
+/// appears in Rust. Like the C++ smart pointer, it may be null.
+///
+/// Cloning a `RefPtr` adds a reference, and dropping one releases it.
+/// Only shared access to the pointee is possible, since other references
+/// may exist.
+pub struct RefPtr<T: RefCounted> {
+    ptr: *mut T,
+}
+
+impl<T: RefCounted> RefPtr<T> {
+    /// A null pointer.
+    pub fn null() -> Self {
+        Self {
+            ptr: ptr::null_mut(),
+        }
+    }
+
+    /// Take ownership of a reference to the given object, which may be null.
+    ///
+    /// # Safety
+    ///
+    /// If non-null, `ptr` must point to a live object, and the caller must
+    /// own a reference to it which is transferred to the `RefPtr`.
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self { ptr }
+    }
+
+    /// Give up ownership of the reference, returning the raw pointer.
+    /// The caller becomes responsible for releasing the reference.
+    pub fn into_raw(self) -> *mut T {
+        let ptr = self.ptr;
+        std::mem::forget(self);
+        ptr
+    }
+
+    /// Whether this is null.
+    pub fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
+    /// A reference to the object, or `None` if this is null.
+    pub fn as_ref(&self) -> Option<&T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// The raw pointer, without affecting the reference count.
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+}
+
+impl<T: RefCounted> Clone for RefPtr<T> {
+    fn clone(&self) -> Self {
+        if !self.ptr.is_null() {
+            unsafe { T::add_ref(self.ptr) };
+        }
+        Self { ptr: self.ptr }
+    }
+}
+
+impl<T: RefCounted> Drop for RefPtr<T> {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            unsafe { T::release(self.ptr) };
+        }
+    }
+}
+
+impl<T: RefCounted> Deref for RefPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.as_ref() {
+            Some(target) => target,
+            None => panic!("called deref on a null RefPtr"),
+        }
+    }
+}
+
+impl<T: RefCounted> Debug for RefPtr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RefPtr").field(&self.ptr).finish()
+    }
+}
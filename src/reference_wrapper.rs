@@ -12,6 +12,7 @@ use core::{marker::PhantomData, ops::Deref, pin::Pin};
 use std::{marker::Unsize, ops::DispatchFromDyn};
 
 use cxx::{memory::UniquePtrTarget, UniquePtr};
+use moveit::{new::copy, CopyNew, Emplace};
 
 /// A C++ const reference. These are different from Rust's `&T` in that
 /// these may exist even while the object is mutated elsewhere. See also
@@ -481,6 +482,17 @@ impl<T: ?Sized> CppPin<T> {
     }
 }
 
+/// Cloning a `CppPin` makes a new object in a new `CppPin` using the C++
+/// copy constructor.
+impl<T: CopyNew> Clone for CppPin<T> {
+    fn clone(&self) -> Self {
+        // Safety: the Rust reference only exists while the copy constructor
+        // runs, which itself takes a C++ const reference to the object.
+        let original = unsafe { self.as_ref() };
+        Self::from_pinned_box(Box::emplace(copy(original)))
+    }
+}
+
 impl<T: ?Sized> AsCppRef<T> for CppPin<T> {
     fn as_cpp_ref(&self) -> CppRef<T> {
         CppRef::from_ptr(self.as_ptr())
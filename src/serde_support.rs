@@ -0,0 +1,74 @@
+//! Helpers used by the serde implementations which autocxx derives for
+//! types given to `derive_serde!`. These are not intended to be used
+//! directly.
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// The value of a padding field which bindgen added to a struct, and which
+/// isn't serialized.
+pub fn zeroed_padding<const N: usize>() -> [u8; N] {
+    [0; N]
+}
+
+/// Serializes and deserializes arrays of any length, for use with
+/// `#[serde(with = ...)]`. serde itself only supports arrays of up to 32
+/// elements. Arrays are represented in the same way as serde represents
+/// shorter arrays, i.e. as tuples.
+pub mod array {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for element in array {
+            tuple.serialize_element(element)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+    }
+
+    struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for ArrayVisitor<T, N> {
+        type Value = [T; N];
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "an array of length {N}")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut elements = Vec::with_capacity(N);
+            for i in 0..N {
+                match seq.next_element()? {
+                    Some(element) => elements.push(element),
+                    None => return Err(A::Error::invalid_length(i, &self)),
+                }
+            }
+            // We've checked the length, so this can't fail.
+            elements
+                .try_into()
+                .map_err(|_| A::Error::invalid_length(N, &self))
+        }
+    }
+}
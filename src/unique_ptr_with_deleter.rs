@@ -0,0 +1,129 @@
+//! Support for `std::unique_ptr`s with custom deleters, which cxx's
+//! [`cxx::UniquePtr`] can't represent. These are passed to and from Rust
+//! as a [`UniquePtrWithDeleter`].
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{fmt::Debug, marker::PhantomData, ops::Deref, pin::Pin, ptr};
+
+/// A stateless C++ deleter for objects of type `T`. autocxx implements
+/// this for the deleter of each `std::unique_ptr<T, D>` it encounters,
+/// by calling the C++ deleter.
+///
+/// # Safety
+///
+/// Implementations must destroy the object in the way expected by
+/// whichever C++ code allocated it.
+pub unsafe trait Deleter<T> {
+    /// Destroy the object.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a live object which the caller owns, and which
+    /// was allocated in a way compatible with this deleter.
+    unsafe fn delete(ptr: *mut T);
+}
+
+/// An owned C++ object which is destroyed using the deleter `D`. This is
+/// how a `std::unique_ptr<T, D>` appears in Rust, for a deleter other than
+/// the default. Like the C++ type, it may be null.
+pub struct UniquePtrWithDeleter<T, D: Deleter<T>> {
+    ptr: *mut T,
+    _deleter: PhantomData<D>,
+}
+
+impl<T, D: Deleter<T>> UniquePtrWithDeleter<T, D> {
+    /// A null pointer.
+    pub fn null() -> Self {
+        Self {
+            ptr: ptr::null_mut(),
+            _deleter: PhantomData,
+        }
+    }
+
+    /// Take ownership of the given object, which may be null.
+    ///
+    /// # Safety
+    ///
+    /// If non-null, `ptr` must point to a live object owned by the caller,
+    /// which can be destroyed using `D`.
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self {
+            ptr,
+            _deleter: PhantomData,
+        }
+    }
+
+    /// Give up ownership of the object, returning the raw pointer.
+    /// The caller becomes responsible for destroying it.
+    pub fn into_raw(self) -> *mut T {
+        let ptr = self.ptr;
+        std::mem::forget(self);
+        ptr
+    }
+
+    /// Whether this is null.
+    pub fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
+    /// A reference to the object, or `None` if this is null.
+    pub fn as_ref(&self) -> Option<&T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// A pinned mutable reference to the object, or `None` if this is null.
+    pub fn as_mut(&mut self) -> Option<Pin<&mut T>> {
+        unsafe { self.ptr.as_mut().map(|target| Pin::new_unchecked(target)) }
+    }
+
+    /// A pinned mutable reference to the object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is null.
+    pub fn pin_mut(&mut self) -> Pin<&mut T> {
+        match self.as_mut() {
+            Some(target) => target,
+            None => panic!("called pin_mut on a null UniquePtrWithDeleter"),
+        }
+    }
+
+    /// The raw pointer, retaining ownership.
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+}
+
+impl<T, D: Deleter<T>> Drop for UniquePtrWithDeleter<T, D> {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            unsafe { D::delete(self.ptr) };
+        }
+    }
+}
+
+impl<T, D: Deleter<T>> Deref for UniquePtrWithDeleter<T, D> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.as_ref() {
+            Some(target) => target,
+            None => panic!("called deref on a null UniquePtrWithDeleter"),
+        }
+    }
+}
+
+impl<T, D: Deleter<T>> Debug for UniquePtrWithDeleter<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("UniquePtrWithDeleter")
+            .field(&self.ptr)
+            .finish()
+    }
+}
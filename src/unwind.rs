@@ -0,0 +1,91 @@
+//! Support for stopping Rust panics from unwinding into C++ frames.
+//! This is used by code generated for Rust subclasses of C++ classes
+//! and for `extern_rust_function`s; see [`crate::panic_policy`].
+
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{
+    any::Any,
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// A Rust panic which was caught before it could unwind into C++.
+/// If the `panic_policy!(rethrow)` directive is used, this is
+/// converted into a C++ exception (a `rust::Error`) whose `what()`
+/// contains this message.
+#[derive(Debug, Clone)]
+pub struct RustPanic(String);
+
+impl RustPanic {
+    fn new(context: &str, payload: Box<dyn Any + Send>) -> Self {
+        Self(format!("{context} panicked: {}", panic_message(&*payload)))
+    }
+
+    /// The message describing the panic.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RustPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RustPanic {}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&'static str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+/// Run some Rust code which has been called from C++. If it panics,
+/// report the panic and abort the process rather than allowing
+/// the panic to unwind into C++ code.
+#[doc(hidden)]
+pub fn abort_on_panic<R>(context: &str, f: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => r,
+        Err(payload) => {
+            eprintln!(
+                "{}; aborting rather than unwinding into C++",
+                RustPanic::new(context, payload)
+            );
+            std::process::abort()
+        }
+    }
+}
+
+/// Run some Rust code which has been called from C++. If it panics,
+/// return the panic as an error so that it can be thrown as a C++
+/// exception.
+#[doc(hidden)]
+pub fn catch_panic<R>(context: &str, f: impl FnOnce() -> R) -> Result<R, RustPanic> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| RustPanic::new(context, payload))
+}
+
+/// Like [`catch_panic`], but for code which itself returns a `Result`.
+/// Either a panic or an error is returned as an error message so that it
+/// can be thrown as a C++ exception.
+#[doc(hidden)]
+pub fn catch_panic_or_error<T, E: Display>(
+    context: &str,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, String> {
+    catch_panic(context, f)
+        .map_err(|panic| panic.to_string())
+        .and_then(|result| result.map_err(|e| e.to_string()))
+}